@@ -0,0 +1,53 @@
+//! encoding of the sign docs of a tx with 10 `MsgSend`: the amino canonical json and the
+//! protobuf `SignDoc`, `cargo bench --bench encoding`
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use cro_sign_tool::hd_wallet::mnemonic::Mnemonic;
+use cro_sign_tool::key_service::private_key_service::PrivateKeyService;
+use cro_sign_tool::proto::cosmos::base::v1beta1::Coin;
+use cro_sign_tool::tx_builder::grpc::TxBuilder;
+use cro_sign_tool::utils::codec::canonical_json;
+use tokio::runtime::Runtime;
+
+const WORDS: &str = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+
+fn bench_encoding(c: &mut Criterion) {
+    let mnemonic = Mnemonic::from_str(WORDS, None).unwrap();
+    let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+    let mut runtime = Runtime::new().unwrap();
+    let mut builder = TxBuilder::new(key_service, "test".parse().unwrap(), None, 0, None);
+    for amount in 1..=10 {
+        let amount = Coin {
+            denom: "basecro".to_string(),
+            amount: amount.to_string(),
+        };
+        let msg = runtime
+            .block_on(
+                builder.create_msg("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd".into(), amount),
+            )
+            .unwrap();
+        builder.add_message(msg);
+    }
+    builder.set_account_number(1).set_sequence(2);
+    let sign_doc: serde_json::Value =
+        serde_json::from_slice(&builder.amino_sign_doc().unwrap()).unwrap();
+
+    c.bench_function("amino canonical json", |b| {
+        b.iter(|| canonical_json(&sign_doc))
+    });
+    c.bench_function("amino sign doc", |b| {
+        b.iter(|| builder.amino_sign_doc().unwrap())
+    });
+    c.bench_function("protobuf tx body", |b| {
+        b.iter(|| builder.raw_tx_body().unwrap())
+    });
+    c.bench_function("protobuf sign doc", |b| {
+        b.iter(|| runtime.block_on(builder.sign_bytes()).unwrap())
+    });
+    c.bench_function("protobuf signed tx", |b| {
+        b.iter(|| runtime.block_on(builder.build_bytes()).unwrap())
+    });
+}
+
+criterion_group!(benches, bench_encoding);
+criterion_main!(benches);
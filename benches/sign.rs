@@ -0,0 +1,42 @@
+//! signing and public key derivation with the shared secp256k1 context, against a new
+//! context per call as before: `cargo bench --bench sign`
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use cro_sign_tool::hd_wallet::mnemonic::Mnemonic;
+use cro_sign_tool::key_service::private_key_service::PrivateKeyService;
+use cro_sign_tool::key_service::KeyService;
+use cro_sign_tool::types::key::PublicKey;
+use secp256k1::{Message, Secp256k1};
+use tokio::runtime::Runtime;
+
+const WORDS: &str = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+
+fn bench_sign(c: &mut Criterion) {
+    let mnemonic = Mnemonic::from_str(WORDS, None).unwrap();
+    let private_key = mnemonic.private_key().unwrap();
+    let key_service = PrivateKeyService::new(private_key.clone());
+    let mut runtime = Runtime::new().unwrap();
+    let msg = [7u8; 32];
+
+    c.bench_function("sign, shared context", |b| {
+        b.iter(|| runtime.block_on(key_service.sign(&msg)).unwrap())
+    });
+    c.bench_function("sign, new context per call", |b| {
+        b.iter(|| {
+            let message = Message::from_slice(&msg).unwrap();
+            Secp256k1::signing_only().sign(&message, private_key.as_ref())
+        })
+    });
+    c.bench_function("address, shared context", |b| {
+        b.iter(|| runtime.block_on(key_service.address()).unwrap())
+    });
+    c.bench_function("public key, new context per call", |b| {
+        b.iter(|| secp256k1::PublicKey::from_secret_key(&Secp256k1::new(), private_key.as_ref()))
+    });
+    c.bench_function("public key, shared context", |b| {
+        b.iter(|| PublicKey::from(&private_key))
+    });
+}
+
+criterion_group!(benches, bench_sign);
+criterion_main!(benches);
@@ -0,0 +1,40 @@
+//! mnemonic parsing, key derivation and address computation: `cargo bench --bench wallet`
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use cro_sign_tool::hd_wallet::mnemonic::Mnemonic;
+use cro_sign_tool::types::address::ChainConfig;
+use cro_sign_tool::types::key::PublicKey;
+
+const WORDS: &str = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+
+fn bench_wallet(c: &mut Criterion) {
+    let mnemonic = Mnemonic::from_str(WORDS, None).unwrap();
+    let public_key = PublicKey::from(&mnemonic.private_key().unwrap());
+    let address = public_key.address().unwrap();
+    let chain_config = ChainConfig::crypto_org();
+
+    c.bench_function("mnemonic from words", |b| {
+        b.iter(|| Mnemonic::from_str(WORDS, None).unwrap())
+    });
+    c.bench_function("derive private key", |b| {
+        b.iter(|| mnemonic.derive(0, 0, 0).unwrap())
+    });
+    c.bench_function("derive 100 private keys", |b| {
+        b.iter(|| {
+            mnemonic
+                .derive_range(0..100)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        })
+    });
+    c.bench_function("address of public key", |b| {
+        b.iter(|| public_key.address().unwrap())
+    });
+    c.bench_function("bech32 account address", |b| {
+        b.iter(|| chain_config.account_address(&address))
+    });
+}
+
+criterion_group!(benches, bench_wallet);
+criterion_main!(benches);
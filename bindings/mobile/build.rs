@@ -0,0 +1,3 @@
+fn main() {
+    uniffi_build::generate_scaffolding("./src/chainlib.udl").unwrap();
+}
@@ -0,0 +1,299 @@
+//! Kotlin and Swift bindings of the key derivation, the amino transfer signing and the
+//! broadcast, generated by uniffi from `chainlib.udl`. The async calls of the crate are run
+//! to completion on a tokio runtime, call them off the ui thread
+
+use cro_sign_tool::client::Client as InnerClient;
+use cro_sign_tool::error::Error;
+use cro_sign_tool::hd_wallet::mnemonic::Mnemonic;
+use cro_sign_tool::key_service::private_key_service::PrivateKeyService;
+use cro_sign_tool::key_service::KeyService;
+use cro_sign_tool::message::amino::Transfer;
+use cro_sign_tool::tx_builder::amino::TxBuilder;
+use cro_sign_tool::types::address::{AccountAddress, ChainConfig};
+use cro_sign_tool::types::basic::{Amount, SyncMode};
+use cro_sign_tool::types::transaction::Transaction;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::runtime::Runtime;
+
+/// the errors of the bindings, the message is the error of the crate
+#[derive(Debug, thiserror::Error)]
+pub enum ChainlibError {
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    #[error("signing error: {0}")]
+    Signing(String),
+    #[error("network error: {0}")]
+    Network(String),
+}
+
+impl From<Error> for ChainlibError {
+    fn from(e: Error) -> Self {
+        let message = e.to_string();
+        match e {
+            Error::RequestError(_)
+            | Error::ClientError(_)
+            | Error::HttpStatus { .. }
+            | Error::GrpcStatus { .. }
+            | Error::ConnectError(_)
+            | Error::BroadcastError(_) => ChainlibError::Network(message),
+            Error::SecpError(_)
+            | Error::SigningUnavailable
+            | Error::KeystoreError(_)
+            | Error::LedgerError(_)
+            | Error::LedgerTimeout(_) => ChainlibError::Signing(message),
+            _ => ChainlibError::InvalidInput(message),
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, ChainlibError>;
+
+fn block_on<F: Future>(runtime: &Mutex<Runtime>, future: F) -> F::Output {
+    runtime
+        .lock()
+        .expect("runtime lock poisoned")
+        .block_on(future)
+}
+
+fn new_runtime() -> Result<Mutex<Runtime>> {
+    let runtime = Runtime::new().map_err(|e| ChainlibError::InvalidInput(e.to_string()))?;
+    Ok(Mutex::new(runtime))
+}
+
+/// new mnemonic words of 12, 15, 18, 21 or 24 words
+fn generate_mnemonic(word_count: u32) -> Result<String> {
+    let mnemonic = Mnemonic::new(word_count, None).map_err(Error::from)?;
+    Ok(mnemonic.phrase().to_string())
+}
+
+pub struct TransferRequest {
+    pub chain_id: String,
+    pub to_address: String,
+    /// e.g. `1.5cro` or `150000000basecro`
+    pub amount: String,
+    pub fee: Option<String>,
+    pub gas: Option<u64>,
+    pub memo: Option<String>,
+    pub account_number: u64,
+    pub sequence: u64,
+}
+
+/// the address prefix and the key derivation of the chain, the crypto.org chain by default
+pub struct ChainSettings {
+    /// e.g. `cro` or `tcro`
+    pub account_prefix: String,
+    /// bip44 coin type, e.g. 394
+    pub coin_type: u32,
+    /// e.g. `basecro`
+    pub base_denom: String,
+}
+
+impl ChainSettings {
+    fn chain_config(&self) -> ChainConfig {
+        ChainConfig::new(&self.account_prefix, self.coin_type, &self.base_denom)
+    }
+}
+
+pub struct AccountInfo {
+    pub account_number: u64,
+    pub sequence: u64,
+}
+
+/// the key of a mnemonic, kept in the memory of the app
+pub struct Wallet {
+    key_service: PrivateKeyService,
+    chain_config: ChainConfig,
+    runtime: Mutex<Runtime>,
+}
+
+impl Wallet {
+    /// the key of `hd_path`, `m/44'/{coin_type}'/0'/0/0` of the chain by default
+    fn new(
+        words: String,
+        password: Option<String>,
+        hd_path: Option<String>,
+        chain: Option<ChainSettings>,
+    ) -> Result<Self> {
+        let chain_config = chain.map(|chain| chain.chain_config()).unwrap_or_default();
+        let mnemonic = Mnemonic::from_str(&words, password).map_err(Error::from)?;
+        let private_key = match hd_path {
+            Some(hd_path) => mnemonic.private_key_with_path(&hd_path),
+            None => mnemonic.derive_with_config(&chain_config, 0, 0, 0),
+        }
+        .map_err(Error::from)?;
+        Ok(Self {
+            key_service: PrivateKeyService::new(private_key),
+            chain_config,
+            runtime: new_runtime()?,
+        })
+    }
+
+    /// the bech32 account address, e.g. `cro1...`
+    fn address(&self, prefix: String) -> Result<String> {
+        let address = block_on(&self.runtime, self.key_service.address())?;
+        Ok(AccountAddress::new(&prefix, address).to_string())
+    }
+
+    /// the base64 compressed public key
+    fn public_key(&self) -> Result<String> {
+        let public_key = block_on(&self.runtime, self.key_service.public_key())?;
+        Ok(public_key.to_string())
+    }
+
+    /// the signed tx json which is passed to `Client.broadcast`
+    fn sign_transfer(&self, request: TransferRequest) -> Result<String> {
+        let fee = request.fee.map(|fee| fee.parse::<Amount>()).transpose()?;
+        let to_address = self
+            .chain_config
+            .parse_account_address(&request.to_address)?;
+        let amount: Amount = request.amount.parse()?;
+        let mut builder = TxBuilder::new(
+            self.key_service.clone(),
+            request.chain_id.parse()?,
+            request.memo,
+            fee,
+            request.gas,
+        );
+        let tx = block_on(&self.runtime, async {
+            let from_address = self.key_service.address().await?;
+            builder
+                .add_message(Transfer::new_with_config(
+                    &self.chain_config,
+                    from_address,
+                    to_address,
+                    amount,
+                ))
+                .set_account_number(request.account_number)
+                .set_sequence(request.sequence);
+            builder.build(SyncMode::Sync).await
+        })?;
+        let json = serde_json::to_string(&tx).map_err(|e| Error::SerializeError(e.to_string()))?;
+        Ok(json)
+    }
+}
+
+/// rest client of a node
+pub struct Client {
+    inner: InnerClient,
+    runtime: Mutex<Runtime>,
+}
+
+impl Client {
+    fn new(base_api_url: String) -> Result<Self> {
+        Ok(Self {
+            inner: InnerClient::try_new(base_api_url)?,
+            runtime: new_runtime()?,
+        })
+    }
+
+    fn get_account_info(&self, address: String) -> Result<AccountInfo> {
+        let (account_number, sequence) =
+            block_on(&self.runtime, self.inner.get_account_info(&address))?;
+        Ok(AccountInfo {
+            account_number,
+            sequence,
+        })
+    }
+
+    /// broadcast the json of `Wallet.sign_transfer`, return the tx hash
+    fn broadcast(&self, signed_tx: String) -> Result<String> {
+        let tx: Transaction<serde_json::Value> =
+            serde_json::from_str(&signed_tx).map_err(|e| Error::SerializeError(e.to_string()))?;
+        Ok(block_on(&self.runtime, self.inner.broadcast_amino_tx(tx))?)
+    }
+}
+
+uniffi_macros::include_scaffolding!("chainlib");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const WORDS: &str = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+
+    fn transfer(to_address: &str) -> TransferRequest {
+        TransferRequest {
+            chain_id: "test".to_string(),
+            to_address: to_address.to_string(),
+            amount: "1cro".to_string(),
+            fee: Some("100000basecro".to_string()),
+            gas: Some(200_000),
+            memo: None,
+            account_number: 9,
+            sequence: 4,
+        }
+    }
+
+    #[test]
+    fn test_wallet() {
+        let wallet = Wallet::new(WORDS.to_string(), None, None, None).unwrap();
+        assert_eq!(
+            wallet.address("cro".to_string()).unwrap(),
+            "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf"
+        );
+        let json = wallet
+            .sign_transfer(transfer("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd"))
+            .unwrap();
+        let tx: Transaction<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(tx.tx.signatures.len(), 1);
+        assert_eq!(tx.tx.signatures[0].sequence, 4);
+        assert_eq!(
+            tx.tx.messages[0]["value"]["from_address"],
+            "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf"
+        );
+
+        assert!(matches!(
+            wallet.sign_transfer(transfer("tcro1s2gsnugjhpzac8m7necv3527jp28z9w0pl6eju")),
+            Err(ChainlibError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            Wallet::new("dune car".to_string(), None, None, None),
+            Err(ChainlibError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_wallet_chain_settings() {
+        let chain = ChainSettings {
+            account_prefix: "cosmos".to_string(),
+            coin_type: 118,
+            base_denom: "uatom".to_string(),
+        };
+        let wallet = Wallet::new(WORDS.to_string(), None, None, Some(chain)).unwrap();
+        let same_path = Wallet::new(
+            WORDS.to_string(),
+            None,
+            Some("m/44'/118'/0'/0/0".to_string()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            wallet.public_key().unwrap(),
+            same_path.public_key().unwrap()
+        );
+
+        // the recipient and the sender have the prefix of the chain
+        let to_address = wallet.address("cosmos".to_string()).unwrap();
+        let json = wallet.sign_transfer(transfer(&to_address)).unwrap();
+        let tx: Transaction<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(tx.tx.messages[0]["value"]["from_address"], to_address);
+        assert!(wallet
+            .sign_transfer(transfer("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_client_error() {
+        // nothing listens on the port 1 of localhost
+        let client = Client::new("http://127.0.0.1:1".to_string()).unwrap();
+        assert!(matches!(
+            client.get_account_info("cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf".to_string()),
+            Err(ChainlibError::Network(_))
+        ));
+        assert!(matches!(
+            client.broadcast("{}".to_string()),
+            Err(ChainlibError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+//! Python bindings of the mnemonic, the software key service, the amino tx builder and the
+//! client, so the transfers can be scripted without re-implementing the amino json
+
+use cro_sign_tool::client::Client as InnerClient;
+use cro_sign_tool::hd_wallet::mnemonic::Mnemonic as InnerMnemonic;
+use cro_sign_tool::key_service::private_key_service::PrivateKeyService as InnerKeyService;
+use cro_sign_tool::key_service::KeyService;
+use cro_sign_tool::message::amino::Transfer;
+use cro_sign_tool::tx_builder::amino::TxBuilder as InnerTxBuilder;
+use cro_sign_tool::types::address::{AccountAddress, ChainConfig};
+use cro_sign_tool::types::basic::{Amount, SyncMode};
+use cro_sign_tool::types::chain_id::ChainId;
+use cro_sign_tool::types::transaction::Transaction as InnerTransaction;
+use once_cell::sync::OnceCell;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::runtime::Runtime;
+
+/// the runtime of all the calls, created by the first one
+static RUNTIME: OnceCell<Mutex<Runtime>> = OnceCell::new();
+
+fn py_err<E: std::fmt::Display>(e: E) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// run the future of a call on the shared runtime
+fn block_on<F: Future>(future: F) -> PyResult<F::Output> {
+    let runtime = RUNTIME.get_or_try_init(|| Runtime::new().map(Mutex::new).map_err(py_err))?;
+    let mut runtime = runtime
+        .lock()
+        .map_err(|_e| PyValueError::new_err("the runtime lock is poisoned"))?;
+    Ok(runtime.block_on(future))
+}
+
+#[pyclass]
+struct Mnemonic {
+    inner: InnerMnemonic,
+}
+
+#[pymethods]
+impl Mnemonic {
+    /// parse the words, or generate new words of `word_count` if `words` is None
+    #[new]
+    #[args(words = "None", password = "None", word_count = "24")]
+    fn new(words: Option<&str>, password: Option<String>, word_count: u32) -> PyResult<Self> {
+        let inner = match words {
+            Some(words) => InnerMnemonic::from_str(words, password),
+            None => InnerMnemonic::new(word_count, password),
+        }
+        .map_err(py_err)?;
+        Ok(Self { inner })
+    }
+
+    fn phrase(&self) -> String {
+        self.inner.phrase().to_string()
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+struct PrivateKeyService {
+    inner: InnerKeyService,
+}
+
+#[pymethods]
+impl PrivateKeyService {
+    /// the key of `hd_path` of the mnemonic, `m/44'/394'/0'/0/0` by default
+    #[staticmethod]
+    #[args(hd_path = "None")]
+    fn from_mnemonic(mnemonic: &Mnemonic, hd_path: Option<&str>) -> PyResult<Self> {
+        let private_key = match hd_path {
+            Some(hd_path) => mnemonic.inner.private_key_with_path(hd_path),
+            None => mnemonic.inner.private_key(),
+        }
+        .map_err(py_err)?;
+        Ok(Self {
+            inner: InnerKeyService::new(private_key),
+        })
+    }
+
+    #[staticmethod]
+    fn from_hex(private_key_hex: &str) -> PyResult<Self> {
+        Ok(Self {
+            inner: InnerKeyService::from_hex(private_key_hex).map_err(py_err)?,
+        })
+    }
+
+    /// the bech32 account address, `cro1...` by default
+    #[args(prefix = "\"cro\"")]
+    fn address(&self, prefix: &str) -> PyResult<String> {
+        let address = block_on(self.inner.address())?.map_err(py_err)?;
+        Ok(AccountAddress::new(prefix, address).to_string())
+    }
+
+    /// the base64 compressed public key
+    fn public_key(&self) -> PyResult<String> {
+        Ok(block_on(self.inner.public_key())?
+            .map_err(py_err)?
+            .to_string())
+    }
+}
+
+/// the signed amino tx
+#[pyclass]
+struct Transaction {
+    inner: InnerTransaction<Transfer>,
+}
+
+#[pymethods]
+impl Transaction {
+    /// the json body of the `/txs` request
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner).map_err(py_err)
+    }
+}
+
+/// amino tx builder of the transfers
+#[pyclass]
+struct TxBuilder {
+    inner: InnerTxBuilder<InnerKeyService, Transfer>,
+    chain_config: ChainConfig,
+}
+
+#[pymethods]
+impl TxBuilder {
+    /// `fee` is an amount like `0.001cro` or `100000basecro`
+    #[new]
+    #[args(memo = "None", fee = "None", gas = "None")]
+    fn new(
+        key_service: &PrivateKeyService,
+        chain_id: String,
+        memo: Option<String>,
+        fee: Option<&str>,
+        gas: Option<u64>,
+    ) -> PyResult<Self> {
+        let fee = fee
+            .map(|fee| fee.parse::<Amount>())
+            .transpose()
+            .map_err(py_err)?;
+        let chain_id = chain_id.parse::<ChainId>().map_err(py_err)?;
+        Ok(Self {
+            inner: InnerTxBuilder::new(key_service.inner.clone(), chain_id, memo, fee, gas),
+            chain_config: ChainConfig::default(),
+        })
+    }
+
+    fn set_account_number(&mut self, account_number: u64) {
+        self.inner.set_account_number(account_number);
+    }
+
+    fn set_sequence(&mut self, sequence: u64) {
+        self.inner.set_sequence(sequence);
+    }
+
+    /// add a transfer from the key service to `to_address`, `amount` is like `1.5cro`
+    fn add_transfer(&mut self, to_address: &str, amount: &str) -> PyResult<()> {
+        let to_address = self
+            .chain_config
+            .parse_account_address(to_address)
+            .map_err(py_err)?;
+        let amount: Amount = amount.parse().map_err(py_err)?;
+        let from_address = block_on(self.inner.key_service.address())?.map_err(py_err)?;
+        let msg = Transfer::new_with_config(&self.chain_config, from_address, to_address, amount);
+        self.inner.add_message(msg);
+        Ok(())
+    }
+
+    /// sign the tx, `mode` is `sync`, `async` or `block`
+    #[args(mode = "\"sync\"")]
+    fn build(&mut self, mode: &str) -> PyResult<Transaction> {
+        let mode: SyncMode = mode.parse().map_err(py_err)?;
+        let inner = block_on(self.inner.build(mode))?.map_err(py_err)?;
+        Ok(Transaction { inner })
+    }
+}
+
+/// rest client of a node
+#[pyclass]
+struct Client {
+    inner: InnerClient,
+}
+
+#[pymethods]
+impl Client {
+    #[new]
+    fn new(base_api_url: String) -> PyResult<Self> {
+        Ok(Self {
+            inner: InnerClient::try_new(base_api_url).map_err(py_err)?,
+        })
+    }
+
+    /// the account number and the sequence
+    fn get_account_info(&self, address: &str) -> PyResult<(u64, u64)> {
+        block_on(self.inner.get_account_info(address))?.map_err(py_err)
+    }
+
+    /// broadcast the signed tx, return the tx hash
+    fn broadcast(&self, tx: &Transaction) -> PyResult<String> {
+        block_on(self.inner.broadcast_amino_tx(tx.inner.clone()))?.map_err(py_err)
+    }
+}
+
+#[pymodule]
+fn chainlib(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<Mnemonic>()?;
+    m.add_class::<PrivateKeyService>()?;
+    m.add_class::<Transaction>()?;
+    m.add_class::<TxBuilder>()?;
+    m.add_class::<Client>()?;
+    Ok(())
+}
@@ -0,0 +1,48 @@
+import json
+
+import pytest
+
+import chainlib
+
+WORDS = (
+    "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call "
+    "select sibling sport gadget please want vault glance verb damage gown"
+)
+ADDRESS = "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf"
+RECIPIENT = "cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd"
+
+
+def key_service():
+    return chainlib.PrivateKeyService.from_mnemonic(chainlib.Mnemonic(WORDS))
+
+
+def test_key_service():
+    key = key_service()
+    # the calls share one runtime, it is not created again by each of them
+    for _ in range(3):
+        assert key.address() == ADDRESS
+    assert key.address(prefix="tcro").startswith("tcro1")
+    assert key.public_key() == key_service().public_key()
+
+
+def test_build_transfer():
+    builder = chainlib.TxBuilder(key_service(), "test", fee="100000basecro", gas=200000)
+    builder.set_account_number(9)
+    builder.set_sequence(4)
+    builder.add_transfer(RECIPIENT, "1cro")
+    tx = json.loads(builder.build(mode="block").to_json())
+    assert tx["mode"] == "block"
+    assert tx["tx"]["msg"][0]["value"]["to_address"] == RECIPIENT
+    signatures = tx["tx"]["signatures"]
+    assert len(signatures) == 1
+    assert int(signatures[0]["sequence"]) == 4
+
+    with pytest.raises(ValueError):
+        builder.add_transfer("cosmos1invalid", "1cro")
+
+
+def test_client_error():
+    # nothing listens on the port 1 of localhost
+    client = chainlib.Client("http://127.0.0.1:1")
+    with pytest.raises(ValueError):
+        client.get_account_info(ADDRESS)
@@ -1,15 +1,17 @@
 // Copyright (c) 2020, cosmos (licensed under the Apache License Version 2.0)
 // Modifications Copyright (c) 2020, Foris Limited (licensed under the Apache License, Version 2.0)
 
-//! Build CosmosSDK/Tendermint/IBC proto files. This build script clones the CosmosSDK version
-//! specified in the COSMOS_REV constant and then uses that to build the required
-//! proto files for further compilation. This is based on the proto-compiler code
+//! Build CosmosSDK/Tendermint/IBC proto files. This build script checks out the submodules
+//! at the CosmosSDK and Tendermint versions specified in the COSMOS_REV and TENDERMINT_REV
+//! constants and then uses them to build the required proto files for further compilation.
+//! The generation is skipped when `src/proto` was already generated from these versions
+//! and this build script, set CHAINLIB_REGENERATE_PROTO=1 to force it. This is based on the proto-compiler code
 //! in github.com/informalsystems/ibc-rs
 
-#[cfg(feature = "grpc")]
 mod grpc {
     use regex::Regex;
     use std::{
+        env,
         ffi::OsStr,
         fs::{self, create_dir_all, remove_dir_all},
         io,
@@ -33,6 +35,11 @@ mod grpc {
     const TENDERMINT_DIR: &str = "./tendermint";
     /// A temporary directory for proto building
     const TMP_BUILD_DIR: &str = "./protobuf/";
+    /// Environment variable forcing the generation of up to date proto files
+    const REGENERATE_ENV: &str = "CHAINLIB_REGENERATE_PROTO";
+    /// File of the generated files recording the hash of the build script which generated
+    /// them, so a change of the proto paths or of the codegen config generates them again
+    const BUILD_STAMP_FILE: &str = "BUILD_SCRIPT_HASH";
 
     // Patch strings used by `copy_and_patch`
 
@@ -43,18 +50,28 @@ mod grpc {
     const TENDERMINT_PROTO_REGEX: &str = "(super::)+tendermint";
     /// Attribute preceeding a Tonic client definition
     const TONIC_CLIENT_ATTRIBUTE: &str = "#[doc = r\" Generated client implementations.\"]";
-    /// Attributes to add to gRPC clients
+    /// Attributes to add to gRPC clients, tonic is not available on wasm32 so the
+    /// message types are the only part of the generated code left there
     const GRPC_CLIENT_ATTRIBUTES: &[&str] = &[
-        "#[cfg(feature = \"grpc\")]",
+        "#[cfg(all(feature = \"grpc\", not(target_arch = \"wasm32\")))]",
         "#[cfg_attr(docsrs, doc(cfg(feature = \"grpc\")))]",
         TONIC_CLIENT_ATTRIBUTE,
     ];
 
-    #[cfg(feature = "grpc")]
     pub fn build() {
         let tmp_build_dir: PathBuf = TMP_BUILD_DIR.parse().unwrap();
         let proto_dir: PathBuf = COSMOS_SDK_PROTO_DIR.parse().unwrap();
 
+        println!("cargo:rerun-if-changed=build.rs");
+        println!("cargo:rerun-if-env-changed={}", REGENERATE_ENV);
+        for dir in &[COSMOS_SDK_DIR, TENDERMINT_DIR] {
+            println!("cargo:rerun-if-changed={}/proto", dir);
+        }
+
+        if is_up_to_date(&proto_dir) && env::var_os(REGENERATE_ENV).is_none() {
+            return;
+        }
+
         if tmp_build_dir.exists() {
             fs::remove_dir_all(&tmp_build_dir).unwrap();
         }
@@ -70,6 +87,23 @@ mod grpc {
         fs::remove_dir_all(&tmp_build_dir).unwrap();
     }
 
+    /// whether the generated files are from the pinned versions and this build script
+    fn is_up_to_date(proto_dir: &Path) -> bool {
+        let version = |file: &str| fs::read_to_string(proto_dir.join(file)).ok();
+        version("COSMOS_SDK_COMMIT").as_deref() == Some(COSMOS_REV)
+            && version("TENDERMINT_COMMIT").as_deref() == Some(TENDERMINT_REV)
+            && version(BUILD_STAMP_FILE) == Some(build_script_hash())
+    }
+
+    /// FNV-1a hash of this build script, stable across the compilers unlike `DefaultHasher`
+    fn build_script_hash() -> String {
+        let script = fs::read("build.rs").expect("read build.rs");
+        let hash = script.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        format!("{:016x}", hash)
+    }
+
     fn run_git(args: impl IntoIterator<Item = impl AsRef<OsStr>>) {
         let exit_status = process::Command::new("git")
             .args(args)
@@ -81,12 +115,33 @@ mod grpc {
         }
     }
 
+    /// the commit of a revision in a submodule, None if it is unknown there
+    fn rev_parse(dir: &str, rev: &str) -> Option<String> {
+        let output = process::Command::new("git")
+            .args(&["-C", dir, "rev-parse", &format!("{}^{{commit}}", rev)])
+            .output()
+            .expect("git output missing");
+        if output.status.success() {
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            None
+        }
+    }
+
+    /// the submodules are fetched and reset to the pinned versions, the ones already there
+    /// are left as they are
     fn update_submodule() {
         run_git(&["submodule", "update", "--init"]);
-        run_git(&["-C", COSMOS_SDK_DIR, "fetch"]);
-        run_git(&["-C", COSMOS_SDK_DIR, "reset", "--hard", COSMOS_REV]);
-        run_git(&["-C", TENDERMINT_DIR, "fetch"]);
-        run_git(&["-C", TENDERMINT_DIR, "reset", "--hard", TENDERMINT_REV]);
+        for (dir, rev) in &[
+            (COSMOS_SDK_DIR, COSMOS_REV),
+            (TENDERMINT_DIR, TENDERMINT_REV),
+        ] {
+            let head = rev_parse(dir, "HEAD");
+            if head.is_none() || head != rev_parse(dir, rev) {
+                run_git(&["-C", dir, "fetch", "--tags"]);
+                run_git(&["-C", dir, "reset", "--hard", rev]);
+            }
+        }
     }
 
     fn output_sdk_version(out_dir: &Path) {
@@ -94,6 +149,7 @@ mod grpc {
         fs::write(path, COSMOS_REV).unwrap();
         let path = out_dir.join("TENDERMINT_COMMIT");
         fs::write(path, TENDERMINT_REV).unwrap();
+        fs::write(out_dir.join(BUILD_STAMP_FILE), build_script_hash()).unwrap();
     }
 
     fn find_proto_files(proto_paths: Vec<String>) -> Vec<PathBuf> {
@@ -125,10 +181,10 @@ mod grpc {
         let protos = find_proto_files(proto_paths);
         let includes: Vec<PathBuf> = proto_includes_paths.iter().map(PathBuf::from).collect();
 
-        // Compile all proto files
+        // Compile all proto files, only the clients are used
         if let Err(e) = tonic_build::configure()
             .build_client(true)
-            .build_server(true)
+            .build_server(false)
             .format(true)
             .out_dir(out_dir)
             .compile(&protos, &includes)
@@ -147,7 +203,9 @@ mod grpc {
             format!("{}/proto/cosmos/tx", sdk_dir.display()),
             format!("{}/proto/cosmos/bank", sdk_dir.display()),
             format!("{}/proto/cosmos/base", sdk_dir.display()),
+            format!("{}/proto/cosmos/crypto", sdk_dir.display()),
             format!("{}/proto/cosmos/staking", sdk_dir.display()),
+            format!("{}/proto/cosmos/vesting", sdk_dir.display()),
         ];
 
         let proto_includes_paths = vec![
@@ -190,6 +248,7 @@ mod grpc {
 
         let proto_services_path = [
             sdk_dir.join("proto/cosmos/auth/v1beta1/query.proto"),
+            sdk_dir.join("proto/cosmos/bank/v1beta1/query.proto"),
             sdk_dir.join("proto/cosmos/staking/v1beta1/query.proto"),
             sdk_dir.join("proto/cosmos/tx/v1beta1/service.proto"),
             sdk_dir.join("proto/cosmos/tx/v1beta1/tx.proto"),
@@ -276,6 +335,5 @@ mod grpc {
 }
 
 fn main() {
-    #[cfg(feature = "grpc")]
     grpc::build();
 }
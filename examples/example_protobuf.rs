@@ -16,12 +16,13 @@ async fn main() -> Result<(), Error> {
     let base_api_url = "http://127.0.0.1:1317".to_string();
     // grpc url is set in `grpc_laddr` in $CHAIN_MAIND_HOME/config/config.toml
     let grpc_url = "http://127.0.0.1:1234".to_string();
-    let client = Client::new(base_api_url, grpc_url);
+    let mut client = Client::try_new(base_api_url)?;
+    client.set_grpc_url(grpc_url);
 
     let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
-    let mnemonic = Mnemonic::from_str(words, None).unwrap();
-    let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
-    let chain_id = "test".into();
+    let mnemonic = Mnemonic::from_str(words, None)?;
+    let key_service = PrivateKeyService::new_from_mnemonic(mnemonic)?;
+    let chain_id = "test".parse()?;
     let fee = Fee {
         amount: vec![Coin {
             denom: "basecro".to_string(),
@@ -35,11 +36,11 @@ async fn main() -> Result<(), Error> {
     let timeout_height = 0;
     let mut builder = TxBuilder::new(key_service, chain_id, None, timeout_height, Some(fee));
 
-    let self_address = builder.key_service.address().unwrap();
+    let self_address = builder.key_service.address().await?;
     let address_str = self_address.to_bech32(ACCOUNT_ADDRESS_PREFIX);
 
     // update account info
-    let (account_number, sequence) = client.get_account_info(&address_str).await.unwrap();
+    let (account_number, sequence) = client.get_account_info(&address_str).await?;
 
     // add msg
     let to_address = "cro1fj6jpmuykvra4kxrw0cp20e4vx4r8eda8q3yn9".into();
@@ -47,15 +48,14 @@ async fn main() -> Result<(), Error> {
         denom: "basecro".into(),
         amount: 100000000.to_string(),
     };
-    let msg = builder.create_msg(to_address, amount).unwrap();
+    let msg = builder.create_msg(to_address, amount).await?;
     builder
         .add_message(msg)
         .set_account_number(account_number)
         .set_sequence(sequence);
 
-    let b64_tx = builder.build().await.unwrap();
-    let tx_bytes = base64::decode(b64_tx).unwrap();
-    let response = client.broadcast_tx(tx_bytes).await;
+    let tx_bytes = builder.build_bytes().await?;
+    let response = client.broadcast_tx_bytes(tx_bytes).await?;
     println!("grpc response: {:?}", response);
     Ok(())
 }
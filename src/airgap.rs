@@ -0,0 +1,346 @@
+//! Exchange of the sign requests and the signatures with an offline device as BC-UR
+//! fragments, which are shown as an animated QR code and scanned by the camera:
+//! the online machine encodes a `SignRequest`, the offline device decodes it, signs it with
+//! its `KeyService` and encodes the `SignResponse` back
+
+use crate::constant::FUNDRAISER_PATH;
+use crate::error::Error;
+use crate::hd_wallet::derivation_path::DerivationPath;
+use crate::hd_wallet::mnemonic::Mnemonic;
+use crate::key_service::private_key_service::PrivateKeyService;
+use crate::key_service::KeyService;
+use crate::utils::codec::canonical_json;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use ur::{Decoder, Encoder};
+
+/// ur type of a `SignRequest`
+pub const UR_TYPE_SIGN_REQUEST: &str = "cosmos-sign-request";
+/// ur type of a `SignResponse`
+pub const UR_TYPE_SIGN_RESPONSE: &str = "cosmos-signature";
+/// fragment length which keeps the QR codes readable by the phone cameras
+pub const DEFAULT_MAX_FRAGMENT_LENGTH: usize = 200;
+
+/// the format of the data to sign
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SignDataType {
+    /// the amino json sign doc, signed as canonical json
+    Amino,
+    /// the base64 protobuf SignDoc bytes
+    Direct,
+}
+
+/// a sign doc sent to the offline device
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SignRequest {
+    /// matches the response to the request
+    pub request_id: String,
+    pub data_type: SignDataType,
+    /// the amino json sign doc, or the base64 protobuf SignDoc bytes
+    pub sign_doc: String,
+    /// the key the device should sign with, if it holds several
+    #[serde(default)]
+    pub hd_path: Option<String>,
+}
+
+/// the signature returned by the offline device
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SignResponse {
+    pub request_id: String,
+    /// base64 signature
+    pub signature: String,
+    /// base64 compressed public key
+    pub public_key: String,
+}
+
+impl SignRequest {
+    /// request the signature of an amino sign doc
+    pub fn amino(request_id: &str, sign_doc: &serde_json::Value) -> Self {
+        Self {
+            request_id: request_id.to_string(),
+            data_type: SignDataType::Amino,
+            sign_doc: canonical_json(sign_doc),
+            hd_path: None,
+        }
+    }
+
+    /// request the signature of the protobuf SignDoc bytes
+    pub fn direct(request_id: &str, sign_doc: &[u8]) -> Self {
+        Self {
+            request_id: request_id.to_string(),
+            data_type: SignDataType::Direct,
+            sign_doc: base64::encode(sign_doc),
+            hd_path: None,
+        }
+    }
+
+    /// the bytes which are signed
+    pub fn sign_bytes(&self) -> Result<Vec<u8>, Error> {
+        match self.data_type {
+            SignDataType::Amino => {
+                let sign_doc: serde_json::Value = serde_json::from_str(&self.sign_doc)
+                    .map_err(|e| Error::SerializeError(e.to_string()))?;
+                Ok(canonical_json(&sign_doc).into_bytes())
+            }
+            SignDataType::Direct => base64::decode(&self.sign_doc)
+                .map_err(|e| Error::InputError(format!("invalid base64 sign doc: {}", e))),
+        }
+    }
+
+    /// sign the request on the offline device, a request for the key of another hd path
+    /// than the one of the key service is rejected
+    pub async fn sign<K: KeyService + ?Sized>(
+        &self,
+        key_service: &K,
+    ) -> Result<SignResponse, Error> {
+        if let Some(hd_path) = &self.hd_path {
+            let key_path = key_service.hd_path();
+            if !same_path(hd_path, key_path.as_deref()) {
+                return Err(Error::InputError(format!(
+                    "the request is for the key of {}, the key service has {}",
+                    hd_path,
+                    key_path.unwrap_or_else(|| "an unknown path".to_string())
+                )));
+            }
+        }
+        self.sign_with(key_service).await
+    }
+
+    /// sign the request with the key of its hd path derived from the mnemonic of the
+    /// offline device, the key of `m/44'/394'/0'/0/0` if the request has no hd path
+    pub async fn sign_with_mnemonic(&self, mnemonic: &Mnemonic) -> Result<SignResponse, Error> {
+        let hd_path = self.hd_path.as_deref().unwrap_or(FUNDRAISER_PATH);
+        let key_service = PrivateKeyService::new(mnemonic.private_key_with_path(hd_path)?);
+        self.sign_with(&key_service).await
+    }
+
+    async fn sign_with<K: KeyService + ?Sized>(
+        &self,
+        key_service: &K,
+    ) -> Result<SignResponse, Error> {
+        let signature = key_service.sign(&self.sign_bytes()?).await?;
+        let public_key = key_service.public_key().await?;
+        Ok(SignResponse {
+            request_id: self.request_id.clone(),
+            signature,
+            public_key: public_key.to_string(),
+        })
+    }
+}
+
+/// whether the requested hd path is the path of the key, the BIP44 paths are compared parsed
+fn same_path(requested: &str, key_path: Option<&str>) -> bool {
+    let key_path = match key_path {
+        Some(key_path) => key_path,
+        None => return false,
+    };
+    match (
+        requested.parse::<DerivationPath>(),
+        key_path.parse::<DerivationPath>(),
+    ) {
+        (Ok(requested), Ok(key_path)) => requested == key_path,
+        _ => requested == key_path,
+    }
+}
+
+/// the fragments of a payload, `next_part` is called for every frame of the animated QR,
+/// after the `fragment_count` original fragments it continues with the fountain fragments
+/// so the scanner can recover the frames it missed
+pub struct AirgapEncoder {
+    encoder: Encoder,
+}
+
+impl AirgapEncoder {
+    pub fn new<T: Serialize>(
+        payload: &T,
+        ur_type: &str,
+        max_fragment_length: usize,
+    ) -> Result<Self, Error> {
+        let json = serde_json::to_vec(payload).map_err(|e| Error::SerializeError(e.to_string()))?;
+        let encoder = Encoder::new(&cbor_bytes(&json), max_fragment_length, ur_type)
+            .map_err(|e| Error::SerializeError(format!("ur encode error: {:?}", e)))?;
+        Ok(Self { encoder })
+    }
+
+    pub fn sign_request(request: &SignRequest) -> Result<Self, Error> {
+        Self::new(request, UR_TYPE_SIGN_REQUEST, DEFAULT_MAX_FRAGMENT_LENGTH)
+    }
+
+    pub fn sign_response(response: &SignResponse) -> Result<Self, Error> {
+        Self::new(response, UR_TYPE_SIGN_RESPONSE, DEFAULT_MAX_FRAGMENT_LENGTH)
+    }
+
+    /// number of the original fragments
+    pub fn fragment_count(&self) -> usize {
+        self.encoder.fragment_count()
+    }
+
+    /// the next `ur:...` part
+    pub fn next_part(&mut self) -> Result<String, Error> {
+        self.encoder
+            .next_part()
+            .map_err(|e| Error::SerializeError(format!("ur encode error: {:?}", e)))
+    }
+}
+
+/// collects the scanned parts until the payload is complete
+#[derive(Default)]
+pub struct AirgapDecoder {
+    decoder: Decoder,
+}
+
+impl AirgapDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add a scanned part, the parts can be received in any order and repeated
+    pub fn receive(&mut self, part: &str) -> Result<(), Error> {
+        self.decoder
+            .receive(&part.to_lowercase())
+            .map_err(|e| Error::InputError(format!("invalid ur part: {:?}", e)))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.decoder.complete()
+    }
+
+    /// the payload, None until all the fragments are received
+    pub fn payload<T: DeserializeOwned>(&self) -> Result<Option<T>, Error> {
+        let message = self
+            .decoder
+            .message()
+            .map_err(|e| Error::InputError(format!("invalid ur message: {:?}", e)))?;
+        match message {
+            Some(message) => {
+                let json = from_cbor_bytes(&message)?;
+                let payload = serde_json::from_slice(json)
+                    .map_err(|e| Error::SerializeError(e.to_string()))?;
+                Ok(Some(payload))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// the payload is a CBOR byte string, as the UR spec requires a CBOR message
+fn cbor_bytes(data: &[u8]) -> Vec<u8> {
+    let len = data.len();
+    let mut cbor = match len {
+        0..=23 => vec![0x40 | len as u8],
+        24..=0xff => vec![0x58, len as u8],
+        0x100..=0xffff => [&[0x59][..], &(len as u16).to_be_bytes()].concat(),
+        _ => [&[0x5a][..], &(len as u32).to_be_bytes()].concat(),
+    };
+    cbor.extend_from_slice(data);
+    cbor
+}
+
+fn from_cbor_bytes(cbor: &[u8]) -> Result<&[u8], Error> {
+    let invalid = || Error::InputError("the ur message is not a CBOR byte string".to_string());
+    let (header, rest) = cbor.split_first().ok_or_else(invalid)?;
+    let (len, data) = match header {
+        0x40..=0x57 => ((header & 0x1f) as usize, rest),
+        0x58 if !rest.is_empty() => (rest[0] as usize, &rest[1..]),
+        0x59 if rest.len() >= 2 => (u16::from_be_bytes([rest[0], rest[1]]) as usize, &rest[2..]),
+        0x5a if rest.len() >= 4 => (
+            u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize,
+            &rest[4..],
+        ),
+        _ => return Err(invalid()),
+    };
+    if data.len() != len {
+        return Err(invalid());
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hd_wallet::mnemonic::Mnemonic;
+    use crate::key_service::private_key_service::PrivateKeyService;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_airgap_sign() {
+        let memo = "m".repeat(500);
+        let sign_doc = json!({"chain_id": "test", "memo": memo, "account_number": "1"});
+        let request = SignRequest::amino("1", &sign_doc);
+        let mut encoder = AirgapEncoder::sign_request(&request).unwrap();
+        assert!(encoder.fragment_count() > 1);
+
+        // the scanner misses the first frame, the fountain parts make up for it
+        let mut decoder = AirgapDecoder::new();
+        encoder.next_part().unwrap();
+        while !decoder.is_complete() {
+            decoder.receive(&encoder.next_part().unwrap()).unwrap();
+        }
+        let received: SignRequest = decoder.payload().unwrap().unwrap();
+        assert_eq!(received, request);
+
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+        let response = received.sign(&key_service).await.unwrap();
+        let expected = key_service
+            .sign(canonical_json(&sign_doc).as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(response.signature, expected);
+
+        let mut encoder = AirgapEncoder::sign_response(&response).unwrap();
+        let mut decoder = AirgapDecoder::new();
+        assert!(decoder.payload::<SignResponse>().unwrap().is_none());
+        while !decoder.is_complete() {
+            decoder
+                .receive(&encoder.next_part().unwrap().to_uppercase())
+                .unwrap();
+        }
+        assert_eq!(
+            decoder.payload::<SignResponse>().unwrap().unwrap(),
+            response
+        );
+
+        assert!(AirgapDecoder::new().receive("ur:bytes/invalid").is_err());
+        assert_eq!(
+            from_cbor_bytes(&cbor_bytes(&[7; 300])).unwrap(),
+            &[7; 300][..]
+        );
+        assert!(from_cbor_bytes(&[0x58, 2, 1]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sign_hd_path() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service =
+            PrivateKeyService::new_from_mnemonic(Mnemonic::from_str(words, None).unwrap()).unwrap();
+        let mut request = SignRequest::direct("2", &[1, 2, 3]);
+
+        request.hd_path = Some(FUNDRAISER_PATH.to_string());
+        let response = request.sign(&key_service).await.unwrap();
+        assert_eq!(
+            response.public_key,
+            key_service.public_key().await.unwrap().to_string()
+        );
+
+        // the key service holds another key than the requested one
+        request.hd_path = Some("m/44'/394'/0'/0/1".to_string());
+        assert!(request.sign(&key_service).await.is_err());
+        let raw_key = PrivateKeyService::new(mnemonic.derive(0, 0, 1).unwrap());
+        assert!(request.sign(&raw_key).await.is_err());
+
+        let response = request.sign_with_mnemonic(&mnemonic).await.unwrap();
+        let expected = PrivateKeyService::new(mnemonic.derive(0, 0, 1).unwrap());
+        assert_eq!(
+            response.public_key,
+            expected.public_key().await.unwrap().to_string()
+        );
+        assert_eq!(
+            response.signature,
+            expected.sign(&request.sign_bytes().unwrap()).await.unwrap()
+        );
+    }
+}
@@ -0,0 +1,358 @@
+//! `chainlib` command line, the keys are kept in password encrypted keystore files
+//! under `--home`, e.g. `chainlib keys add alice` and `chainlib tx send alice cro1... 1.5cro`
+
+use cro_sign_tool::client::Client;
+use cro_sign_tool::error::Error;
+use cro_sign_tool::hd_wallet::mnemonic::Mnemonic;
+use cro_sign_tool::key_service::keystore_service::{Keystore, KeystoreService};
+use cro_sign_tool::key_service::KeyService;
+use cro_sign_tool::message::amino::Transfer;
+use cro_sign_tool::tx_builder::amino::TxBuilder;
+use cro_sign_tool::tx_builder::{grpc, TxFormat};
+use cro_sign_tool::types::address::ChainConfig;
+use cro_sign_tool::types::basic::{Amount, SyncMode};
+use cro_sign_tool::types::chain_id::ChainId;
+use cro_sign_tool::types::fee::FeeBuilder;
+use cro_sign_tool::types::key::PublicKey;
+use cro_sign_tool::types::transaction::{Transaction, Tx};
+use std::io::BufRead;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// the messages of the signed and broadcast tx files are kept as they are
+type JsonTx = Tx<serde_json::Value>;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "chainlib",
+    about = "keys, transfers and queries of the crypto.org chain"
+)]
+struct Opt {
+    /// directory of the keystore files, `~/.chainlib` by default
+    #[structopt(long, parse(from_os_str))]
+    home: Option<PathBuf>,
+    /// rest api of the node
+    #[structopt(long, default_value = "http://127.0.0.1:1317")]
+    node: String,
+    /// use the testnet prefixes and coin type
+    #[structopt(long)]
+    testnet: bool,
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// manage the keys
+    Keys(KeysCommand),
+    /// sign and broadcast the txs
+    Tx(TxCommand),
+    /// query the chain
+    Query(QueryCommand),
+}
+
+#[derive(Debug, StructOpt)]
+enum KeysCommand {
+    /// create a key from new words, or from the words read from stdin with `--recover`
+    Add {
+        name: String,
+        #[structopt(long)]
+        recover: bool,
+        /// derivation path of the key, the first account of the chain by default
+        #[structopt(long)]
+        hd_path: Option<String>,
+        #[structopt(flatten)]
+        password: PasswordOpt,
+    },
+    /// show the address and the public key of a key
+    Show { name: String },
+}
+
+#[derive(Debug, StructOpt)]
+enum TxCommand {
+    /// transfer from a key, the account number and the sequence are queried from the node
+    Send {
+        from: String,
+        to_address: String,
+        /// e.g. `1.5cro` or `150000000basecro`
+        amount: String,
+        #[structopt(flatten)]
+        tx: TxOpt,
+        /// `amino` or `protobuf`, protobuf with the grpc feature and amino otherwise
+        #[structopt(long)]
+        format: Option<TxFormat>,
+        #[structopt(long, default_value = "sync")]
+        mode: SyncMode,
+        #[structopt(flatten)]
+        password: PasswordOpt,
+    },
+    /// sign a StdTx json file (e.g. from `--generate-only`) and print the signed json
+    Sign {
+        name: String,
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        #[structopt(long)]
+        chain_id: ChainId,
+        /// don't query the node, `--account-number` and `--sequence` are required
+        #[structopt(long)]
+        offline: bool,
+        #[structopt(long)]
+        account_number: Option<u64>,
+        #[structopt(long)]
+        sequence: Option<u64>,
+        /// write the signed tx into the file instead of stdout
+        #[structopt(long, parse(from_os_str))]
+        output: Option<PathBuf>,
+        #[structopt(flatten)]
+        password: PasswordOpt,
+    },
+    /// broadcast a signed StdTx json file
+    Broadcast {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        #[structopt(long, default_value = "sync")]
+        mode: SyncMode,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+enum QueryCommand {
+    /// the account of an address
+    Account { address: String },
+}
+
+#[derive(Debug, StructOpt)]
+struct TxOpt {
+    #[structopt(long)]
+    chain_id: ChainId,
+    /// e.g. `0.001cro`
+    #[structopt(long)]
+    fee: Option<Amount>,
+    #[structopt(long)]
+    gas: Option<u64>,
+    #[structopt(long)]
+    memo: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+struct PasswordOpt {
+    /// password of the keystore, asked on the terminal if not given
+    #[structopt(long, env = "CHAINLIB_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+}
+
+impl PasswordOpt {
+    fn get(&self) -> Result<String, Error> {
+        match &self.password {
+            Some(password) => Ok(password.clone()),
+            None => Ok(rpassword::read_password_from_tty(Some("password: "))?),
+        }
+    }
+}
+
+struct Cli {
+    home: PathBuf,
+    node: String,
+    chain_config: ChainConfig,
+}
+
+impl Cli {
+    fn key_file(&self, name: &str) -> PathBuf {
+        self.home.join(format!("{}.json", name))
+    }
+
+    fn load_key(&self, name: &str, password: &PasswordOpt) -> Result<KeystoreService, Error> {
+        KeystoreService::load(self.key_file(name), &password.get()?)
+    }
+
+    fn client(&self) -> Result<Client, Error> {
+        Client::try_new(self.node.clone())
+    }
+
+    async fn account_info(&self, key_service: &KeystoreService) -> Result<(u64, u64), Error> {
+        let address = self
+            .chain_config
+            .account_address(&key_service.address().await?);
+        self.client()?.get_account_info(&address).await
+    }
+
+    async fn keys(&self, command: KeysCommand) -> Result<(), Error> {
+        match command {
+            KeysCommand::Add {
+                name,
+                recover,
+                hd_path,
+                password,
+            } => {
+                let key_file = self.key_file(&name);
+                if key_file.exists() {
+                    return Err(Error::InputError(format!("the key {} exists", name)));
+                }
+                let mnemonic = if recover {
+                    eprintln!("enter the mnemonic words:");
+                    let mut words = String::new();
+                    std::io::stdin().lock().read_line(&mut words)?;
+                    Mnemonic::from_str(words.trim(), None)?
+                } else {
+                    Mnemonic::new(24, None)?
+                };
+                let hd_path = hd_path
+                    .unwrap_or_else(|| self.chain_config.derivation_path(0, 0, 0).to_string());
+                let private_key = mnemonic.private_key_with_path(&hd_path)?;
+                let key_service = KeystoreService::new(private_key, &password.get()?)?;
+                std::fs::create_dir_all(&self.home)?;
+                key_service.save(&key_file)?;
+                let address = key_service.address().await?;
+                println!("{}", self.chain_config.account_address(&address));
+                if !recover {
+                    eprintln!(
+                        "write down the mnemonic words, they are the only backup of the key:"
+                    );
+                    println!("{}", mnemonic.phrase());
+                }
+            }
+            KeysCommand::Show { name } => {
+                let keystore = Keystore::load(self.key_file(&name))?;
+                let public_key = PublicKey::from_base64_str(&keystore.public_key)?;
+                let address = self.chain_config.account_address(&public_key.address()?);
+                println!("address: {}", address);
+                println!("public key: {}", public_key.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    async fn tx(&self, command: TxCommand) -> Result<(), Error> {
+        match command {
+            TxCommand::Send {
+                from,
+                to_address,
+                amount,
+                tx,
+                format,
+                mode,
+                password,
+            } => {
+                let key_service = self.load_key(&from, &password)?;
+                let from_address = key_service.address().await?;
+                let to_address = self.chain_config.parse_account_address(&to_address)?;
+                let amount: Amount = amount.parse()?;
+                let (account_number, sequence) = self.account_info(&key_service).await?;
+                if format.unwrap_or_default() == TxFormat::Protobuf {
+                    let mut fee = FeeBuilder::new();
+                    if let Some(amount) = tx.fee {
+                        fee.add_amount(amount);
+                    }
+                    if let Some(gas) = tx.gas {
+                        fee.set_gas_limit(gas);
+                    }
+                    let mut builder =
+                        grpc::TxBuilder::new(key_service, tx.chain_id, tx.memo, 0, None);
+                    builder
+                        .set_chain_config(self.chain_config.clone())
+                        .set_fee_builder(&fee)?
+                        .set_account_number(account_number)
+                        .set_sequence(sequence);
+                    let msg = builder
+                        .create_msg(
+                            self.chain_config.account_address(&to_address),
+                            (&amount).into(),
+                        )
+                        .await?;
+                    builder.add_message(msg);
+                    let tx_bytes = builder.build_bytes().await?;
+                    let response = self.client()?.broadcast_raw_tx(tx_bytes, mode).await?;
+                    println!("{:#?}", response);
+                    return Ok(());
+                }
+                let mut builder = TxBuilder::new(key_service, tx.chain_id, tx.memo, tx.fee, tx.gas);
+                builder
+                    .add_message(Transfer::new_with_config(
+                        &self.chain_config,
+                        from_address,
+                        to_address,
+                        amount,
+                    ))
+                    .set_account_number(account_number)
+                    .set_sequence(sequence);
+                let tx = builder.build(mode).await?;
+                println!("{}", self.client()?.broadcast_amino_tx(tx).await?);
+            }
+            TxCommand::Sign {
+                name,
+                file,
+                chain_id,
+                offline,
+                account_number,
+                sequence,
+                output,
+                password,
+            } => {
+                let key_service = self.load_key(&name, &password)?;
+                let (account_number, sequence) = match (account_number, sequence) {
+                    (Some(account_number), Some(sequence)) => (account_number, sequence),
+                    _ if offline => {
+                        return Err(Error::InputError(
+                            "--offline needs --account-number and --sequence".to_string(),
+                        ))
+                    }
+                    _ => self.account_info(&key_service).await?,
+                };
+                let mut tx = JsonTx::read_file(&file)?;
+                let mut builder = TxBuilder::from_tx(key_service, chain_id, tx.clone());
+                builder
+                    .set_account_number(account_number)
+                    .set_sequence(sequence);
+                builder.append_signature(&mut tx).await?;
+                match output {
+                    Some(output) => tx.write_file(output)?,
+                    None => println!("{}", tx.to_json()?),
+                }
+            }
+            TxCommand::Broadcast { file, mode } => {
+                let tx = JsonTx::read_file(&file)?;
+                let transaction = Transaction { tx, mode };
+                println!("{}", self.client()?.broadcast_amino_tx(transaction).await?);
+            }
+        }
+        Ok(())
+    }
+
+    async fn query(&self, command: QueryCommand) -> Result<(), Error> {
+        match command {
+            QueryCommand::Account { address } => {
+                let account = self.client()?.get_account(&address).await?;
+                println!("{:#?}", account);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let opt = Opt::from_args();
+    let home = opt.home.unwrap_or_else(|| {
+        let home = std::env::var_os("HOME").unwrap_or_else(|| ".".into());
+        PathBuf::from(home).join(".chainlib")
+    });
+    let chain_config = if opt.testnet {
+        ChainConfig::crypto_org_testnet()
+    } else {
+        ChainConfig::default()
+    };
+    let cli = Cli {
+        home,
+        node: opt.node,
+        chain_config,
+    };
+    let result = match opt.command {
+        Command::Keys(command) => cli.keys(command).await,
+        Command::Tx(command) => cli.tx(command).await,
+        Command::Query(command) => cli.query(command).await,
+    };
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,248 @@
+//! Cache of the account numbers and the sequences in front of a `ClientApi`, for the senders
+//! which broadcast many txs and would otherwise query the account before each of them
+
+use crate::client::ClientApi;
+use crate::error::{BroadcastError, Error};
+use crate::proto::cosmos::base::v1beta1::Coin;
+use crate::proto::tendermint::rpc::grpc::ResponseBroadcastTx;
+use crate::types::account::Account;
+use crate::types::basic::{GasPrice, SyncMode};
+use crate::types::block::Block;
+use crate::types::node_info::NodeInfo;
+use crate::types::transaction::Transaction;
+use crate::types::tx_response::{SimulateResponse, TxResponse};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// how long a sequence is used before it is queried again
+pub const DEFAULT_SEQUENCE_TTL: Duration = Duration::from_secs(5);
+
+struct CachedAccount {
+    /// never changes once the account exists
+    account_number: u64,
+    /// the sequence and when it was queried, None once invalidated
+    sequence: Option<(u64, Instant)>,
+}
+
+/// `ClientApi` which keeps the account numbers forever and the sequences for a short ttl,
+/// the sequences are dropped after each successful broadcast, each sequence mismatch and
+/// each failed broadcast request (the tx may have reached the node anyway),
+/// the other requests go to the inner client. Clones share the cache.
+#[derive(Clone)]
+pub struct AccountCache<C: ClientApi> {
+    client: C,
+    accounts: Arc<Mutex<HashMap<String, CachedAccount>>>,
+    sequence_ttl: Duration,
+}
+
+impl<C: ClientApi> AccountCache<C> {
+    pub fn new(client: C) -> Self {
+        Self {
+            client,
+            accounts: Arc::new(Mutex::new(HashMap::new())),
+            sequence_ttl: DEFAULT_SEQUENCE_TTL,
+        }
+    }
+
+    /// how long a sequence is used before it is queried again, 5s by default
+    pub fn set_sequence_ttl(&mut self, sequence_ttl: Duration) -> &mut Self {
+        self.sequence_ttl = sequence_ttl;
+        self
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.client
+    }
+
+    fn accounts(&self) -> MutexGuard<'_, HashMap<String, CachedAccount>> {
+        self.accounts.lock().expect("account cache lock poisoned")
+    }
+
+    fn cached(&self, address: &str) -> Option<(u64, u64)> {
+        let accounts = self.accounts();
+        let account = accounts.get(address)?;
+        match account.sequence {
+            Some((sequence, queried)) if queried.elapsed() < self.sequence_ttl => {
+                Some((account.account_number, sequence))
+            }
+            _ => None,
+        }
+    }
+
+    fn store(&self, address: &str, account_number: u64, sequence: u64) -> (u64, u64) {
+        let mut accounts = self.accounts();
+        let account = accounts
+            .entry(address.to_string())
+            .or_insert(CachedAccount {
+                account_number,
+                sequence: None,
+            });
+        account.sequence = Some((sequence, Instant::now()));
+        (account.account_number, sequence)
+    }
+
+    /// drop the sequence of an account, e.g. after a tx was sent by another client
+    pub fn invalidate(&self, address: &str) {
+        if let Some(account) = self.accounts().get_mut(address) {
+            account.sequence = None;
+        }
+    }
+
+    /// drop the sequences of all the accounts, the account numbers are kept
+    pub fn invalidate_all(&self) {
+        for account in self.accounts().values_mut() {
+            account.sequence = None;
+        }
+    }
+}
+
+#[async_trait]
+impl<C: ClientApi> ClientApi for AccountCache<C> {
+    async fn get_account(&self, address: &str) -> Result<Account, Error> {
+        let account = self.client.get_account(address).await?;
+        self.store(address, account.account_number(), account.sequence());
+        Ok(account)
+    }
+
+    async fn get_account_info(&self, address: &str) -> Result<(u64, u64), Error> {
+        if let Some(info) = self.cached(address) {
+            return Ok(info);
+        }
+        let (account_number, sequence) = self.client.get_account_info(address).await?;
+        Ok(self.store(address, account_number, sequence))
+    }
+
+    async fn get_balance(&self, address: &str, denom: &str) -> Result<Coin, Error> {
+        self.client.get_balance(address, denom).await
+    }
+
+    async fn get_all_balances(&self, address: &str) -> Result<Vec<Coin>, Error> {
+        self.client.get_all_balances(address).await
+    }
+
+    async fn get_node_info(&self) -> Result<NodeInfo, Error> {
+        self.client.get_node_info().await
+    }
+
+    async fn get_min_gas_prices(&self) -> Result<Vec<GasPrice>, Error> {
+        self.client.get_min_gas_prices().await
+    }
+
+    async fn get_latest_block(&self) -> Result<Block, Error> {
+        self.client.get_latest_block().await
+    }
+
+    async fn get_block(&self, height: u64) -> Result<Block, Error> {
+        self.client.get_block(height).await
+    }
+
+    async fn get_tx(&self, hash: &str) -> Result<Option<TxResponse>, Error> {
+        self.client.get_tx(hash).await
+    }
+
+    async fn simulate(&self, tx_bytes: &[u8]) -> Result<SimulateResponse, Error> {
+        self.client.simulate(tx_bytes).await
+    }
+
+    async fn broadcast_raw_tx(
+        &self,
+        tx_bytes: Vec<u8>,
+        mode: SyncMode,
+    ) -> Result<TxResponse, Error> {
+        let result = self.client.broadcast_raw_tx(tx_bytes, mode).await;
+        match result.as_ref().map(TxResponse::error) {
+            Ok(None) | Ok(Some(BroadcastError::SequenceMismatch { .. })) | Err(_) => {
+                self.invalidate_all()
+            }
+            Ok(Some(_)) => {}
+        }
+        result
+    }
+
+    async fn broadcast_tx_bytes(&self, tx: Vec<u8>) -> Result<ResponseBroadcastTx, Error> {
+        let result = self.client.broadcast_tx_bytes(tx).await;
+        let rejected = result.as_ref().map_or(false, |response| {
+            response
+                .check_tx
+                .as_ref()
+                .map_or(false, |check| check.code != 0)
+        });
+        if !rejected {
+            self.invalidate_all();
+        }
+        result
+    }
+
+    async fn broadcast_amino_tx(
+        &self,
+        tx: Transaction<serde_json::Value>,
+    ) -> Result<String, Error> {
+        let result = self.client.broadcast_amino_tx(tx).await;
+        match &result {
+            Err(Error::BroadcastError(BroadcastError::SequenceMismatch { .. })) => {
+                self.invalidate_all()
+            }
+            // rejected by the node, the sequence is unchanged
+            Err(Error::BroadcastError(_)) => {}
+            Ok(_) | Err(_) => self.invalidate_all(),
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::mock::MockClient;
+    use crate::types::account::BaseAccount;
+
+    const ADDRESS: &str = "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf";
+
+    fn account(account_number: u64, sequence: u64) -> Account {
+        Account::BaseAccount(BaseAccount {
+            address: ADDRESS.to_string(),
+            pub_key: None,
+            account_number,
+            sequence,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_account_cache() {
+        let mock = MockClient::new();
+        mock.set_account(account(9, 25));
+        let mut cache = AccountCache::new(mock.clone());
+        assert_eq!(cache.get_account_info(ADDRESS).await.unwrap(), (9, 25));
+
+        // served from the cache, the node is not queried
+        mock.set_account(account(9, 26));
+        assert_eq!(cache.get_account_info(ADDRESS).await.unwrap(), (9, 25));
+        cache
+            .broadcast_raw_tx(vec![1], SyncMode::Sync)
+            .await
+            .unwrap();
+        assert_eq!(cache.get_account_info(ADDRESS).await.unwrap(), (9, 26));
+
+        // the failed request may have reached the node, the sequence is queried again
+        mock.set_account(account(9, 27)).fail_next_requests(1);
+        assert!(cache
+            .broadcast_raw_tx(vec![2], SyncMode::Sync)
+            .await
+            .is_err());
+        assert_eq!(cache.get_account_info(ADDRESS).await.unwrap(), (9, 27));
+        mock.set_account(account(9, 28)).fail_next_requests(1);
+        assert!(cache.broadcast_tx_bytes(vec![3]).await.is_err());
+        assert_eq!(cache.get_account_info(ADDRESS).await.unwrap(), (9, 28));
+
+        cache.set_sequence_ttl(Duration::from_secs(0));
+        mock.set_account(account(10, 29));
+        assert_eq!(cache.get_account_info(ADDRESS).await.unwrap(), (9, 29));
+
+        cache.set_sequence_ttl(DEFAULT_SEQUENCE_TTL);
+        mock.set_account(account(9, 30));
+        cache.invalidate(ADDRESS);
+        assert_eq!(cache.get_account_info(ADDRESS).await.unwrap(), (9, 30));
+    }
+}
@@ -0,0 +1,274 @@
+//! Client over several endpoints of the same chain, switching to the next endpoint
+//! when the current one can't be reached
+
+use crate::client::{Client, ClientApi, ClientConfig};
+use crate::error::Error;
+use crate::proto::cosmos::base::v1beta1::Coin;
+use crate::proto::tendermint::rpc::grpc::ResponseBroadcastTx;
+use crate::types::account::Account;
+use crate::types::basic::{GasPrice, SyncMode};
+use crate::types::block::Block;
+use crate::types::node_info::NodeInfo;
+use crate::types::transaction::Transaction;
+use crate::types::tx_response::{SimulateResponse, TxResponse};
+
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// the urls of a node
+#[derive(Debug, Clone)]
+pub struct EndpointUrls {
+    /// rest api, e.g. http://127.0.0.1:1317
+    pub api_url: String,
+    /// app grpc server, e.g. http://127.0.0.1:9090
+    pub app_grpc_url: Option<String>,
+    /// tendermint grpc server (`grpc_laddr`), e.g. http://127.0.0.1:26658
+    pub grpc_url: Option<String>,
+}
+
+impl EndpointUrls {
+    pub fn new(api_url: String) -> Self {
+        Self {
+            api_url,
+            app_grpc_url: None,
+            grpc_url: None,
+        }
+    }
+}
+
+/// builder of `FailoverClient`, the endpoints are tried in the order they are added
+#[derive(Debug, Clone, Default)]
+pub struct ClientBuilder {
+    endpoints: Vec<EndpointUrls>,
+    config: ClientConfig,
+}
+
+impl ClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_endpoint(&mut self, endpoint: EndpointUrls) -> &mut Self {
+        self.endpoints.push(endpoint);
+        self
+    }
+
+    /// the config of the clients of all the endpoints
+    pub fn set_config(&mut self, config: ClientConfig) -> &mut Self {
+        self.config = config;
+        self
+    }
+
+    /// create the clients and start with the first healthy endpoint
+    pub async fn build(&self) -> Result<FailoverClient, Error> {
+        if self.endpoints.is_empty() {
+            return Err(Error::InputError("no endpoint is added".to_string()));
+        }
+        let mut clients = vec![];
+        for endpoint in &self.endpoints {
+            let mut client = Client::try_new(endpoint.api_url.clone())?;
+            client.set_config(self.config.clone())?;
+            #[cfg(feature = "grpc")]
+            {
+                if let Some(app_grpc_url) = &endpoint.app_grpc_url {
+                    client.set_app_grpc_url(app_grpc_url.clone());
+                }
+                if let Some(grpc_url) = &endpoint.grpc_url {
+                    client.set_grpc_url(grpc_url.clone());
+                }
+            }
+            #[cfg(not(feature = "grpc"))]
+            {
+                if endpoint.app_grpc_url.is_some() || endpoint.grpc_url.is_some() {
+                    return Err(Error::InputError(
+                        "the grpc urls need the grpc feature".to_string(),
+                    ));
+                }
+            }
+            clients.push(client);
+        }
+        let client = FailoverClient {
+            clients,
+            active: Default::default(),
+        };
+        client.check_health().await?;
+        Ok(client)
+    }
+}
+
+/// ClientApi over the prioritized endpoints, a request which can't reach the active
+/// endpoint is retried on the next ones, which becomes the active endpoint if it succeeds.
+/// A broadcast is only retried when the active endpoint can't be connected, a broadcast
+/// timed out may have reached the node, so its timeout is returned rather than sending
+/// the tx again. Clones share the active endpoint.
+#[derive(Clone)]
+pub struct FailoverClient {
+    clients: Vec<Client>,
+    active: Arc<AtomicUsize>,
+}
+
+/// the broadcast request never left, e.g. the connection is refused
+fn broadcast_not_sent(error: &Error) -> bool {
+    match error {
+        Error::ConnectError(_) => true,
+        #[cfg(not(target_arch = "wasm32"))]
+        Error::RequestError(e) => e.is_connect(),
+        _ => false,
+    }
+}
+
+/// run the request on the active endpoint, then the following ones on connection errors,
+/// or on the errors of `$retry`
+macro_rules! failover {
+    ($self:ident, $client:ident => $request:expr) => {
+        failover!($self, $client => $request, Error::is_connection_error)
+    };
+    ($self:ident, $client:ident => $request:expr, $retry:expr) => {{
+        let count = $self.clients.len();
+        let start = $self.active.load(Ordering::Relaxed);
+        let mut index = start;
+        loop {
+            let $client = &$self.clients[index];
+            match $request.await {
+                Err(e) if $retry(&e) && (index + 1) % count != start => {
+                    log::warn!("endpoint {} failed: {}", $client.base_api_url(), e);
+                    index = (index + 1) % count;
+                }
+                result => {
+                    $self.active.store(index, Ordering::Relaxed);
+                    break result;
+                }
+            }
+        }
+    }};
+}
+
+impl FailoverClient {
+    /// the client of the active endpoint
+    pub fn active(&self) -> &Client {
+        &self.clients[self.active.load(Ordering::Relaxed)]
+    }
+
+    pub fn clients(&self) -> &[Client] {
+        &self.clients
+    }
+
+    /// switch to the first healthy endpoint in the priority order, return its index
+    pub async fn check_health(&self) -> Result<usize, Error> {
+        for (index, client) in self.clients.iter().enumerate() {
+            match client.health_check().await {
+                Ok(()) => {
+                    self.active.store(index, Ordering::Relaxed);
+                    return Ok(index);
+                }
+                Err(e) => log::warn!("endpoint {} is unhealthy: {}", client.base_api_url(), e),
+            }
+        }
+        Err(Error::ConnectError("no healthy endpoint".to_string()))
+    }
+}
+
+#[async_trait]
+impl ClientApi for FailoverClient {
+    async fn get_account(&self, address: &str) -> Result<Account, Error> {
+        failover!(self, client => client.get_account(address))
+    }
+
+    async fn get_account_info(&self, address: &str) -> Result<(u64, u64), Error> {
+        failover!(self, client => client.get_account_info(address))
+    }
+
+    async fn get_balance(&self, address: &str, denom: &str) -> Result<Coin, Error> {
+        failover!(self, client => client.get_balance(address, denom))
+    }
+
+    async fn get_all_balances(&self, address: &str) -> Result<Vec<Coin>, Error> {
+        failover!(self, client => client.get_all_balances(address))
+    }
+
+    async fn get_node_info(&self) -> Result<NodeInfo, Error> {
+        failover!(self, client => client.get_node_info())
+    }
+
+    async fn get_min_gas_prices(&self) -> Result<Vec<GasPrice>, Error> {
+        failover!(self, client => client.get_min_gas_prices())
+    }
+
+    async fn get_latest_block(&self) -> Result<Block, Error> {
+        failover!(self, client => client.get_latest_block())
+    }
+
+    async fn get_block(&self, height: u64) -> Result<Block, Error> {
+        failover!(self, client => client.get_block(height))
+    }
+
+    async fn get_tx(&self, hash: &str) -> Result<Option<TxResponse>, Error> {
+        failover!(self, client => client.get_tx(hash))
+    }
+
+    async fn simulate(&self, tx_bytes: &[u8]) -> Result<SimulateResponse, Error> {
+        failover!(self, client => client.simulate(tx_bytes))
+    }
+
+    async fn broadcast_raw_tx(
+        &self,
+        tx_bytes: Vec<u8>,
+        mode: SyncMode,
+    ) -> Result<TxResponse, Error> {
+        failover!(
+            self,
+            client => client.broadcast_raw_tx(tx_bytes.clone(), mode.clone()),
+            broadcast_not_sent
+        )
+    }
+
+    async fn broadcast_tx_bytes(&self, tx: Vec<u8>) -> Result<ResponseBroadcastTx, Error> {
+        failover!(self, client => client.broadcast_tx_bytes(tx.clone()), broadcast_not_sent)
+    }
+
+    async fn broadcast_amino_tx(
+        &self,
+        tx: Transaction<serde_json::Value>,
+    ) -> Result<String, Error> {
+        failover!(self, client => client.broadcast_amino_tx(tx.clone()), broadcast_not_sent)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_failover() {
+        // nothing listens on the port 1 of localhost
+        let mut builder = ClientBuilder::new();
+        builder
+            .add_endpoint(EndpointUrls::new("http://127.0.0.1:1".to_string()))
+            .add_endpoint(EndpointUrls::new("http://127.0.0.1:2".to_string()));
+        assert!(builder.build().await.is_err());
+
+        let client = FailoverClient {
+            clients: vec![
+                Client::try_new("http://127.0.0.1:1".to_string()).unwrap(),
+                Client::try_new("http://127.0.0.1:2".to_string()).unwrap(),
+            ],
+            active: Default::default(),
+        };
+        let error = client.get_latest_height().await.unwrap_err();
+        assert!(error.is_connection_error());
+        // every endpoint is tried once, the last one becomes active
+        assert_eq!(client.active().base_api_url(), "http://127.0.0.1:2");
+
+        // the broadcast is sent to the next endpoint as it didn't reach the active one
+        let error = client
+            .broadcast_raw_tx(vec![1], SyncMode::Sync)
+            .await
+            .unwrap_err();
+        assert!(broadcast_not_sent(&error));
+        assert_eq!(client.active().base_api_url(), "http://127.0.0.1:1");
+        assert!(!broadcast_not_sent(&Error::InputError(
+            "invalid".to_string()
+        )));
+    }
+}
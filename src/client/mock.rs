@@ -0,0 +1,302 @@
+//! ClientApi with programmable responses for unit tests
+
+use crate::client::ClientApi;
+use crate::error::Error;
+use crate::proto::cosmos::base::v1beta1::Coin;
+use crate::proto::tendermint::rpc::grpc::ResponseBroadcastTx;
+use crate::types::account::Account;
+use crate::types::basic::{GasPrice, SyncMode};
+use crate::types::block::Block;
+use crate::types::node_info::NodeInfo;
+use crate::types::transaction::Transaction;
+use crate::types::tx_response::{SimulateResponse, TxResponse};
+
+use async_trait::async_trait;
+use bitcoin_hashes::{sha256, Hash};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct MockState {
+    /// number of the following calls which fail
+    failures: usize,
+    accounts: HashMap<String, Account>,
+    balances: HashMap<String, Vec<Coin>>,
+    node_info: Option<NodeInfo>,
+    min_gas_prices: Vec<GasPrice>,
+    blocks: Vec<Block>,
+    txs: HashMap<String, TxResponse>,
+    simulate_response: Option<SimulateResponse>,
+    /// responses of `broadcast_raw_tx`, a successful response is made up when it is empty
+    tx_responses: VecDeque<TxResponse>,
+    /// responses of `broadcast_tx_bytes`, a successful response is made up when it is empty
+    broadcast_responses: VecDeque<ResponseBroadcastTx>,
+    /// tx bytes passed to `simulate` and the broadcasts
+    simulated_txs: Vec<Vec<u8>>,
+    broadcasted_txs: Vec<Vec<u8>>,
+}
+
+/// ClientApi serving the programmed data and recording the simulated and broadcasted txs.
+/// Clones share the same state.
+#[derive(Clone, Default)]
+pub struct MockClient {
+    state: Arc<Mutex<MockState>>,
+}
+
+fn not_found(what: String) -> Error {
+    Error::ClientError(format!("{} is not set in the mock", what))
+}
+
+/// uppercase hex sha256 of the tx bytes, the same as the tx hash of the chain
+pub fn tx_hash(tx_bytes: &[u8]) -> String {
+    hex::encode_upper(sha256::Hash::hash(tx_bytes).into_inner())
+}
+
+impl MockClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().expect("mock state lock poisoned")
+    }
+
+    /// lock the state, fail if a failure is programmed
+    fn checked_state(&self) -> Result<std::sync::MutexGuard<'_, MockState>, Error> {
+        let mut state = self.state();
+        if state.failures > 0 {
+            state.failures -= 1;
+            return Err(Error::ClientError("mock client failure".to_string()));
+        }
+        Ok(state)
+    }
+
+    /// make the next `times` calls fail
+    pub fn fail_next_requests(&self, times: usize) -> &Self {
+        self.state().failures = times;
+        self
+    }
+
+    pub fn set_account(&self, account: Account) -> &Self {
+        self.state()
+            .accounts
+            .insert(account.address().to_string(), account);
+        self
+    }
+
+    pub fn set_balances(&self, address: &str, coins: Vec<Coin>) -> &Self {
+        self.state().balances.insert(address.to_string(), coins);
+        self
+    }
+
+    pub fn set_node_info(&self, node_info: NodeInfo) -> &Self {
+        self.state().node_info = Some(node_info);
+        self
+    }
+
+    pub fn set_min_gas_prices(&self, gas_prices: Vec<GasPrice>) -> &Self {
+        self.state().min_gas_prices = gas_prices;
+        self
+    }
+
+    /// add a block, the highest block is the latest one
+    pub fn add_block(&self, block: Block) -> &Self {
+        self.state().blocks.push(block);
+        self
+    }
+
+    pub fn add_tx(&self, tx_response: TxResponse) -> &Self {
+        self.state()
+            .txs
+            .insert(tx_response.txhash.clone(), tx_response);
+        self
+    }
+
+    pub fn set_simulate_response(&self, response: SimulateResponse) -> &Self {
+        self.state().simulate_response = Some(response);
+        self
+    }
+
+    /// queue the response of the next `broadcast_raw_tx`
+    pub fn push_tx_response(&self, response: TxResponse) -> &Self {
+        self.state().tx_responses.push_back(response);
+        self
+    }
+
+    /// queue the response of the next `broadcast_tx_bytes`
+    pub fn push_broadcast_response(&self, response: ResponseBroadcastTx) -> &Self {
+        self.state().broadcast_responses.push_back(response);
+        self
+    }
+
+    pub fn simulated_txs(&self) -> Vec<Vec<u8>> {
+        self.state().simulated_txs.clone()
+    }
+
+    /// tx bytes passed to `broadcast_raw_tx` and `broadcast_tx_bytes`, including the rejected txs
+    pub fn broadcasted_txs(&self) -> Vec<Vec<u8>> {
+        self.state().broadcasted_txs.clone()
+    }
+}
+
+#[async_trait]
+impl ClientApi for MockClient {
+    async fn get_account(&self, address: &str) -> Result<Account, Error> {
+        self.checked_state()?
+            .accounts
+            .get(address)
+            .cloned()
+            .ok_or_else(|| not_found(format!("account {}", address)))
+    }
+
+    async fn get_balance(&self, address: &str, denom: &str) -> Result<Coin, Error> {
+        let coin = self
+            .checked_state()?
+            .balances
+            .get(address)
+            .and_then(|coins| coins.iter().find(|coin| coin.denom == denom).cloned());
+        Ok(coin.unwrap_or(Coin {
+            denom: denom.to_string(),
+            amount: "0".to_string(),
+        }))
+    }
+
+    async fn get_all_balances(&self, address: &str) -> Result<Vec<Coin>, Error> {
+        Ok(self
+            .checked_state()?
+            .balances
+            .get(address)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn get_node_info(&self) -> Result<NodeInfo, Error> {
+        self.checked_state()?
+            .node_info
+            .clone()
+            .ok_or_else(|| not_found("node info".to_string()))
+    }
+
+    async fn get_min_gas_prices(&self) -> Result<Vec<GasPrice>, Error> {
+        Ok(self.checked_state()?.min_gas_prices.clone())
+    }
+
+    async fn get_latest_block(&self) -> Result<Block, Error> {
+        self.checked_state()?
+            .blocks
+            .iter()
+            .max_by_key(|block| block.height())
+            .cloned()
+            .ok_or_else(|| not_found("block".to_string()))
+    }
+
+    async fn get_block(&self, height: u64) -> Result<Block, Error> {
+        self.checked_state()?
+            .blocks
+            .iter()
+            .find(|block| block.height() == height)
+            .cloned()
+            .ok_or_else(|| not_found(format!("block {}", height)))
+    }
+
+    async fn get_tx(&self, hash: &str) -> Result<Option<TxResponse>, Error> {
+        Ok(self.checked_state()?.txs.get(hash).cloned())
+    }
+
+    async fn simulate(&self, tx_bytes: &[u8]) -> Result<SimulateResponse, Error> {
+        let mut state = self.checked_state()?;
+        state.simulated_txs.push(tx_bytes.to_vec());
+        state
+            .simulate_response
+            .clone()
+            .ok_or_else(|| not_found("simulate response".to_string()))
+    }
+
+    async fn broadcast_raw_tx(
+        &self,
+        tx_bytes: Vec<u8>,
+        _mode: SyncMode,
+    ) -> Result<TxResponse, Error> {
+        let mut state = self.checked_state()?;
+        let response = state
+            .tx_responses
+            .pop_front()
+            .unwrap_or_else(|| TxResponse {
+                height: 0,
+                txhash: tx_hash(&tx_bytes),
+                codespace: String::new(),
+                code: 0,
+                raw_log: String::new(),
+                gas_wanted: 0,
+                gas_used: 0,
+            });
+        state.broadcasted_txs.push(tx_bytes);
+        Ok(response)
+    }
+
+    async fn broadcast_tx_bytes(&self, tx: Vec<u8>) -> Result<ResponseBroadcastTx, Error> {
+        let mut state = self.checked_state()?;
+        let response = state.broadcast_responses.pop_front().unwrap_or_default();
+        state.broadcasted_txs.push(tx);
+        Ok(response)
+    }
+
+    /// the json of the tx is recorded, the responses are shared with `broadcast_raw_tx`
+    async fn broadcast_amino_tx(
+        &self,
+        tx: Transaction<serde_json::Value>,
+    ) -> Result<String, Error> {
+        let tx_json = serde_json::to_vec(&tx).map_err(|e| Error::SerializeError(e.to_string()))?;
+        let response = self.broadcast_raw_tx(tx_json, tx.mode).await?;
+        match response.error() {
+            Some(error) => Err(error.into()),
+            None => Ok(response.txhash),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::account::BaseAccount;
+
+    #[tokio::test]
+    async fn test_mock_client() {
+        let client = MockClient::new();
+        client.set_account(Account::BaseAccount(BaseAccount {
+            address: "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf".to_string(),
+            pub_key: None,
+            account_number: 9,
+            sequence: 25,
+        }));
+        assert_eq!(
+            client
+                .get_account_info("cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf")
+                .await
+                .unwrap(),
+            (9, 25)
+        );
+        assert!(client.get_account_info("cro1unknown").await.is_err());
+        assert_eq!(
+            client
+                .get_balance("cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf", "basecro")
+                .await
+                .unwrap()
+                .amount,
+            "0"
+        );
+
+        client.fail_next_requests(1);
+        assert!(client
+            .broadcast_raw_tx(vec![1], SyncMode::Sync)
+            .await
+            .is_err());
+        let response = client
+            .broadcast_raw_tx(vec![1], SyncMode::Sync)
+            .await
+            .unwrap();
+        assert!(response.is_success());
+        assert_eq!(response.txhash, tx_hash(&[1]));
+        assert_eq!(client.broadcasted_txs(), vec![vec![1]]);
+    }
+}
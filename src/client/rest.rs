@@ -0,0 +1,482 @@
+//! Client of the rest api of the app, which serves the amino and the protobuf txs
+
+use crate::client::ClientConfig;
+use crate::error::{BroadcastError, Error};
+use crate::proto::cosmos::base::v1beta1::Coin;
+use crate::types::account::Account;
+use crate::types::basic::{GasPrice, SyncMode};
+use crate::types::block::Block;
+use crate::types::decoded_tx::DecodedTx;
+use crate::types::mempool::UnconfirmedTxs;
+use crate::types::node_info::NodeInfo;
+use crate::types::params::{params_from_json, AuthParams, BankParams, StakingParams};
+use crate::types::transaction::Transaction;
+use crate::types::tx_response::{Event, SimulateResponse, TxResponse};
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// the clones share the connection pool
+#[derive(Clone)]
+pub struct RestClient {
+    // base api url is set in section `address` in $CHAIN_MAIND_HOME/config/app.toml
+    base_api_url: String,
+    // tendermint rpc url is set in section `rpc.laddr` in $CHAIN_MAIND_HOME/config/config.toml
+    tendermint_rpc_url: Option<String>,
+    config: ClientConfig,
+    // keeps the connection pool of the rest api
+    http: reqwest::Client,
+    // whether the event attributes are base64 encoded, detected from the node info once
+    base64_event_attributes: Arc<OnceCell<bool>>,
+}
+
+/// parse the `{"denom": "basecro", "amount": "100"}` of the rest api
+fn coin_from_json(value: &serde_json::Value) -> Result<Coin, Error> {
+    match (value["denom"].as_str(), value["amount"].as_str()) {
+        (Some(denom), Some(amount)) => Ok(Coin {
+            denom: denom.to_string(),
+            amount: amount.to_string(),
+        }),
+        _ => Err(Error::ClientError(format!("invalid coin: {}", value))),
+    }
+}
+
+/// the base64 next key is passed back in the query string
+fn urlencode_base64(key: &str) -> String {
+    key.replace('+', "%2B")
+        .replace('/', "%2F")
+        .replace('=', "%3D")
+}
+
+fn http_client(config: &ClientConfig) -> Result<reqwest::Client, Error> {
+    use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(config.connect_timeout)
+        .timeout(config.request_timeout);
+    if let Some(proxy) = &config.proxy {
+        builder = builder.proxy(reqwest::Proxy::all(proxy.as_str())?);
+    }
+    if let Some(user_agent) = &config.user_agent {
+        builder = builder.user_agent(user_agent.as_str());
+    }
+    let mut headers = HeaderMap::new();
+    for (key, value) in &config.headers {
+        let key = HeaderName::from_bytes(key.as_bytes())
+            .map_err(|e| Error::InputError(format!("invalid header name {}: {}", key, e)))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| Error::InputError(format!("invalid header value {}: {}", value, e)))?;
+        headers.append(key, value);
+    }
+    Ok(builder.default_headers(headers).build()?)
+}
+
+impl RestClient {
+    /// panics if the tls backend can't be initialized, the same as `reqwest::Client::new`,
+    /// use `try_new` to get the error instead
+    #[deprecated(note = "panics if the http client can't be built, use `try_new` instead")]
+    pub fn new(base_api_url: String) -> Self {
+        Self::try_new(base_api_url).expect("build http client")
+    }
+
+    /// create the client, an error if the http client can't be built
+    pub fn try_new(base_api_url: String) -> Result<Self, Error> {
+        Ok(Self {
+            base_api_url,
+            tendermint_rpc_url: None,
+            config: ClientConfig::default(),
+            http: http_client(&ClientConfig::default())?,
+            base64_event_attributes: Arc::new(OnceCell::new()),
+        })
+    }
+
+    pub fn base_api_url(&self) -> &str {
+        &self.base_api_url
+    }
+
+    /// set the url of the tendermint rpc (e.g. http://127.0.0.1:26657), used to query the mempool
+    pub fn set_tendermint_rpc_url(&mut self, tendermint_rpc_url: String) -> &mut Self {
+        self.tendermint_rpc_url = Some(tendermint_rpc_url);
+        self
+    }
+
+    /// set the timeouts, proxy and headers, the existing connections are dropped
+    pub fn set_config(&mut self, config: ClientConfig) -> Result<&mut Self, Error> {
+        self.http = http_client(&config)?;
+        self.config = config;
+        Ok(self)
+    }
+
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    /// whether the node encodes the event attributes in base64 (tendermint before 0.35),
+    /// instead of detecting it from the node info
+    pub fn set_base64_event_attributes(&mut self, base64_attributes: bool) -> &mut Self {
+        let cell = OnceCell::new();
+        let _ = cell.set(base64_attributes);
+        self.base64_event_attributes = Arc::new(cell);
+        self
+    }
+
+    async fn base64_event_attributes(&self) -> Result<bool, Error> {
+        if let Some(base64_attributes) = self.base64_event_attributes.get() {
+            return Ok(*base64_attributes);
+        }
+        let base64_attributes = self.get_node_info().await?.base64_event_attributes();
+        Ok(*self
+            .base64_event_attributes
+            .get_or_init(|| base64_attributes))
+    }
+
+    /// check the rest api responds and the node is not catching up
+    pub async fn health_check(&self) -> Result<(), Error> {
+        if self.get_node_info().await?.syncing {
+            return Err(Error::ClientError(format!(
+                "node {} is catching up",
+                self.base_api_url
+            )));
+        }
+        Ok(())
+    }
+
+    /// simulate the encoded `TxRaw` (the signatures can be empty), the `tx_bytes` of the
+    /// request are only accepted by the rest api of cosmos-sdk 0.43+, simulate through the
+    /// app grpc server (`Client::set_app_grpc_url`) with the older nodes
+    pub async fn simulate(&self, tx_bytes: &[u8]) -> Result<SimulateResponse, Error> {
+        DecodedTx::decode(tx_bytes)?;
+        let url = format!("{}/cosmos/tx/v1beta1/simulate", self.base_api_url);
+        let body = serde_json::json!({ "tx_bytes": base64::encode(tx_bytes) });
+        let response = self.http.post(&url).json(&body).send().await?;
+        let status = response.status();
+        let response = response.json::<serde_json::Value>().await?;
+        if !status.is_success() {
+            return Err(Error::HttpStatus {
+                status: status.as_u16(),
+                message: format!(
+                    "simulate failed (the rest simulate needs cosmos-sdk 0.43+): {}",
+                    response
+                ),
+            });
+        }
+        let base64_attributes = self.base64_event_attributes().await?;
+        let gas = |key: &str| {
+            response["gas_info"][key]
+                .as_str()
+                .and_then(|gas| gas.parse().ok())
+                .ok_or_else(|| {
+                    Error::ClientError(format!("invalid simulate response: {}", response))
+                })
+        };
+        Ok(SimulateResponse {
+            gas_wanted: gas("gas_wanted")?,
+            gas_used: gas("gas_used")?,
+            events: response["result"]["events"]
+                .as_array()
+                .map(|events| {
+                    events
+                        .iter()
+                        .map(|event| Event::from_json(event, base64_attributes))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    pub async fn get_balance(&self, address: &str, denom: &str) -> Result<Coin, Error> {
+        let url = format!(
+            "{}/cosmos/bank/v1beta1/balances/{}/{}",
+            self.base_api_url, address, denom
+        );
+        let response = self
+            .http
+            .get(&url)
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        coin_from_json(&response["balance"])
+    }
+
+    /// balances of all the denoms, the pages are fetched one by one
+    pub async fn get_all_balances(&self, address: &str) -> Result<Vec<Coin>, Error> {
+        let mut balances = vec![];
+        let mut next_key: Option<String> = None;
+        loop {
+            let mut url = format!(
+                "{}/cosmos/bank/v1beta1/balances/{}",
+                self.base_api_url, address
+            );
+            if let Some(key) = &next_key {
+                url = format!("{}?pagination.key={}", url, urlencode_base64(key));
+            }
+            let response = self
+                .http
+                .get(&url)
+                .send()
+                .await?
+                .json::<serde_json::Value>()
+                .await?;
+            let page = response["balances"]
+                .as_array()
+                .ok_or_else(|| Error::ClientError(format!("invalid balances: {}", response)))?;
+            for coin in page {
+                balances.push(coin_from_json(coin)?);
+            }
+            match response["pagination"]["next_key"].as_str() {
+                Some(key) if !key.is_empty() => next_key = Some(key.to_string()),
+                _ => return Ok(balances),
+            }
+        }
+    }
+
+    /// the result of the tx, None if it is not included yet
+    pub async fn get_tx(&self, hash: &str) -> Result<Option<TxResponse>, Error> {
+        let url = format!("{}/cosmos/tx/v1beta1/txs/{}", self.base_api_url, hash);
+        let response = self.http.get(&url).send().await?;
+        let status = response.status();
+        let body = response.json::<serde_json::Value>().await?;
+        if status == reqwest::StatusCode::NOT_FOUND
+            || body["message"]
+                .as_str()
+                .map(|message| message.contains("not found"))
+                .unwrap_or(false)
+        {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(Error::HttpStatus {
+                status: status.as_u16(),
+                message: format!("get tx failed: {}", body),
+            });
+        }
+        let tx_response = serde_json::from_value(body["tx_response"].clone())
+            .map_err(|e| Error::SerializeError(e.to_string()))?;
+        Ok(Some(tx_response))
+    }
+
+    /// height of the latest block
+    pub async fn get_latest_height(&self) -> Result<u64, Error> {
+        Ok(self.get_latest_block().await?.height())
+    }
+
+    pub async fn get_latest_block(&self) -> Result<Block, Error> {
+        let url = format!(
+            "{}/cosmos/base/tendermint/v1beta1/blocks/latest",
+            self.base_api_url
+        );
+        Block::from_json(self.get_json(&url).await?)
+    }
+
+    /// number of the blocks since the block at `height` (included), 0 if it is not committed yet
+    pub async fn get_confirmations(&self, height: u64) -> Result<u64, Error> {
+        let latest = self.get_latest_height().await?;
+        Ok((latest + 1).saturating_sub(height))
+    }
+
+    pub async fn get_block(&self, height: u64) -> Result<Block, Error> {
+        let url = format!(
+            "{}/cosmos/base/tendermint/v1beta1/blocks/{}",
+            self.base_api_url, height
+        );
+        Block::from_json(self.get_json(&url).await?)
+    }
+
+    /// chain id, versions and sync status of the node
+    pub async fn get_node_info(&self) -> Result<NodeInfo, Error> {
+        let url = format!(
+            "{}/cosmos/base/tendermint/v1beta1/node_info",
+            self.base_api_url
+        );
+        let node_info = self
+            .http
+            .get(&url)
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let url = format!(
+            "{}/cosmos/base/tendermint/v1beta1/syncing",
+            self.base_api_url
+        );
+        let response = self
+            .http
+            .get(&url)
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let syncing = response["syncing"]
+            .as_bool()
+            .ok_or_else(|| Error::ClientError(format!("invalid syncing response: {}", response)))?;
+        NodeInfo::from_json(node_info, syncing)
+    }
+
+    /// the `minimum-gas-prices` of the node, empty if the node accepts any fee,
+    /// the node config query is only served by the cosmos sdk v0.46+ nodes
+    pub async fn get_min_gas_prices(&self) -> Result<Vec<GasPrice>, Error> {
+        let url = format!("{}/cosmos/base/node/v1beta1/config", self.base_api_url);
+        let response = self.get_json(&url).await?;
+        let min_gas_price = response["minimum_gas_price"].as_str().ok_or_else(|| {
+            Error::ClientError(format!("invalid node config response: {}", response))
+        })?;
+        GasPrice::parse_list(min_gas_price)
+    }
+
+    fn tendermint_rpc_url(&self) -> Result<&str, Error> {
+        self.tendermint_rpc_url
+            .as_deref()
+            .ok_or_else(|| Error::ClientError("tendermint rpc url is not set".to_string()))
+    }
+
+    /// number and size of the txs in the mempool, without the txs
+    pub async fn num_unconfirmed_txs(&self) -> Result<UnconfirmedTxs, Error> {
+        let url = format!("{}/num_unconfirmed_txs", self.tendermint_rpc_url()?);
+        UnconfirmedTxs::from_json(&self.get_json(&url).await?)
+    }
+
+    /// the txs in the mempool, at most 100 of them which is the limit of the tendermint rpc
+    pub async fn unconfirmed_txs(&self) -> Result<UnconfirmedTxs, Error> {
+        let url = format!("{}/unconfirmed_txs?limit=100", self.tendermint_rpc_url()?);
+        UnconfirmedTxs::from_json(&self.get_json(&url).await?)
+    }
+
+    /// whether the tx is still in the mempool, the resubmission of a tx which is neither
+    /// pending nor included (see `get_tx`) is safe. It errors if the tx is not in the
+    /// returned txs but the mempool has more txs than the rpc returns.
+    pub async fn is_tx_pending(&self, hash: &str) -> Result<bool, Error> {
+        let unconfirmed = self.unconfirmed_txs().await?;
+        if unconfirmed.contains(hash)? {
+            return Ok(true);
+        }
+        if !unconfirmed.is_complete() {
+            return Err(Error::ClientError(format!(
+                "tx {} is not in the first {} of the {} unconfirmed txs",
+                hash, unconfirmed.count, unconfirmed.total
+            )));
+        }
+        Ok(false)
+    }
+
+    pub async fn get_auth_params(&self) -> Result<AuthParams, Error> {
+        let url = format!("{}/cosmos/auth/v1beta1/params", self.base_api_url);
+        params_from_json(&self.get_json(&url).await?)
+    }
+
+    pub async fn get_bank_params(&self) -> Result<BankParams, Error> {
+        let url = format!("{}/cosmos/bank/v1beta1/params", self.base_api_url);
+        params_from_json(&self.get_json(&url).await?)
+    }
+
+    pub async fn get_staking_params(&self) -> Result<StakingParams, Error> {
+        let url = format!("{}/cosmos/staking/v1beta1/params", self.base_api_url);
+        params_from_json(&self.get_json(&url).await?)
+    }
+
+    /// get the json of the rest api, a non success status is an error
+    async fn get_json(&self, url: &str) -> Result<serde_json::Value, Error> {
+        let response = self.http.get(url).send().await?;
+        let status = response.status();
+        let response = response.json::<serde_json::Value>().await?;
+        if !status.is_success() {
+            return Err(Error::HttpStatus {
+                status: status.as_u16(),
+                message: format!("query {} failed: {}", url, response),
+            });
+        }
+        Ok(response)
+    }
+
+    pub async fn get_account(&self, address: &str) -> Result<Account, Error> {
+        let url = format!(
+            "{}/cosmos/auth/v1beta1/accounts/{}",
+            self.base_api_url, address
+        );
+        let response = self.http.get(&url).send().await?;
+        let status = response.status();
+        let response = response.json::<serde_json::Value>().await?;
+        if !status.is_success() {
+            return Err(Error::HttpStatus {
+                status: status.as_u16(),
+                message: format!("query account {} failed: {}", address, response),
+            });
+        }
+        // {'account': {'@type': '/cosmos.auth.v1beta1.BaseAccount', 'address': 'cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf', 'pub_key': {'@type': '/cosmos.crypto.secp256k1.PubKey', 'key': 'AntL+UxMyJ9NZ9DGLp2v7a3dlSxiNXMaItyOXSRw8iYi'}, 'account_number': '9', 'sequence': '25'}}
+        Account::from_json(&response["account"])
+    }
+
+    /// return the account number and the sequence of the account
+    pub async fn get_account_info(&self, address: &str) -> Result<(u64, u64), Error> {
+        let account = self.get_account(address).await?;
+        Ok((account.account_number(), account.sequence()))
+    }
+
+    /// broadcast the encoded `TxRaw`
+    pub async fn broadcast_raw_tx(
+        &self,
+        tx_bytes: Vec<u8>,
+        mode: SyncMode,
+    ) -> Result<TxResponse, Error> {
+        let url = format!("{}/cosmos/tx/v1beta1/txs", self.base_api_url);
+        let mode = match mode {
+            SyncMode::Sync => "BROADCAST_MODE_SYNC",
+            SyncMode::Async => "BROADCAST_MODE_ASYNC",
+            SyncMode::Block => "BROADCAST_MODE_BLOCK",
+        };
+        let body = serde_json::json!({
+            "tx_bytes": base64::encode(tx_bytes),
+            "mode": mode,
+        });
+        let response = self
+            .http
+            .post(&url)
+            .timeout(self.config.broadcast_timeout)
+            .json(&body)
+            .send()
+            .await?;
+        let status = response.status();
+        let response = response.json::<serde_json::Value>().await?;
+        if !status.is_success() {
+            return Err(Error::HttpStatus {
+                status: status.as_u16(),
+                message: format!("broadcast failed: {}", response),
+            });
+        }
+        serde_json::from_value(response["tx_response"].clone())
+            .map_err(|e| Error::SerializeError(e.to_string()))
+    }
+
+    /// broadcast the amino StdTx, return the tx hash
+    pub async fn broadcast_amino_tx<M: Serialize>(
+        &self,
+        tx: Transaction<M>,
+    ) -> Result<String, Error> {
+        let url = format!("{}/txs", self.base_api_url);
+        let response = self
+            .http
+            .post(&url)
+            .timeout(self.config.broadcast_timeout)
+            .header("Content-Type", "application/json")
+            .json(&tx)
+            .send()
+            .await?;
+        if response.status() != reqwest::StatusCode::OK {
+            return Err(Error::HttpStatus {
+                status: response.status().as_u16(),
+                message: format!("send failed, response: {:?}", response),
+            });
+        }
+        let response = response.json::<serde_json::Value>().await?;
+        let code = response["code"].as_u64().unwrap_or_default() as u32;
+        let codespace = response["codespace"].as_str().unwrap_or_default();
+        let raw_log = response["raw_log"].as_str().unwrap_or_default();
+        if let Some(error) = BroadcastError::from_code(codespace, code, raw_log) {
+            return Err(error.into());
+        }
+        let tx_hash = response["txhash"].as_str().ok_or_else(|| {
+            Error::ClientError(format!("invalid broadcast response: {}", response))
+        })?;
+        Ok(tx_hash.into())
+    }
+}
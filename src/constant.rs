@@ -2,3 +2,10 @@ pub const CRO: u64 = 100_000_000;
 pub static COIN_TYPE: u32 = 394;
 pub static ACCOUNT_ADDRESS_PREFIX: &'static str = "cro";
 pub static FUNDRAISER_PATH: &'static str = "m/44'/394'/0'/0/0";
+pub static ETH_COIN_TYPE: u32 = 60;
+pub static ETH_FUNDRAISER_PATH: &'static str = "m/44'/60'/0'/0/0";
+pub static COSMOS_COIN_TYPE: u32 = 118;
+/// 44'/118'/0'/0'/0', the default path of the validator app and the consensus key
+pub const VALIDATOR_PATH: &str = "m/44'/118'/0'/0'/0'";
+/// default `max_memo_characters` of the auth module
+pub static MAX_MEMO_LENGTH: usize = 256;
@@ -3,12 +3,15 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum Error {
-    #[error("mnemonic error")]
+    #[error("mnemonic error: {0}")]
     MnemonicError(#[from] MnemonicError),
 
     #[error("invalid input: {0}")]
     InputError(String),
 
+    #[error("amount overflow: {0}")]
+    AmountOverflow(String),
+
     #[error("secp error")]
     SecpError(#[from] secp256k1::Error),
 
@@ -18,12 +21,272 @@ pub enum Error {
     #[error("ledger error: {0}")]
     LedgerError(String),
 
+    #[error("ledger operation timed out after {0:?}")]
+    LedgerTimeout(std::time::Duration),
+
     #[error("prost encode error")]
     ProstEncodeError(#[from] prost::EncodeError),
 
+    #[error("prost decode error")]
+    ProstDecodeError(#[from] prost::DecodeError),
+
+    #[cfg(not(target_arch = "wasm32"))]
     #[error("client request error")]
     RequestError(#[from] reqwest::Error),
 
     #[error("client error: {0}")]
     ClientError(String),
+
+    /// the rest api returns a non success status
+    #[error("http status {status}: {message}")]
+    HttpStatus { status: u16, message: String },
+
+    /// the grpc call returns a non ok status
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("grpc status {code:?}: {message}")]
+    GrpcStatus { code: tonic::Code, message: String },
+
+    /// the endpoint can't be reached
+    #[error("connect error: {0}")]
+    ConnectError(String),
+
+    #[error("keystore error: {0}")]
+    KeystoreError(String),
+
+    #[error("keyring error: {0}")]
+    KeyringError(String),
+
+    #[error("hsm error: {0}")]
+    HsmError(String),
+
+    #[error("vault error: {0}")]
+    VaultError(String),
+
+    #[error("signing is unavailable for a watch-only key service")]
+    SigningUnavailable,
+
+    /// the key service can't do the operation, e.g. sign a prehashed digest
+    #[error("unsupported by the key service: {0}")]
+    Unsupported(String),
+
+    #[error("io error")]
+    IoError(#[from] std::io::Error),
+
+    #[error("broadcast error: {0}")]
+    BroadcastError(#[from] BroadcastError),
+
+    /// the signer server fails to bind or serve
+    #[error("server error: {0}")]
+    ServerError(String),
+}
+
+impl Error {
+    /// whether the request failed to reach the endpoint (refused, timed out...),
+    /// so it may succeed on another endpoint
+    pub fn is_connection_error(&self) -> bool {
+        match self {
+            Error::ConnectError(_) => true,
+            #[cfg(not(target_arch = "wasm32"))]
+            Error::RequestError(e) => e.is_connect() || e.is_timeout(),
+            _ => false,
+        }
+    }
+
+    /// whether the same request may succeed later: the network failures, the timeouts and
+    /// the overloaded or unavailable servers. The rejections of the tx (bad signature,
+    /// insufficient funds...) and the invalid inputs are deterministic, never retry them
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::ConnectError(_) => true,
+            #[cfg(not(target_arch = "wasm32"))]
+            Error::RequestError(e) => {
+                e.is_connect()
+                    || e.is_timeout()
+                    || e.status()
+                        .map_or(false, |status| is_retryable_http_status(status.as_u16()))
+            }
+            Error::HttpStatus { status, .. } => is_retryable_http_status(*status),
+            #[cfg(not(target_arch = "wasm32"))]
+            Error::GrpcStatus { code, .. } => matches!(
+                code,
+                tonic::Code::Unavailable
+                    | tonic::Code::DeadlineExceeded
+                    | tonic::Code::ResourceExhausted
+                    | tonic::Code::Aborted
+            ),
+            Error::BroadcastError(e) => e.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// the error of a grpc call, `context` tells the call
+    #[cfg(all(any(test, feature = "grpc"), not(target_arch = "wasm32")))]
+    pub(crate) fn grpc_status(context: &str, status: tonic::Status) -> Self {
+        Error::GrpcStatus {
+            code: status.code(),
+            message: format!("{}: {}", context, status.message()),
+        }
+    }
+}
+
+/// request timeout, too many requests and the server errors
+fn is_retryable_http_status(status: u16) -> bool {
+    status == 408 || status == 429 || status >= 500
+}
+
+/// codespace of the errors defined by the cosmos sdk (types/errors)
+const SDK_CODESPACE: &str = "sdk";
+const CODE_UNAUTHORIZED: u32 = 4;
+const CODE_INSUFFICIENT_FUNDS: u32 = 5;
+const CODE_OUT_OF_GAS: u32 = 11;
+const CODE_MEMPOOL_FULL: u32 = 20;
+const CODE_WRONG_SEQUENCE: u32 = 32;
+
+/// a tx rejected by the node, decoded from the `code`, `codespace` and `raw_log`
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BroadcastError {
+    #[error("insufficient funds: {raw_log}")]
+    InsufficientFunds { raw_log: String },
+
+    #[error("out of gas: {raw_log}")]
+    OutOfGas { raw_log: String },
+
+    /// `expected` is the account sequence reported by the node, if the log has it
+    #[error("account sequence mismatch: {raw_log}")]
+    SequenceMismatch {
+        expected: Option<u64>,
+        raw_log: String,
+    },
+
+    #[error("invalid signature: {raw_log}")]
+    InvalidSignature { raw_log: String },
+
+    /// the node is busy, the same tx can be broadcast again later
+    #[error("mempool is full: {raw_log}")]
+    MempoolFull { raw_log: String },
+
+    #[error("code {code} of codespace {codespace}: {raw_log}")]
+    Unknown {
+        codespace: String,
+        code: u32,
+        raw_log: String,
+    },
+}
+
+impl BroadcastError {
+    /// decode the abci result, None if the code is 0 (success)
+    pub fn from_code(codespace: &str, code: u32, raw_log: &str) -> Option<Self> {
+        if code == 0 {
+            return None;
+        }
+        let raw_log = raw_log.to_string();
+        // the old nodes leave the codespace empty
+        if codespace != SDK_CODESPACE && !codespace.is_empty() {
+            return Some(BroadcastError::Unknown {
+                codespace: codespace.to_string(),
+                code,
+                raw_log,
+            });
+        }
+        let error = match code {
+            CODE_INSUFFICIENT_FUNDS => BroadcastError::InsufficientFunds { raw_log },
+            CODE_OUT_OF_GAS => BroadcastError::OutOfGas { raw_log },
+            CODE_WRONG_SEQUENCE => BroadcastError::SequenceMismatch {
+                expected: expected_sequence(&raw_log),
+                raw_log,
+            },
+            CODE_UNAUTHORIZED => BroadcastError::InvalidSignature { raw_log },
+            CODE_MEMPOOL_FULL => BroadcastError::MempoolFull { raw_log },
+            _ => BroadcastError::Unknown {
+                codespace: codespace.to_string(),
+                code,
+                raw_log,
+            },
+        };
+        Some(error)
+    }
+}
+
+impl BroadcastError {
+    /// only a full mempool is transient, a sequence mismatch needs a tx signed with the
+    /// new sequence and the other rejections fail again
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, BroadcastError::MempoolFull { .. })
+    }
+}
+
+/// parse `account sequence mismatch, expected 26, got 25: incorrect account sequence`
+fn expected_sequence(raw_log: &str) -> Option<u64> {
+    let start = raw_log.find("expected ")? + "expected ".len();
+    let digits: String = raw_log[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_broadcast_error() {
+        assert_eq!(BroadcastError::from_code("", 0, ""), None);
+        assert_eq!(
+            BroadcastError::from_code(
+                "sdk",
+                32,
+                "account sequence mismatch, expected 26, got 25: incorrect account sequence"
+            ),
+            Some(BroadcastError::SequenceMismatch {
+                expected: Some(26),
+                raw_log:
+                    "account sequence mismatch, expected 26, got 25: incorrect account sequence"
+                        .to_string(),
+            })
+        );
+        assert!(matches!(
+            BroadcastError::from_code("sdk", 5, "0basecro is smaller than 100basecro"),
+            Some(BroadcastError::InsufficientFunds { .. })
+        ));
+        assert!(matches!(
+            BroadcastError::from_code("", 11, "out of gas in location: WriteFlat"),
+            Some(BroadcastError::OutOfGas { .. })
+        ));
+        assert!(matches!(
+            BroadcastError::from_code("sdk", 4, "signature verification failed"),
+            Some(BroadcastError::InvalidSignature { .. })
+        ));
+        assert!(BroadcastError::from_code("sdk", 20, "mempool is full")
+            .unwrap()
+            .is_retryable());
+        assert_eq!(
+            BroadcastError::from_code("staking", 5, "validator does not exist"),
+            Some(BroadcastError::Unknown {
+                codespace: "staking".to_string(),
+                code: 5,
+                raw_log: "validator does not exist".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let http = |status| Error::HttpStatus {
+            status,
+            message: String::new(),
+        };
+        assert!(http(503).is_retryable());
+        assert!(http(429).is_retryable());
+        assert!(!http(400).is_retryable());
+        assert!(!http(404).is_retryable());
+        assert!(Error::ConnectError("refused".to_string()).is_retryable());
+        assert!(Error::grpc_status("query", tonic::Status::unavailable("down")).is_retryable());
+        assert!(!Error::grpc_status("query", tonic::Status::not_found("none")).is_retryable());
+        assert!(!Error::InputError("bad".to_string()).is_retryable());
+        let rejected = BroadcastError::from_code("sdk", 5, "insufficient funds").unwrap();
+        assert!(!Error::from(rejected).is_retryable());
+        let rejected = BroadcastError::from_code("sdk", 4, "signature verification failed");
+        assert!(!Error::from(rejected.unwrap()).is_retryable());
+    }
 }
@@ -0,0 +1,110 @@
+use crate::constant::{COIN_TYPE, COSMOS_COIN_TYPE};
+use crate::hd_wallet::mnemonic::MnemonicError;
+use std::fmt;
+use std::str::FromStr;
+
+/// BIP44 path `m/44'/coin_type'/account'/change/index`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DerivationPath {
+    pub coin_type: u32,
+    pub account: u32,
+    pub change: u32,
+    pub index: u32,
+}
+
+impl DerivationPath {
+    pub fn new(coin_type: u32, account: u32, change: u32, index: u32) -> Self {
+        Self {
+            coin_type,
+            account,
+            change,
+            index,
+        }
+    }
+
+    /// the first key of the crypto.org chain, `m/44'/394'/0'/0/0`
+    pub fn crypto_org() -> Self {
+        Self::new(COIN_TYPE, 0, 0, 0)
+    }
+
+    /// the first key of the cosmos hub and the other cosmos chains, `m/44'/118'/0'/0/0`
+    pub fn cosmos() -> Self {
+        Self::new(COSMOS_COIN_TYPE, 0, 0, 0)
+    }
+}
+
+impl Default for DerivationPath {
+    fn default() -> Self {
+        Self::crypto_org()
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "m/44'/{}'/{}'/{}/{}",
+            self.coin_type, self.account, self.change, self.index
+        )
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = MnemonicError;
+
+    /// parse `m/44'/coin_type'/account'/change/index`, the first three indexes are hardened
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let invalid = || MnemonicError::InputError(format!("invalid derivation path {}", path));
+        let parts: Vec<&str> = path.split('/').collect();
+        if parts.len() != 6 || parts[0] != "m" || parts[1] != "44'" {
+            return Err(invalid());
+        }
+        let hardened = |part: &str| -> Result<u32, MnemonicError> {
+            part.strip_suffix('\'')
+                .and_then(|index| index.parse().ok())
+                .ok_or_else(invalid)
+        };
+        let normal =
+            |part: &str| -> Result<u32, MnemonicError> { part.parse().map_err(|_| invalid()) };
+        let path = Self::new(
+            hardened(parts[2])?,
+            hardened(parts[3])?,
+            normal(parts[4])?,
+            normal(parts[5])?,
+        );
+        // the indexes are below 2^31, the upper half is for the hardened keys
+        let max = (1u32 << 31) - 1;
+        if path.coin_type > max || path.account > max || path.change > max || path.index > max {
+            return Err(invalid());
+        }
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constant::FUNDRAISER_PATH;
+
+    #[test]
+    fn test_derivation_path() {
+        let path: DerivationPath = FUNDRAISER_PATH.parse().unwrap();
+        assert_eq!(path, DerivationPath::crypto_org());
+        assert_eq!(path.to_string(), FUNDRAISER_PATH);
+
+        let path: DerivationPath = "m/44'/118'/2'/1/7".parse().unwrap();
+        assert_eq!(path, DerivationPath::new(118, 2, 1, 7));
+        assert_eq!(DerivationPath::cosmos().to_string(), "m/44'/118'/0'/0/0");
+
+        for invalid in &[
+            "m/44'/394'/0'/0",
+            "m/44'/394/0'/0/0",
+            "m/49'/394'/0'/0/0",
+            "44'/394'/0'/0/0/0",
+            "m/44'/394'/0'/0/x",
+            "m/44'/394'/0'/0/2147483648",
+        ] {
+            assert!(invalid.parse::<DerivationPath>().is_err(), "{}", invalid);
+        }
+    }
+}
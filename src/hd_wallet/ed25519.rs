@@ -0,0 +1,123 @@
+//! SLIP-0010 derivation of the ed25519 keys, e.g. the consensus keys,
+//! only the hardened children are defined for ed25519
+
+use crate::hd_wallet::mnemonic::MnemonicError;
+use bitcoin_hashes::hmac::{Hmac, HmacEngine};
+use bitcoin_hashes::{sha512, Hash, HashEngine};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// the hmac key of the master key
+const ED25519_SEED_KEY: &[u8] = b"ed25519 seed";
+const HARDENED: u32 = 0x8000_0000;
+
+/// ed25519 secret key and chain code, zeroized on drop
+#[derive(Clone)]
+pub struct Ed25519ExtendedKey {
+    secret_key: [u8; 32],
+    chain_code: [u8; 32],
+}
+
+impl Drop for Ed25519ExtendedKey {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+        self.chain_code.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for Ed25519ExtendedKey {}
+
+impl Ed25519ExtendedKey {
+    /// the master key of the bip39 seed
+    pub fn from_seed(seed: &[u8]) -> Self {
+        Self::from_hmac(ED25519_SEED_KEY, &[seed])
+    }
+
+    /// split HMAC-SHA512(key, data) into the secret key and the chain code
+    fn from_hmac(key: &[u8], data: &[&[u8]]) -> Self {
+        let mut engine = HmacEngine::<sha512::Hash>::new(key);
+        for part in data {
+            engine.input(part);
+        }
+        let mut hash = Hmac::<sha512::Hash>::from_engine(engine).into_inner();
+        let mut extended = Self {
+            secret_key: [0; 32],
+            chain_code: [0; 32],
+        };
+        extended.secret_key.copy_from_slice(&hash[..32]);
+        extended.chain_code.copy_from_slice(&hash[32..]);
+        hash.zeroize();
+        extended
+    }
+
+    /// the hardened child of the index, e.g. 44 for `44'`
+    pub fn derive_child(&self, index: u32) -> Result<Self, MnemonicError> {
+        if index >= HARDENED {
+            return Err(MnemonicError::InputError(format!(
+                "invalid ed25519 child index {}",
+                index
+            )));
+        }
+        Ok(Self::from_hmac(
+            &self.chain_code,
+            &[&[0u8], &self.secret_key, &(index | HARDENED).to_be_bytes()],
+        ))
+    }
+
+    /// derive the path from this key as the master key, e.g. `m/44'/118'/0'/0'/0'`,
+    /// all the indexes must be hardened
+    pub fn derive_path(&self, path: &str) -> Result<Self, MnemonicError> {
+        let invalid = || MnemonicError::InputError(format!("invalid ed25519 path {}", path));
+        let mut parts = path.split('/');
+        if parts.next() != Some("m") {
+            return Err(invalid());
+        }
+        let mut key = self.clone();
+        for part in parts {
+            let index = part
+                .strip_suffix('\'')
+                .and_then(|index| index.parse().ok())
+                .ok_or_else(invalid)?;
+            key = key.derive_child(index)?;
+        }
+        Ok(key)
+    }
+
+    pub fn secret_key(&self) -> &[u8; 32] {
+        &self.secret_key
+    }
+
+    pub fn chain_code(&self) -> &[u8; 32] {
+        &self.chain_code
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_slip10_vector() {
+        // test vector 1 of SLIP-0010 for ed25519
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = Ed25519ExtendedKey::from_seed(&seed);
+        assert_eq!(
+            hex::encode(master.secret_key()),
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"
+        );
+        assert_eq!(
+            hex::encode(master.chain_code()),
+            "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb"
+        );
+        let child = master.derive_path("m/0'").unwrap();
+        assert_eq!(
+            hex::encode(child.secret_key()),
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"
+        );
+        assert_eq!(
+            hex::encode(child.chain_code()),
+            "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69"
+        );
+        assert!(master.derive_path("m/44'/118'/0'/0/0").is_err());
+        assert!(master.derive_child(HARDENED).is_err());
+    }
+}
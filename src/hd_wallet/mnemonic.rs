@@ -1,25 +1,155 @@
-use crate::types::key::PrivateKey;
+use crate::types::key::{zeroize_extended_key, PrivateKey};
 
 use crate::constant::FUNDRAISER_PATH;
-use bip39::{Language, MnemonicType, Seed};
-use hdwallet::{ChainPath, KeyChain};
-use hdwallet::{DefaultKeyChain, ExtendedPrivKey};
+use crate::hd_wallet::derivation_path::DerivationPath;
+use crate::hd_wallet::ed25519::Ed25519ExtendedKey;
+use crate::types::address::ChainConfig;
+use bip39::{MnemonicType, Seed};
+use hdwallet::{ChainPath, ExtendedPrivKey, KeyIndex, SubPath};
+use std::ops::Range;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
+pub use bip39::Language;
+
+/// the languages tried by `Mnemonic::from_str`, in order
+pub const LANGUAGES: [Language; 8] = [
+    Language::English,
+    Language::ChineseSimplified,
+    Language::ChineseTraditional,
+    Language::French,
+    Language::Italian,
+    Language::Japanese,
+    Language::Korean,
+    Language::Spanish,
+];
+
+/// the words and the password are zeroized on drop
 pub struct Mnemonic {
+    // bip39 zeroizes the words and the entropy on drop
     inner_mnemonic: bip39::Mnemonic,
     password: Option<String>,
 }
 
+impl Drop for Mnemonic {
+    fn drop(&mut self) {
+        self.password.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for Mnemonic {}
+
 #[derive(thiserror::Error, Debug)]
 pub enum MnemonicError {
     #[error("input error: {0}")]
     InputError(String),
 
-    #[error("mnemonic error")]
+    /// the error of bip39, e.g. an invalid checksum
+    #[error("{0}")]
     MnemonicError(#[from] anyhow::Error),
 
     #[error("hdwallet error")]
     HdWalletError(hdwallet::error::Error),
+
+    #[error("invalid number of words: {0}, expected 12, 15, 18, 21 or 24")]
+    WordCount(usize),
+
+    /// `position` starts from 1, `suggestions` are the closest words of the wordlist
+    #[error("unknown word {word} at position {position}, did you mean {suggestions:?}")]
+    UnknownWord {
+        position: usize,
+        word: String,
+        suggestions: Vec<String>,
+    },
+
+    #[error("invalid checksum of the words")]
+    InvalidChecksum,
+}
+
+/// the 2048 words of the language, read back from the first word of the mnemonics
+/// of the entropy starting with each 11 bits index
+fn wordlist(language: Language) -> Vec<String> {
+    (0..2048u16)
+        .map(|index| {
+            let mut entropy = [0u8; 16];
+            entropy[0] = (index >> 3) as u8;
+            entropy[1] = ((index & 0x7) << 5) as u8;
+            let mnemonic = bip39::Mnemonic::from_entropy(&entropy, language)
+                .expect("16 bytes entropy is valid");
+            mnemonic
+                .phrase()
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        })
+        .collect()
+}
+
+/// levenshtein distance of the chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// at most 3 words of the wordlist within the edit distance 2, the closest first
+fn suggestions(word: &str, wordlist: &[String]) -> Vec<String> {
+    let mut candidates: Vec<(usize, &String)> = wordlist
+        .iter()
+        .map(|candidate| (edit_distance(word, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .collect();
+    candidates.sort();
+    candidates
+        .into_iter()
+        .take(3)
+        .map(|(_, candidate)| candidate.clone())
+        .collect()
+}
+
+/// whether bip39 rejects a word which is not in the wordlist of the language
+fn is_unknown_word(error: &MnemonicError) -> bool {
+    match error {
+        MnemonicError::MnemonicError(e) => matches!(
+            e.downcast_ref::<bip39::ErrorKind>(),
+            Some(bip39::ErrorKind::InvalidWord)
+        ),
+        _ => false,
+    }
+}
+
+/// an extended private key, e.g. the key of the external chain of an account,
+/// zeroized on drop
+#[derive(Clone)]
+pub(crate) struct ExtendedKey(ExtendedPrivKey);
+
+impl ExtendedKey {
+    fn derive(&self, key_index: KeyIndex) -> Result<Self, MnemonicError> {
+        Ok(Self(self.0.derive_private_key(key_index)?))
+    }
+
+    /// the private key of the normal child of the index
+    pub(crate) fn child_key(&self, index: u32) -> Result<PrivateKey, MnemonicError> {
+        Ok(self.0.derive_private_key(KeyIndex::Normal(index))?.into())
+    }
+
+    pub(crate) fn private_key(&self) -> PrivateKey {
+        self.0.clone().into()
+    }
+}
+
+impl Drop for ExtendedKey {
+    fn drop(&mut self) {
+        zeroize_extended_key(&mut self.0);
+    }
 }
 
 impl From<hdwallet::error::Error> for MnemonicError {
@@ -29,8 +159,17 @@ impl From<hdwallet::error::Error> for MnemonicError {
 }
 
 impl Mnemonic {
-    /// create new Mnemonic
+    /// create new Mnemonic of english words
     pub fn new(word_count: u32, password: Option<String>) -> Result<Self, MnemonicError> {
+        Self::new_with_language(word_count, Language::English, password)
+    }
+
+    /// create new Mnemonic of the words of the language
+    pub fn new_with_language(
+        word_count: u32,
+        language: Language,
+        password: Option<String>,
+    ) -> Result<Self, MnemonicError> {
         let flag = match word_count {
             12 => MnemonicType::Words12,
             15 => MnemonicType::Words15,
@@ -39,7 +178,7 @@ impl Mnemonic {
             24 => MnemonicType::Words24,
             _ => return Err(MnemonicError::InputError("invalid words count".to_string())),
         };
-        let mnemonic = bip39::Mnemonic::new(flag, Language::English);
+        let mnemonic = bip39::Mnemonic::new(flag, language);
         let m = Mnemonic {
             inner_mnemonic: mnemonic,
             password,
@@ -47,9 +186,37 @@ impl Mnemonic {
         Ok(m)
     }
 
-    /// Create Mnemonic from words in string literal
+    /// Create Mnemonic from words in string literal, the language is detected
+    /// from the words by trying `LANGUAGES` in order. The error is the bip39 error of the
+    /// first language, or of the language which knows all the words (e.g. a wrong checksum)
     pub fn from_str(words: &str, password: Option<String>) -> Result<Self, MnemonicError> {
-        let mnemonic = bip39::Mnemonic::from_phrase(words, Language::English)?;
+        let mut error: Option<MnemonicError> = None;
+        for language in LANGUAGES.iter() {
+            match Self::from_str_with_language(words, *language, password.clone()) {
+                Ok(mnemonic) => return Ok(mnemonic),
+                Err(e) => {
+                    let replace = match &error {
+                        None => true,
+                        Some(previous) => is_unknown_word(previous) && !is_unknown_word(&e),
+                    };
+                    if replace {
+                        error = Some(e);
+                    }
+                }
+            }
+        }
+        Err(error.unwrap_or_else(|| {
+            MnemonicError::InputError("invalid mnemonic words in any language".to_string())
+        }))
+    }
+
+    /// Create Mnemonic from words of the language
+    pub fn from_str_with_language(
+        words: &str,
+        language: Language,
+        password: Option<String>,
+    ) -> Result<Self, MnemonicError> {
+        let mnemonic = bip39::Mnemonic::from_phrase(words, language)?;
         let m = Mnemonic {
             inner_mnemonic: mnemonic,
             password,
@@ -57,17 +224,263 @@ impl Mnemonic {
         Ok(m)
     }
 
+    /// check the english words, the error tells the wrong count, the unknown words
+    /// with the suggestions or the invalid checksum
+    pub fn validate(words: &str) -> Result<(), MnemonicError> {
+        Self::validate_with_language(words, Language::English)
+    }
+
+    /// check the words of the language, see `validate`
+    pub fn validate_with_language(words: &str, language: Language) -> Result<(), MnemonicError> {
+        let words: Vec<&str> = words.split_whitespace().collect();
+        if ![12, 15, 18, 21, 24].contains(&words.len()) {
+            return Err(MnemonicError::WordCount(words.len()));
+        }
+        let wordlist = wordlist(language);
+        for (index, word) in words.iter().enumerate() {
+            let word = word.to_lowercase();
+            if !wordlist.contains(&word) {
+                return Err(MnemonicError::UnknownWord {
+                    position: index + 1,
+                    suggestions: suggestions(&word, &wordlist),
+                    word,
+                });
+            }
+        }
+        bip39::Mnemonic::from_phrase(&words.join(" "), language)
+            .map(|_| ())
+            .map_err(|_| MnemonicError::InvalidChecksum)
+    }
+
+    /// Create Mnemonic of english words from the raw entropy (16, 20, 24, 28 or 32 bytes)
+    pub fn from_entropy(entropy: &[u8], password: Option<String>) -> Result<Self, MnemonicError> {
+        let mnemonic = bip39::Mnemonic::from_entropy(entropy, Language::English)?;
+        Ok(Mnemonic {
+            inner_mnemonic: mnemonic,
+            password,
+        })
+    }
+
+    /// the words, to be backed up
+    pub fn phrase(&self) -> &str {
+        self.inner_mnemonic.phrase()
+    }
+
+    /// the raw entropy the words encode
+    pub fn entropy(&self) -> &[u8] {
+        self.inner_mnemonic.entropy()
+    }
+
+    /// the bip39 password
+    pub(crate) fn password(&self) -> Option<&str> {
+        self.password.as_deref()
+    }
+
+    pub fn language(&self) -> Language {
+        self.inner_mnemonic.language()
+    }
+
     /// Generates private key
     pub fn private_key(&self) -> Result<PrivateKey, MnemonicError> {
-        let chain_path = ChainPath::from(FUNDRAISER_PATH);
-        let password = self.password.clone().unwrap_or_default();
-        let seed = Seed::new(&self.inner_mnemonic, &password)
-            .as_bytes()
-            .to_vec();
-        let master_key = ExtendedPrivKey::with_seed(&seed)?;
-        let key_chain = DefaultKeyChain::new(master_key);
-
-        let (extended_private_key, _) = key_chain.derive_private_key(chain_path)?;
-        Ok(extended_private_key.into())
+        self.derive_private_key(FUNDRAISER_PATH)
+    }
+
+    /// Generates private key of the BIP44 path, e.g. `m/44'/118'/0'/0/0`
+    pub fn private_key_with_path(&self, path: &str) -> Result<PrivateKey, MnemonicError> {
+        let path: DerivationPath = path.parse()?;
+        self.private_key_of(&path)
+    }
+
+    /// Generates private key of the derivation path
+    pub fn private_key_of(&self, path: &DerivationPath) -> Result<PrivateKey, MnemonicError> {
+        self.derive_private_key(&path.to_string())
+    }
+
+    /// Generates private key of the path m/44'/394'/account'/change/index of the crypto.org chain
+    pub fn derive(
+        &self,
+        account: u32,
+        change: u32,
+        index: u32,
+    ) -> Result<PrivateKey, MnemonicError> {
+        self.derive_with_config(&ChainConfig::default(), account, change, index)
+    }
+
+    /// Generates private key of the path m/44'/coin_type'/account'/change/index of the chain
+    pub fn derive_with_config(
+        &self,
+        chain_config: &ChainConfig,
+        account: u32,
+        change: u32,
+        index: u32,
+    ) -> Result<PrivateKey, MnemonicError> {
+        self.private_key_of(&chain_config.derivation_path(account, change, index))
+    }
+
+    /// Generates the private keys of the paths m/44'/394'/0'/0/index of the indexes in the range,
+    /// e.g. the sequential deposit addresses, the seed and the chain key are only derived once
+    pub fn derive_range(
+        &self,
+        range: Range<u32>,
+    ) -> Result<impl Iterator<Item = Result<PrivateKey, MnemonicError>>, MnemonicError> {
+        self.derive_range_with_config(&ChainConfig::default(), range)
+    }
+
+    /// Generates the private keys of the paths m/44'/coin_type'/0'/0/index of the chain
+    /// of the indexes in the range
+    pub fn derive_range_with_config(
+        &self,
+        chain_config: &ChainConfig,
+        range: Range<u32>,
+    ) -> Result<impl Iterator<Item = Result<PrivateKey, MnemonicError>>, MnemonicError> {
+        let chain_path = format!("m/44'/{}'/0'/0", chain_config.coin_type);
+        let chain_key = self.derive_extended_key(&chain_path)?;
+        Ok(range.map(move |index| chain_key.child_key(index)))
+    }
+
+    /// Generates private key of the hd path
+    pub(crate) fn derive_private_key(&self, path: &str) -> Result<PrivateKey, MnemonicError> {
+        Ok(self.derive_extended_key(path)?.private_key())
+    }
+
+    /// Generates ed25519 key of the hd path (e.g. `m/44'/118'/0'/0'/0'`) by SLIP-0010
+    pub fn ed25519_key(&self, path: &str) -> Result<Ed25519ExtendedKey, MnemonicError> {
+        Ed25519ExtendedKey::from_seed(self.seed().as_bytes()).derive_path(path)
+    }
+
+    /// the bip39 seed, which bip39 zeroizes on drop
+    fn seed(&self) -> Seed {
+        let password = Zeroizing::new(self.password.clone().unwrap_or_default());
+        Seed::new(&self.inner_mnemonic, &password)
+    }
+
+    /// Generates extended private key of the hd path, the master key and the
+    /// intermediate keys are zeroized once their child is derived
+    pub(crate) fn derive_extended_key(&self, path: &str) -> Result<ExtendedKey, MnemonicError> {
+        let chain_path = ChainPath::from(path);
+        let mut sub_paths = chain_path.iter();
+        if !matches!(sub_paths.next(), Some(Ok(SubPath::Root))) {
+            return Err(MnemonicError::InputError(format!(
+                "invalid hd path {}",
+                path
+            )));
+        }
+        let mut key = ExtendedKey(ExtendedPrivKey::with_seed(self.seed().as_bytes())?);
+        for sub_path in sub_paths {
+            match sub_path.map_err(hdwallet::error::Error::from)? {
+                SubPath::Child(key_index) => key = key.derive(key_index)?,
+                SubPath::Root => {
+                    return Err(MnemonicError::InputError(format!(
+                        "invalid hd path {}",
+                        path
+                    )))
+                }
+            }
+        }
+        Ok(key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::key::PublicKey;
+
+    #[test]
+    fn test_derive_range() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let keys: Vec<PublicKey> = mnemonic
+            .derive_range(0..3)
+            .unwrap()
+            .map(|key| key.map(|key| PublicKey::from(&key)))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[0], PublicKey::from(&mnemonic.private_key().unwrap()));
+        assert_eq!(keys[2], PublicKey::from(&mnemonic.derive(0, 0, 2).unwrap()));
+        assert_ne!(keys[1], keys[2]);
+
+        let cosmos_hub = ChainConfig::cosmos_hub();
+        let key = mnemonic
+            .derive_range_with_config(&cosmos_hub, 1..2)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        let expected = mnemonic.private_key_with_path("m/44'/118'/0'/0/1").unwrap();
+        assert_eq!(PublicKey::from(&key), PublicKey::from(&expected));
+        let key = mnemonic.derive_with_config(&cosmos_hub, 0, 0, 1).unwrap();
+        assert_eq!(PublicKey::from(&key), PublicKey::from(&expected));
+
+        let chain_key = mnemonic.derive_extended_key("m/44'/394'/0'/0").unwrap();
+        assert_eq!(PublicKey::from(&chain_key.child_key(0).unwrap()), keys[0]);
+        assert!(mnemonic.derive_extended_key("44'/394'/0'/0").is_err());
+    }
+
+    #[test]
+    fn test_language() {
+        let mnemonic = Mnemonic::new_with_language(12, Language::Japanese, None).unwrap();
+        let words = mnemonic.phrase().to_string();
+        let detected = Mnemonic::from_str(&words, None).unwrap();
+        assert_eq!(detected.language(), Language::Japanese);
+        assert_eq!(
+            PublicKey::from(&detected.private_key().unwrap()),
+            PublicKey::from(&mnemonic.private_key().unwrap())
+        );
+        assert!(Mnemonic::from_str_with_language(&words, Language::English, None).is_err());
+        assert!(Mnemonic::from_str("not a mnemonic", None).is_err());
+    }
+
+    #[test]
+    fn test_from_str_error() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let error = Mnemonic::from_str(&words.replace("gown", "gift"), None)
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("checksum"), "{}", error);
+        assert!(!is_unknown_word(&error));
+
+        let error = Mnemonic::from_str(&words.replace("gown", "gownn"), None)
+            .err()
+            .unwrap();
+        assert!(is_unknown_word(&error));
+        assert!(error.to_string().contains("word"), "{}", error);
+    }
+
+    #[test]
+    fn test_validate() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        assert!(Mnemonic::validate(words).is_ok());
+        assert!(matches!(
+            Mnemonic::validate("dune car envelope"),
+            Err(MnemonicError::WordCount(3))
+        ));
+        match Mnemonic::validate(&words.replace("envelope", "envelop")) {
+            Err(MnemonicError::UnknownWord {
+                position,
+                word,
+                suggestions,
+            }) => {
+                assert_eq!(position, 3);
+                assert_eq!(word, "envelop");
+                assert_eq!(suggestions[0], "envelope");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(matches!(
+            Mnemonic::validate(&words.replace("gown", "gift")),
+            Err(MnemonicError::InvalidChecksum)
+        ));
+    }
+
+    #[test]
+    fn test_entropy() {
+        let mnemonic = Mnemonic::new(24, None).unwrap();
+        assert_eq!(mnemonic.phrase().split_whitespace().count(), 24);
+        assert_eq!(mnemonic.entropy().len(), 32);
+        let restored = Mnemonic::from_entropy(mnemonic.entropy(), None).unwrap();
+        assert_eq!(restored.phrase(), mnemonic.phrase());
+        assert!(Mnemonic::from_entropy(&[0; 15], None).is_err());
     }
 }
@@ -1 +1,5 @@
+pub mod derivation_path;
+pub mod ed25519;
 pub mod mnemonic;
+pub mod vanity;
+pub mod vault;
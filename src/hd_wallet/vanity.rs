@@ -0,0 +1,259 @@
+//! Search of the keys whose bech32 address matches a pattern (e.g. a branded
+//! treasury address), over the derivation indexes of a mnemonic or random keys
+
+use crate::error::Error;
+use crate::hd_wallet::mnemonic::Mnemonic;
+use crate::types::address::ChainConfig;
+use crate::types::key::{PrivateKey, PublicKey};
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// the characters of the bech32 data part
+const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// pattern of the data part of the address, the part after `cro1`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VanityPattern {
+    Prefix(String),
+    Suffix(String),
+}
+
+impl VanityPattern {
+    fn text(&self) -> &str {
+        match self {
+            VanityPattern::Prefix(text) | VanityPattern::Suffix(text) => text,
+        }
+    }
+
+    fn matches(&self, data: &str) -> bool {
+        match self {
+            VanityPattern::Prefix(prefix) => data.starts_with(prefix.as_str()),
+            VanityPattern::Suffix(suffix) => data.ends_with(suffix.as_str()),
+        }
+    }
+}
+
+/// stops a running search, the clones share the state
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// the key found by the search
+#[derive(Debug, Clone)]
+pub struct VanityMatch {
+    pub private_key: PrivateKey,
+    pub address: String,
+    /// the index of the path m/44'/coin_type'/account'/0/index, None for a random key
+    pub index: Option<u32>,
+}
+
+/// search over several threads, `progress` is called with the number of the tried keys
+pub struct VanitySearch {
+    pattern: VanityPattern,
+    account_prefix: String,
+    coin_type: u32,
+    threads: usize,
+    progress_interval: u64,
+    token: CancellationToken,
+}
+
+type Generator =
+    dyn Fn(usize, u64) -> Option<Result<(PrivateKey, Option<u32>), Error>> + Send + Sync;
+
+impl VanitySearch {
+    /// the pattern must be made of the bech32 characters, the case is ignored
+    pub fn new(pattern: VanityPattern) -> Result<Self, Error> {
+        let text = pattern.text().to_lowercase();
+        if text.is_empty() || !text.chars().all(|c| BECH32_CHARSET.contains(c)) {
+            return Err(Error::InputError(format!(
+                "the pattern {} has non bech32 characters (b, i, o, 1)",
+                pattern.text()
+            )));
+        }
+        let pattern = match pattern {
+            VanityPattern::Prefix(_) => VanityPattern::Prefix(text),
+            VanityPattern::Suffix(_) => VanityPattern::Suffix(text),
+        };
+        let chain_config = ChainConfig::default();
+        Ok(Self {
+            pattern,
+            account_prefix: chain_config.account_prefix,
+            coin_type: chain_config.coin_type,
+            threads: 4,
+            progress_interval: 10_000,
+            token: CancellationToken::default(),
+        })
+    }
+
+    pub fn set_account_prefix(&mut self, account_prefix: String) -> &mut Self {
+        self.account_prefix = account_prefix;
+        self
+    }
+
+    /// the account prefix and the coin type of the chain
+    pub fn set_chain_config(&mut self, chain_config: &ChainConfig) -> &mut Self {
+        self.account_prefix = chain_config.account_prefix.clone();
+        self.coin_type = chain_config.coin_type;
+        self
+    }
+
+    /// number of the threads, 4 by default
+    pub fn set_threads(&mut self, threads: usize) -> &mut Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// call `progress` every `interval` tried keys, 10000 by default
+    pub fn set_progress_interval(&mut self, interval: u64) -> &mut Self {
+        self.progress_interval = interval.max(1);
+        self
+    }
+
+    /// the token which stops the search, the search returns None once it is cancelled
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// try the keys of the path m/44'/coin_type'/account'/0/index of the indexes
+    pub fn search_indexes<F>(
+        &self,
+        mnemonic: &Mnemonic,
+        account: u32,
+        indexes: Range<u32>,
+        progress: F,
+    ) -> Result<Option<VanityMatch>, Error>
+    where
+        F: Fn(u64) + Send + Sync + 'static,
+    {
+        let chain_path = format!("m/44'/{}'/{}'/0", self.coin_type, account);
+        let chain_key = mnemonic.derive_extended_key(&chain_path)?;
+        let threads = self.threads as u64;
+        let generator = move |worker: usize, attempt: u64| {
+            let index = indexes.start as u64 + attempt * threads + worker as u64;
+            if index >= indexes.end as u64 {
+                return None;
+            }
+            let child = chain_key.child_key(index as u32).map_err(Error::from);
+            Some(child.map(|child| (child, Some(index as u32))))
+        };
+        self.run(Arc::new(generator), Arc::new(progress))
+    }
+
+    /// try random keys until one matches or the search is cancelled
+    pub fn search_random<F>(&self, progress: F) -> Result<Option<VanityMatch>, Error>
+    where
+        F: Fn(u64) + Send + Sync + 'static,
+    {
+        let generator = |_worker: usize, _attempt: u64| {
+            Some(Ok((PrivateKey::new(&mut rand::thread_rng()), None)))
+        };
+        self.run(Arc::new(generator), Arc::new(progress))
+    }
+
+    fn run(
+        &self,
+        generator: Arc<Generator>,
+        progress: Arc<dyn Fn(u64) + Send + Sync>,
+    ) -> Result<Option<VanityMatch>, Error> {
+        let found: Arc<Mutex<Option<Result<VanityMatch, Error>>>> = Default::default();
+        let done = Arc::new(AtomicBool::new(false));
+        let tried = Arc::new(AtomicU64::new(0));
+        let workers: Vec<_> = (0..self.threads)
+            .map(|worker| {
+                let generator = generator.clone();
+                let progress = progress.clone();
+                let found = found.clone();
+                let done = done.clone();
+                let tried = tried.clone();
+                let token = self.token.clone();
+                let pattern = self.pattern.clone();
+                let account_prefix = self.account_prefix.clone();
+                let progress_interval = self.progress_interval;
+                thread::spawn(move || {
+                    let data_start = account_prefix.len() + 1;
+                    let mut attempt = 0;
+                    while !done.load(Ordering::Relaxed) && !token.is_cancelled() {
+                        let result = match generator(worker, attempt) {
+                            Some(result) => result,
+                            None => return,
+                        };
+                        attempt += 1;
+                        let count = tried.fetch_add(1, Ordering::Relaxed) + 1;
+                        if count % progress_interval == 0 {
+                            progress(count);
+                        }
+                        let result = result.and_then(|(private_key, index)| {
+                            let address = PublicKey::from(&private_key)
+                                .address()?
+                                .to_bech32(&account_prefix);
+                            Ok(VanityMatch {
+                                private_key,
+                                address,
+                                index,
+                            })
+                        });
+                        let matched = match &result {
+                            Ok(candidate) => pattern.matches(&candidate.address[data_start..]),
+                            Err(_) => true,
+                        };
+                        if matched {
+                            let mut found = found.lock().expect("vanity result lock poisoned");
+                            if found.is_none() {
+                                *found = Some(result);
+                            }
+                            done.store(true, Ordering::Relaxed);
+                            return;
+                        }
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker
+                .join()
+                .map_err(|_| Error::InputError("vanity search thread panicked".to_string()))?;
+        }
+        let found = found.lock().expect("vanity result lock poisoned").take();
+        found.transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_vanity_search() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let mut search = VanitySearch::new(VanityPattern::Suffix("Q".to_string())).unwrap();
+        search.set_threads(2);
+        let found = search
+            .search_indexes(&mnemonic, 0, 0..10_000, |_| {})
+            .unwrap()
+            .unwrap();
+        assert!(found.address.ends_with('q'));
+        let index = found.index.unwrap();
+        let expected = mnemonic.derive(0, 0, index).unwrap();
+        assert_eq!(
+            PublicKey::from(&found.private_key),
+            PublicKey::from(&expected)
+        );
+
+        search.cancellation_token().cancel();
+        assert!(search.search_random(|_| {}).unwrap().is_none());
+        assert!(VanitySearch::new(VanityPattern::Prefix("bio".to_string())).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! Mnemonic encrypted with a password (argon2id + aes-256-gcm) in a json file,
+//! a minimal storage of a software wallet
+
+use crate::error::Error;
+use crate::hd_wallet::mnemonic::Mnemonic;
+use crate::key_service::keystore_service::KeystoreCrypto;
+use crate::key_service::private_key_service::PrivateKeyService;
+use crate::types::key::PublicKey;
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use zeroize::{Zeroize, Zeroizing};
+
+const VAULT_VERSION: u32 = 1;
+
+/// the encrypted secret, zeroized after use
+#[derive(Serialize, Deserialize)]
+struct VaultSecret {
+    phrase: String,
+    /// the bip39 password of the mnemonic
+    password: Option<String>,
+}
+
+impl Drop for VaultSecret {
+    fn drop(&mut self) {
+        self.phrase.zeroize();
+        self.password.zeroize();
+    }
+}
+
+/// mnemonic vault file content
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MnemonicVault {
+    pub version: u32,
+    /// base64 encoded public key of `FUNDRAISER_PATH`, so the wallet can be identified
+    /// without the password
+    pub public_key: String,
+    pub crypto: KeystoreCrypto,
+}
+
+impl MnemonicVault {
+    /// encrypt the words and the bip39 password of the mnemonic with the vault password
+    pub fn encrypt(mnemonic: &Mnemonic, password: &str) -> Result<Self, Error> {
+        let public_key = PublicKey::from(&mnemonic.private_key()?).to_string();
+        let secret = VaultSecret {
+            phrase: mnemonic.phrase().to_string(),
+            password: mnemonic.password().map(|password| password.to_string()),
+        };
+        let plaintext = Zeroizing::new(
+            serde_json::to_vec(&secret).map_err(|e| Error::SerializeError(e.to_string()))?,
+        );
+        Ok(Self {
+            version: VAULT_VERSION,
+            public_key,
+            crypto: KeystoreCrypto::encrypt(&plaintext, password)?,
+        })
+    }
+
+    /// decrypt the mnemonic, a wrong password is an error
+    pub fn decrypt(&self, password: &str) -> Result<Mnemonic, Error> {
+        if self.version != VAULT_VERSION {
+            return Err(Error::KeystoreError(format!(
+                "unsupported vault version: {}",
+                self.version
+            )));
+        }
+        let plaintext = self.crypto.decrypt(password)?;
+        let secret: VaultSecret = serde_json::from_slice(&plaintext)
+            .map_err(|e| Error::KeystoreError(format!("invalid vault secret: {}", e)))?;
+        let mnemonic = Mnemonic::from_str(&secret.phrase, secret.password.clone())?;
+        if PublicKey::from(&mnemonic.private_key()?).to_string() != self.public_key {
+            return Err(Error::KeystoreError(
+                "the mnemonic doesn't match the public key of the vault".to_string(),
+            ));
+        }
+        Ok(mnemonic)
+    }
+
+    /// decrypt the mnemonic into a KeyService of `FUNDRAISER_PATH`
+    pub fn key_service(&self, password: &str) -> Result<PrivateKeyService, Error> {
+        PrivateKeyService::new_from_mnemonic(self.decrypt(password)?)
+    }
+
+    /// load a vault from a json file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| Error::SerializeError(e.to_string()))
+    }
+
+    /// save the vault into a json file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| Error::SerializeError(e.to_string()))?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::key_service::KeyService;
+
+    #[tokio::test]
+    async fn test_vault_save_load() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, Some("bip39 password".to_string())).unwrap();
+        let vault = MnemonicVault::encrypt(&mnemonic, "password").unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.json");
+        vault.save(&path).unwrap();
+        let loaded = MnemonicVault::load(&path).unwrap();
+        assert_eq!(loaded, vault);
+
+        assert_eq!(loaded.decrypt("password").unwrap().phrase(), words);
+        assert!(loaded.decrypt("wrong").is_err());
+        let key_service = loaded.key_service("password").unwrap();
+        assert_eq!(
+            key_service.public_key().await.unwrap(),
+            PublicKey::from(&mnemonic.private_key().unwrap())
+        );
+
+        let mut unsupported = vault;
+        unsupported.version = 2;
+        assert!(unsupported.decrypt("password").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+//! Ed25519 consensus signer with a software key
+use crate::constant::VALIDATOR_PATH;
+use crate::error::Error;
+use crate::hd_wallet::ed25519::Ed25519ExtendedKey;
+use crate::hd_wallet::mnemonic::Mnemonic;
+use crate::key_service::ConsensusKeyService;
+
+use async_trait::async_trait;
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use std::sync::Arc;
+
+/// ConsensusKeyService of an ed25519 key kept in memory, e.g. derived from a mnemonic
+#[derive(Clone)]
+pub struct Ed25519Service {
+    keypair: Arc<Keypair>,
+    hd_path: Option<String>,
+}
+
+impl std::fmt::Debug for Ed25519Service {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Ed25519Service")
+            .field("hd_path", &self.hd_path)
+            .field(
+                "public_key",
+                &base64::encode(self.keypair.public.as_bytes()),
+            )
+            .finish()
+    }
+}
+
+impl Ed25519Service {
+    /// create a new Ed25519Service from the 32 bytes secret key
+    pub fn new(secret_key: &[u8; 32]) -> Result<Self, Error> {
+        let secret = SecretKey::from_bytes(secret_key)
+            .map_err(|e| Error::InputError(format!("invalid ed25519 secret key: {}", e)))?;
+        let public = PublicKey::from(&secret);
+        Ok(Self {
+            keypair: Arc::new(Keypair { secret, public }),
+            hd_path: None,
+        })
+    }
+
+    /// create a new Ed25519Service from the SLIP-0010 derived key
+    pub fn from_extended_key(key: &Ed25519ExtendedKey) -> Result<Self, Error> {
+        Self::new(key.secret_key())
+    }
+
+    /// create a new Ed25519Service from Mnemonic, `hd_path` defaults to `VALIDATOR_PATH`
+    pub fn new_from_mnemonic(mnemonic: &Mnemonic, hd_path: Option<&str>) -> Result<Self, Error> {
+        let hd_path = hd_path.unwrap_or(VALIDATOR_PATH);
+        let key = mnemonic.ed25519_key(hd_path)?;
+        let mut service = Self::from_extended_key(&key)?;
+        service.hd_path = Some(hd_path.to_string());
+        Ok(service)
+    }
+
+    /// the hd path the key is derived from, if it is known
+    pub fn hd_path(&self) -> Option<&str> {
+        self.hd_path.as_deref()
+    }
+}
+
+#[async_trait]
+impl ConsensusKeyService for Ed25519Service {
+    async fn public_key(&self) -> Result<[u8; 32], Error> {
+        Ok(self.keypair.public.to_bytes())
+    }
+
+    async fn sign(&self, msg: &[u8]) -> Result<String, Error> {
+        let signature = self.keypair.sign(msg);
+        Ok(base64::encode(&signature.to_bytes()[..]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ed25519_dalek::{Signature, Verifier};
+    use std::convert::TryFrom;
+
+    #[tokio::test]
+    async fn test_sign() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let service = Ed25519Service::new_from_mnemonic(&mnemonic, None).unwrap();
+        assert_eq!(service.hd_path(), Some(VALIDATOR_PATH));
+
+        let public_key = PublicKey::from_bytes(&service.public_key().await.unwrap()).unwrap();
+        let signature = base64::decode(service.sign(b"vote").await.unwrap()).unwrap();
+        let signature = Signature::try_from(&signature[..]).unwrap();
+        assert!(public_key.verify(b"vote", &signature).is_ok());
+        assert!(public_key.verify(b"proposal", &signature).is_err());
+    }
+}
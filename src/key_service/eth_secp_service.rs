@@ -0,0 +1,87 @@
+use crate::constant::ETH_FUNDRAISER_PATH;
+use crate::error::Error;
+use crate::hd_wallet::mnemonic::Mnemonic;
+use crate::key_service::{KeyService, SigningAlgorithm};
+use crate::types::key::{PrivateKey, PublicKey, SECP256K1};
+
+use async_trait::async_trait;
+use secp256k1::Message;
+use stdtx::address::{Address, ADDRESS_SIZE};
+use tiny_keccak::{Hasher, Keccak};
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    let mut output = [0u8; 32];
+    hasher.update(data);
+    hasher.finalize(&mut output);
+    output
+}
+
+/// KeyService for ethermint style (e.g. Cronos) accounts:
+/// keys are derived with coin type 60 and addresses are keccak256 based
+#[derive(Clone)]
+pub struct EthSecpKeyService {
+    pub private_key: PrivateKey,
+    hd_path: Option<String>,
+}
+
+impl EthSecpKeyService {
+    /// create a new EthSecpKeyService from Mnemonic, using the path m/44'/60'/0'/0/0
+    pub fn new_from_mnemonic(mnemonic: Mnemonic) -> Result<Self, Error> {
+        let private_key = mnemonic.derive_private_key(ETH_FUNDRAISER_PATH)?;
+        Ok(Self {
+            private_key,
+            hd_path: Some(ETH_FUNDRAISER_PATH.to_string()),
+        })
+    }
+
+    /// create a new EthSecpKeyService from a PrivateKey
+    pub fn new(private_key: PrivateKey) -> Self {
+        Self {
+            private_key,
+            hd_path: None,
+        }
+    }
+}
+
+#[async_trait]
+impl KeyService for EthSecpKeyService {
+    async fn public_key(&self) -> Result<PublicKey, Error> {
+        Ok(PublicKey::from(&self.private_key))
+    }
+
+    fn algorithm(&self) -> SigningAlgorithm {
+        SigningAlgorithm::EthSecp256k1
+    }
+
+    fn hd_path(&self) -> Option<String> {
+        self.hd_path.clone()
+    }
+
+    /// Address returns an ethereum style address: the last 20 bytes of KECCAK256(pubkey)
+    async fn address(&self) -> Result<Address, Error> {
+        let pubkey = PublicKey::from(&self.private_key);
+        let uncompressed = pubkey.as_ref().serialize_uncompressed();
+        let hash = keccak256(&uncompressed[1..]);
+        let mut raw = [0; ADDRESS_SIZE];
+        raw.copy_from_slice(&hash[hash.len() - ADDRESS_SIZE..]);
+        Ok(raw.into())
+    }
+
+    /// sign the keccak256 digest of msg, return the base64 encoded 65 bytes
+    /// recoverable signature (r || s || v)
+    async fn sign(&self, msg: &[u8]) -> Result<String, Error> {
+        let message = Message::from_slice(&keccak256(msg))?;
+        let signature = SECP256K1.sign_recoverable(&message, self.private_key.secret_key());
+        let (recovery_id, compact) = signature.serialize_compact();
+        let mut raw = compact.to_vec();
+        raw.push(recovery_id.to_i32() as u8);
+        Ok(base64::encode(&raw))
+    }
+
+    async fn sign_digest(&self, _sha256_digest: [u8; 32]) -> Result<String, Error> {
+        Err(Error::InputError(
+            "eth_secp256k1 keys sign keccak256 digests, not sha256".to_string(),
+        ))
+    }
+}
@@ -0,0 +1,326 @@
+//! Transports of the hardware wallets and a registry of them by scheme, so a device backend
+//! (e.g. a WebUSB bridge or a vendor socket) can be added by a downstream crate and chosen
+//! at runtime by a uri such as `hid`, `tcp:127.0.0.1:9999` or `ble`
+
+use crate::error::Error;
+use crate::key_service::ledger_service::{TransportHid, TransportTcp};
+use async_trait::async_trait;
+use ledger_crypto::APDUTransport;
+use ledger_transport::errors::TransportError;
+use ledger_transport::{APDUAnswer, APDUCommand, Exchange};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+/// a way to reach the device, it is opened again when the device reconnects
+#[async_trait]
+pub trait HardwareTransport: Send + Sync {
+    /// the name shown in the logs, e.g. `tcp:127.0.0.1:9999`
+    fn name(&self) -> String;
+
+    /// open a new connection to the device
+    async fn open(&self) -> Result<APDUTransport, Error>;
+}
+
+impl fmt::Debug for dyn HardwareTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HardwareTransport({})", self.name())
+    }
+}
+
+/// the ledger connected over usb
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HidTransport;
+
+#[async_trait]
+impl HardwareTransport for HidTransport {
+    fn name(&self) -> String {
+        "hid".to_string()
+    }
+
+    async fn open(&self) -> Result<APDUTransport, Error> {
+        Ok(APDUTransport {
+            transport_wrapper: Box::new(TransportHid::open().await?),
+        })
+    }
+}
+
+/// the apdu port of the Speculos emulator, e.g. `127.0.0.1:9999`
+#[derive(Debug, Clone)]
+pub struct TcpTransport {
+    pub address: String,
+}
+
+#[async_trait]
+impl HardwareTransport for TcpTransport {
+    fn name(&self) -> String {
+        format!("tcp:{}", self.address)
+    }
+
+    async fn open(&self) -> Result<APDUTransport, Error> {
+        Ok(APDUTransport {
+            transport_wrapper: Box::new(TransportTcp::connect(&self.address).await?),
+        })
+    }
+}
+
+/// the first paired Nano X over bluetooth
+#[cfg(feature = "ledger-ble")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BleTransport;
+
+#[cfg(feature = "ledger-ble")]
+#[async_trait]
+impl HardwareTransport for BleTransport {
+    fn name(&self) -> String {
+        "ble".to_string()
+    }
+
+    async fn open(&self) -> Result<APDUTransport, Error> {
+        use crate::key_service::ledger_ble::TransportBle;
+        Ok(APDUTransport {
+            transport_wrapper: Box::new(TransportBle::connect().await?),
+        })
+    }
+}
+
+type DeviceRequest = (
+    APDUCommand,
+    oneshot::Sender<Result<APDUAnswer, TransportError>>,
+);
+
+/// `APDUCommand` is not `Clone`, the command is copied to be sent to the device thread
+fn copy_command(command: &APDUCommand) -> APDUCommand {
+    APDUCommand {
+        cla: command.cla,
+        ins: command.ins,
+        p1: command.p1,
+        p2: command.p2,
+        data: command.data.clone(),
+    }
+}
+
+/// a device with blocking I/O (hidapi, btleplug) owned by a dedicated thread, the commands
+/// are sent to it over a channel so the runtime of the caller is never blocked, whichever
+/// scheduler it uses. The thread ends when the handle is dropped.
+pub(crate) struct DeviceThread {
+    requests: mpsc::UnboundedSender<DeviceRequest>,
+}
+
+impl DeviceThread {
+    /// open the device on a new thread named `name`
+    pub(crate) async fn spawn<D, F>(name: &str, open: F) -> Result<Self, Error>
+    where
+        D: Exchange + 'static,
+        F: FnOnce() -> Result<D, Error> + Send + 'static,
+    {
+        let (requests, mut receiver) = mpsc::unbounded_channel::<DeviceRequest>();
+        let (opened, ready) = oneshot::channel();
+        std::thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                let runtime = tokio::runtime::Builder::new().basic_scheduler().build();
+                let mut runtime = match runtime {
+                    Ok(runtime) => runtime,
+                    Err(e) => {
+                        let msg = format!("can't start the device runtime: {}", e);
+                        let _ = opened.send(Err(Error::LedgerError(msg)));
+                        return;
+                    }
+                };
+                let device = match open() {
+                    Ok(device) => device,
+                    Err(e) => {
+                        let _ = opened.send(Err(e));
+                        return;
+                    }
+                };
+                let _ = opened.send(Ok(()));
+                runtime.block_on(async move {
+                    while let Some((command, reply)) = receiver.recv().await {
+                        let _ = reply.send(device.exchange(&command).await);
+                    }
+                });
+            })
+            .map_err(|e| Error::LedgerError(format!("can't start the device thread: {}", e)))?;
+        ready
+            .await
+            .map_err(|_e| Error::LedgerError("the device thread stopped".to_string()))??;
+        Ok(Self { requests })
+    }
+
+    /// exchange the command on the device thread
+    pub(crate) async fn exchange(
+        &self,
+        command: &APDUCommand,
+    ) -> Result<APDUAnswer, TransportError> {
+        let (reply, answer) = oneshot::channel();
+        self.requests
+            .send((copy_command(command), reply))
+            .map_err(|_e| {
+                log::error!("the device thread stopped");
+                TransportError::APDUExchangeError
+            })?;
+        answer.await.map_err(|_e| {
+            log::error!("the device thread stopped during the exchange");
+            TransportError::APDUExchangeError
+        })?
+    }
+}
+
+/// creates a transport from the part of the uri after `scheme:`, empty if there is none
+pub type TransportFactory =
+    Arc<dyn Fn(&str) -> Result<Arc<dyn HardwareTransport>, Error> + Send + Sync>;
+
+/// the transports by uri scheme, `TransportRegistry::default()` has `hid`, `tcp` and
+/// `ble` (with the `ledger-ble` feature)
+#[derive(Clone)]
+pub struct TransportRegistry {
+    factories: HashMap<String, TransportFactory>,
+}
+
+impl TransportRegistry {
+    /// a registry without any transport
+    pub fn empty() -> Self {
+        Self {
+            factories: HashMap::new(),
+        }
+    }
+
+    /// add or replace the transport of a scheme
+    pub fn register<F>(&mut self, scheme: &str, factory: F) -> &mut Self
+    where
+        F: Fn(&str) -> Result<Arc<dyn HardwareTransport>, Error> + Send + Sync + 'static,
+    {
+        self.factories
+            .insert(scheme.to_lowercase(), Arc::new(factory));
+        self
+    }
+
+    /// the registered schemes, sorted
+    pub fn schemes(&self) -> Vec<&str> {
+        let mut schemes: Vec<&str> = self.factories.keys().map(String::as_str).collect();
+        schemes.sort_unstable();
+        schemes
+    }
+
+    /// the transport of a uri `scheme[:argument]`
+    pub fn create(&self, uri: &str) -> Result<Arc<dyn HardwareTransport>, Error> {
+        let (scheme, argument) = match uri.find(':') {
+            Some(colon) => (&uri[..colon], &uri[colon + 1..]),
+            None => (uri, ""),
+        };
+        let factory = self.factories.get(&scheme.to_lowercase()).ok_or_else(|| {
+            Error::InputError(format!(
+                "unknown hardware transport {}, registered: {}",
+                scheme,
+                self.schemes().join(", ")
+            ))
+        })?;
+        factory(argument)
+    }
+}
+
+impl Default for TransportRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry
+            .register("hid", |_| Ok(Arc::new(HidTransport)))
+            .register("tcp", |address| {
+                if address.is_empty() {
+                    return Err(Error::InputError(
+                        "the tcp transport needs an address, e.g. tcp:127.0.0.1:9999".to_string(),
+                    ));
+                }
+                Ok(Arc::new(TcpTransport {
+                    address: address.to_string(),
+                }))
+            });
+        #[cfg(feature = "ledger-ble")]
+        registry.register("ble", |_| Ok(Arc::new(BleTransport)));
+        registry
+    }
+}
+
+impl fmt::Debug for TransportRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransportRegistry")
+            .field("schemes", &self.schemes())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct OfflineTransport;
+
+    #[async_trait]
+    impl HardwareTransport for OfflineTransport {
+        fn name(&self) -> String {
+            "offline".to_string()
+        }
+
+        async fn open(&self) -> Result<APDUTransport, Error> {
+            Err(Error::LedgerError("device is offline".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transport_registry() {
+        let mut registry = TransportRegistry::default();
+        assert_eq!(
+            registry.create("tcp:127.0.0.1:9999").unwrap().name(),
+            "tcp:127.0.0.1:9999"
+        );
+        assert_eq!(registry.create("HID").unwrap().name(), "hid");
+        assert!(registry.create("tcp").is_err());
+        assert!(registry.create("webusb").is_err());
+
+        registry.register("webusb", |_| Ok(Arc::new(OfflineTransport)));
+        let transport = registry.create("webusb").unwrap();
+        assert!(transport.open().await.is_err());
+        assert!(registry.schemes().contains(&"webusb"));
+        assert!(TransportRegistry::empty().create("hid").is_err());
+    }
+
+    /// answers the data of the command followed by the status word 0x9000
+    struct EchoDevice;
+
+    #[async_trait]
+    impl Exchange for EchoDevice {
+        async fn exchange(&self, command: &APDUCommand) -> Result<APDUAnswer, TransportError> {
+            // a blocking device, it must not stall the runtime of the test
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            let mut answer = command.data.clone();
+            answer.extend_from_slice(&[0x90, 0x00]);
+            Ok(APDUAnswer::from_answer(answer))
+        }
+    }
+
+    // the default basic scheduler of `#[tokio::test]`, where `block_in_place` panics
+    #[tokio::test]
+    async fn test_device_thread() {
+        let device = DeviceThread::spawn("echo", || Ok(EchoDevice))
+            .await
+            .unwrap();
+        let command = APDUCommand {
+            cla: 0x55,
+            ins: 0,
+            p1: 0,
+            p2: 0,
+            data: vec![1, 2, 3],
+        };
+        let answer = device.exchange(&command).await.unwrap();
+        assert_eq!(answer.data, vec![1, 2, 3]);
+        assert_eq!(answer.retcode, 0x9000);
+
+        let failed = DeviceThread::spawn("offline", || -> Result<EchoDevice, Error> {
+            Err(Error::LedgerError("no device".to_string()))
+        })
+        .await;
+        assert!(failed.is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use crate::error::Error;
+use crate::key_service::private_key_service::PrivateKeyService;
+use crate::key_service::KeyService;
+use crate::types::key::{PrivateKey, PublicKey};
+
+use async_trait::async_trait;
+use keyring::Keyring;
+use stdtx::Address;
+
+/// KeyService which keeps the private key in the platform keyring
+/// (macOS Keychain, Windows Credential Manager or the Secret Service on linux)
+#[derive(Clone)]
+pub struct KeyringService {
+    /// keyring service name
+    pub service: String,
+    /// keyring user name of the entry
+    pub username: String,
+    inner: PrivateKeyService,
+}
+
+impl KeyringService {
+    /// store the private key into the keyring entry and create a KeyringService from it
+    pub fn save(service: &str, username: &str, private_key: PrivateKey) -> Result<Self, Error> {
+        let encoded = base64::encode(&private_key.secret_key()[..]);
+        Keyring::new(service, username)
+            .set_password(&encoded)
+            .map_err(|e| Error::KeyringError(format!("save key failed: {}", e)))?;
+        Ok(Self {
+            service: service.to_string(),
+            username: username.to_string(),
+            inner: PrivateKeyService::new(private_key),
+        })
+    }
+
+    /// load the private key from an existing keyring entry
+    pub fn load(service: &str, username: &str) -> Result<Self, Error> {
+        let encoded = Keyring::new(service, username)
+            .get_password()
+            .map_err(|e| Error::KeyringError(format!("load key failed: {}", e)))?;
+        let raw = base64::decode(&encoded)
+            .map_err(|_e| Error::KeyringError("invalid key in keyring entry".to_string()))?;
+        let private_key = PrivateKey::from_slice(&raw)?;
+        Ok(Self {
+            service: service.to_string(),
+            username: username.to_string(),
+            inner: PrivateKeyService::new(private_key),
+        })
+    }
+
+    /// remove the keyring entry
+    pub fn delete(self) -> Result<(), Error> {
+        Keyring::new(&self.service, &self.username)
+            .delete_password()
+            .map_err(|e| Error::KeyringError(format!("delete key failed: {}", e)))
+    }
+}
+
+#[async_trait]
+impl KeyService for KeyringService {
+    async fn public_key(&self) -> Result<PublicKey, Error> {
+        self.inner.public_key().await
+    }
+
+    async fn address(&self) -> Result<Address, Error> {
+        self.inner.address().await
+    }
+
+    async fn sign(&self, msg: &[u8]) -> Result<String, Error> {
+        KeyService::sign(&self.inner, msg).await
+    }
+
+    async fn sign_digest(&self, sha256_digest: [u8; 32]) -> Result<String, Error> {
+        KeyService::sign_digest(&self.inner, sha256_digest).await
+    }
+}
@@ -0,0 +1,361 @@
+use crate::error::Error;
+use crate::key_service::private_key_service::PrivateKeyService;
+use crate::key_service::KeyService;
+use crate::types::key::{PrivateKey, PublicKey};
+
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::{Aead, NewAead};
+use aes_gcm::Aes256Gcm;
+use argon2::{Algorithm, Argon2, Version};
+use async_trait::async_trait;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use stdtx::Address;
+use zeroize::Zeroizing;
+
+const KEYSTORE_VERSION: u32 = 1;
+const KDF_NAME: &str = "argon2id";
+const CIPHER_NAME: &str = "aes-256-gcm";
+const SALT_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+const KEY_SIZE: usize = 32;
+/// bounds of the kdf params of a loaded keystore, the memory size is in KiB (8 MiB to 1 GiB)
+const M_COST_RANGE: std::ops::RangeInclusive<u32> = 8 * 1024..=1024 * 1024;
+const T_COST_RANGE: std::ops::RangeInclusive<u32> = 1..=16;
+const P_COST_RANGE: std::ops::RangeInclusive<u32> = 1..=16;
+
+/// argon2id parameters stored in the keystore file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KdfParams {
+    /// memory size in KiB
+    pub m_cost: u32,
+    /// number of iterations
+    pub t_cost: u32,
+    /// degree of parallelism
+    pub p_cost: u32,
+    /// base64 encoded salt
+    pub salt: String,
+}
+
+impl KdfParams {
+    /// check the params are in the bounds, so a crafted keystore can't make the key
+    /// derivation exhaust the memory or take forever, or weaken it
+    pub fn validate(&self) -> Result<(), Error> {
+        if !M_COST_RANGE.contains(&self.m_cost)
+            || !T_COST_RANGE.contains(&self.t_cost)
+            || !P_COST_RANGE.contains(&self.p_cost)
+        {
+            return Err(Error::KeystoreError(format!(
+                "kdf params out of range: m_cost {}, t_cost {}, p_cost {}",
+                self.m_cost, self.t_cost, self.p_cost
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            m_cost: 65536,
+            t_cost: 3,
+            p_cost: 1,
+            salt: String::new(),
+        }
+    }
+}
+
+/// encrypted part of the keystore
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KeystoreCrypto {
+    pub kdf: String,
+    pub kdf_params: KdfParams,
+    pub cipher: String,
+    /// base64 encoded nonce
+    pub nonce: String,
+    /// base64 encoded encrypted secret key (with the authentication tag)
+    pub ciphertext: String,
+}
+
+impl KeystoreCrypto {
+    /// encrypt the secret with the key derived from the password and a random salt
+    pub fn encrypt(secret: &[u8], password: &str) -> Result<Self, Error> {
+        let mut rng = rand::thread_rng();
+        let mut salt = [0u8; SALT_SIZE];
+        rng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_SIZE];
+        rng.fill_bytes(&mut nonce);
+
+        let kdf_params = KdfParams {
+            salt: base64::encode(&salt),
+            ..Default::default()
+        };
+        let key = derive_key(password, &kdf_params)?;
+        let cipher = Aes256Gcm::new(GenericArray::from_slice(&*key));
+        let ciphertext = cipher
+            .encrypt(GenericArray::from_slice(&nonce), secret)
+            .map_err(|_e| Error::KeystoreError("encrypt secret failed".to_string()))?;
+        Ok(Self {
+            kdf: KDF_NAME.to_string(),
+            kdf_params,
+            cipher: CIPHER_NAME.to_string(),
+            nonce: base64::encode(&nonce),
+            ciphertext: base64::encode(&ciphertext),
+        })
+    }
+
+    /// decrypt the secret, a wrong password fails the authentication of the ciphertext
+    pub fn decrypt(&self, password: &str) -> Result<Zeroizing<Vec<u8>>, Error> {
+        if self.kdf != KDF_NAME || self.cipher != CIPHER_NAME {
+            return Err(Error::KeystoreError(format!(
+                "unsupported kdf {} or cipher {}",
+                self.kdf, self.cipher
+            )));
+        }
+        let nonce = decode_field(&self.nonce, "nonce")?;
+        if nonce.len() != NONCE_SIZE {
+            return Err(Error::KeystoreError("invalid nonce length".to_string()));
+        }
+        let ciphertext = decode_field(&self.ciphertext, "ciphertext")?;
+        let key = derive_key(password, &self.kdf_params)?;
+        let cipher = Aes256Gcm::new(GenericArray::from_slice(&*key));
+        cipher
+            .decrypt(GenericArray::from_slice(&nonce), ciphertext.as_ref())
+            .map(Zeroizing::new)
+            .map_err(|_e| Error::KeystoreError("wrong password or corrupted keystore".to_string()))
+    }
+}
+
+/// keystore file content
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Keystore {
+    pub version: u32,
+    /// base64 encoded public key, so the key can be identified without the password
+    pub public_key: String,
+    pub crypto: KeystoreCrypto,
+}
+
+impl Keystore {
+    /// encrypt the private key with the password
+    pub fn encrypt(private_key: &PrivateKey, password: &str) -> Result<Self, Error> {
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            public_key: PublicKey::from(private_key).to_string(),
+            crypto: KeystoreCrypto::encrypt(&private_key.secret_key()[..], password)?,
+        })
+    }
+
+    /// decrypt the private key with the password
+    pub fn decrypt(&self, password: &str) -> Result<PrivateKey, Error> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(Error::KeystoreError(format!(
+                "unsupported keystore version: {}",
+                self.version
+            )));
+        }
+        let plaintext = self.crypto.decrypt(password)?;
+        PrivateKey::from_slice(&plaintext)
+    }
+
+    /// load a keystore from a json file, the kdf params must be in the bounds
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let content = fs::read_to_string(path)?;
+        let keystore: Self =
+            serde_json::from_str(&content).map_err(|e| Error::SerializeError(e.to_string()))?;
+        keystore.crypto.kdf_params.validate()?;
+        Ok(keystore)
+    }
+
+    /// save the keystore into a json file, only readable by the owner on unix
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| Error::SerializeError(e.to_string()))?;
+        write_private_file(path.as_ref(), content.as_bytes())
+    }
+}
+
+/// write the file with the mode 0600 on unix, an existing file gets the mode too
+pub(crate) fn write_private_file(path: &Path, content: &[u8]) -> Result<(), Error> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content)?;
+    Ok(())
+}
+
+fn decode_field(value: &str, name: &str) -> Result<Vec<u8>, Error> {
+    base64::decode(value).map_err(|_e| Error::KeystoreError(format!("invalid base64 {}", name)))
+}
+
+fn derive_key(password: &str, params: &KdfParams) -> Result<Zeroizing<[u8; KEY_SIZE]>, Error> {
+    params.validate()?;
+    let salt = decode_field(&params.salt, "salt")?;
+    let argon2 = Argon2::new(
+        None,
+        params.t_cost,
+        params.m_cost,
+        params.p_cost,
+        Version::V0x13,
+    )
+    .map_err(|e| Error::KeystoreError(format!("invalid kdf params: {:?}", e)))?;
+    let mut key = Zeroizing::new([0u8; KEY_SIZE]);
+    argon2
+        .hash_password_into(
+            Algorithm::Argon2id,
+            password.as_bytes(),
+            &salt,
+            &[],
+            &mut *key,
+        )
+        .map_err(|e| Error::KeystoreError(format!("derive key failed: {:?}", e)))?;
+    Ok(key)
+}
+
+/// KeyService which keeps the private key in a password encrypted keystore file
+#[derive(Clone)]
+pub struct KeystoreService {
+    keystore: Keystore,
+    inner: PrivateKeyService,
+}
+
+impl KeystoreService {
+    /// create a new keystore which encrypts the private key with the password
+    pub fn new(private_key: PrivateKey, password: &str) -> Result<Self, Error> {
+        let keystore = Keystore::encrypt(&private_key, password)?;
+        Ok(Self {
+            keystore,
+            inner: PrivateKeyService::new(private_key),
+        })
+    }
+
+    /// load the keystore file and decrypt it with the password
+    pub fn load<P: AsRef<Path>>(path: P, password: &str) -> Result<Self, Error> {
+        let keystore = Keystore::load(path)?;
+        let private_key = keystore.decrypt(password)?;
+        Ok(Self {
+            keystore,
+            inner: PrivateKeyService::new(private_key),
+        })
+    }
+
+    /// save the encrypted keystore into a json file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.keystore.save(path)
+    }
+
+    /// re-encrypt the private key with a new password, the old password must be correct
+    pub fn change_password(&mut self, old_password: &str, new_password: &str) -> Result<(), Error> {
+        let private_key = self.keystore.decrypt(old_password)?;
+        self.keystore = Keystore::encrypt(&private_key, new_password)?;
+        Ok(())
+    }
+
+    /// the encrypted keystore
+    pub fn keystore(&self) -> &Keystore {
+        &self.keystore
+    }
+}
+
+#[async_trait]
+impl KeyService for KeystoreService {
+    async fn public_key(&self) -> Result<PublicKey, Error> {
+        self.inner.public_key().await
+    }
+
+    async fn address(&self) -> Result<Address, Error> {
+        self.inner.address().await
+    }
+
+    async fn sign(&self, msg: &[u8]) -> Result<String, Error> {
+        KeyService::sign(&self.inner, msg).await
+    }
+
+    async fn sign_digest(&self, sha256_digest: [u8; 32]) -> Result<String, Error> {
+        KeyService::sign_digest(&self.inner, sha256_digest).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_keystore_encrypt_decrypt() {
+        let private_raw = base64::decode("1Jp5fbY7YcFI0XZ+YW/xXD3ZyDtjy6YcIY6hcvI4Yio=").unwrap();
+        let private_key = PrivateKey::from_slice(&private_raw).unwrap();
+        let mut service = KeystoreService::new(private_key.clone(), "password").unwrap();
+        assert_eq!(
+            service.keystore().public_key,
+            "AntL+UxMyJ9NZ9DGLp2v7a3dlSxiNXMaItyOXSRw8iYi"
+        );
+
+        let decrypted = service.keystore().decrypt("password").unwrap();
+        assert_eq!(decrypted.secret_key(), private_key.secret_key());
+        assert!(service.keystore().decrypt("wrong").is_err());
+
+        service.change_password("password", "new password").unwrap();
+        assert!(service.keystore().decrypt("password").is_err());
+        let decrypted = service.keystore().decrypt("new password").unwrap();
+        assert_eq!(decrypted.secret_key(), private_key.secret_key());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_keystore_save_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let private_raw = base64::decode("1Jp5fbY7YcFI0XZ+YW/xXD3ZyDtjy6YcIY6hcvI4Yio=").unwrap();
+        let private_key = PrivateKey::from_slice(&private_raw).unwrap();
+        let service = KeystoreService::new(private_key, "password").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keystore.json");
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        service.save(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let loaded = KeystoreService::load(&path, "password").unwrap();
+        assert_eq!(loaded.keystore(), service.keystore());
+    }
+
+    #[test]
+    fn test_keystore_kdf_params_bounds() {
+        let private_raw = base64::decode("1Jp5fbY7YcFI0XZ+YW/xXD3ZyDtjy6YcIY6hcvI4Yio=").unwrap();
+        let private_key = PrivateKey::from_slice(&private_raw).unwrap();
+        let service = KeystoreService::new(private_key, "password").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keystore.json");
+        for (m_cost, t_cost, p_cost) in &[
+            (8 * 1024 - 1, 3, 1),
+            (1024 * 1024 + 1, 3, 1),
+            (65536, 0, 1),
+            (65536, 17, 1),
+            (65536, 3, 0),
+            (65536, 3, 17),
+        ] {
+            let mut keystore = service.keystore().clone();
+            keystore.crypto.kdf_params.m_cost = *m_cost;
+            keystore.crypto.kdf_params.t_cost = *t_cost;
+            keystore.crypto.kdf_params.p_cost = *p_cost;
+            keystore.save(&path).unwrap();
+            assert!(Keystore::load(&path).is_err());
+            assert!(KeystoreService::load(&path, "password").is_err());
+            assert!(keystore.decrypt("password").is_err());
+        }
+        service.save(&path).unwrap();
+        assert!(KeystoreService::load(&path, "password").is_ok());
+    }
+}
@@ -0,0 +1,204 @@
+//! Bluetooth low energy transport of the Ledger Nano X
+use crate::error::Error;
+use crate::key_service::hardware_transport::DeviceThread;
+
+use async_trait::async_trait;
+use btleplug::api::{Central, Characteristic, Peripheral as _, ValueNotification};
+#[cfg(target_os = "linux")]
+use btleplug::bluez::{manager::Manager, peripheral::Peripheral};
+#[cfg(target_os = "macos")]
+use btleplug::corebluetooth::{manager::Manager, peripheral::Peripheral};
+#[cfg(target_os = "windows")]
+use btleplug::winrtble::{manager::Manager, peripheral::Peripheral};
+use ledger_transport::errors::TransportError;
+use ledger_transport::{APDUAnswer, APDUCommand, Exchange};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// notify characteristic of the nano x ledger service
+const NOTIFY_UUID: &str = "13d634002c97000400014c6564676572";
+/// write characteristic of the nano x ledger service
+const WRITE_UUID: &str = "13d634002c97000400024c6564676572";
+/// name prefix of the advertised nano x device
+const DEVICE_NAME_PREFIX: &str = "Nano X";
+
+const TAG_APDU: u8 = 0x05;
+/// payload size of a frame with the default ATT MTU
+const FRAME_SIZE: usize = 20;
+const SCAN_DURATION: Duration = Duration::from_secs(3);
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// APDU transport over BLE, an APDU is split into frames of
+/// `tag(0x05) || sequence(u16 be) || [apdu length(u16 be) in the first frame] || data`.
+/// The btleplug api is blocking, so the device is owned by a `ledger-ble` thread.
+pub struct TransportBle {
+    device: DeviceThread,
+}
+
+/// the connected Nano X, only used on the device thread
+struct BleDevice {
+    peripheral: Peripheral,
+    write: Characteristic,
+    notifications: Mutex<Receiver<Vec<u8>>>,
+}
+
+fn normalize_uuid(uuid: &str) -> String {
+    uuid.chars()
+        .filter(|c| c.is_ascii_hexdigit())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+fn ble_error<E: std::fmt::Debug>(context: &str) -> impl Fn(E) -> Error + '_ {
+    move |e| Error::LedgerError(format!("{}: {:?}", context, e))
+}
+
+impl TransportBle {
+    /// scan for the first Nano X and connect to it, the device must be paired
+    pub async fn connect() -> Result<Self, Error> {
+        let device = DeviceThread::spawn("ledger-ble", BleDevice::connect).await?;
+        Ok(Self { device })
+    }
+}
+
+impl BleDevice {
+    fn connect() -> Result<Self, Error> {
+        let manager = Manager::new().map_err(ble_error("ble manager"))?;
+        let central = manager
+            .adapters()
+            .map_err(ble_error("list ble adapters"))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::LedgerError("no ble adapter found".to_string()))?;
+        central.start_scan().map_err(ble_error("ble scan"))?;
+        std::thread::sleep(SCAN_DURATION);
+        let peripheral = central
+            .peripherals()
+            .into_iter()
+            .find(|p| {
+                p.properties()
+                    .local_name
+                    .map(|name| name.starts_with(DEVICE_NAME_PREFIX))
+                    .unwrap_or(false)
+            })
+            .ok_or_else(|| Error::LedgerError("can't find Nano X over ble".to_string()))?;
+        peripheral.connect().map_err(ble_error("ble connect"))?;
+        let characteristics = peripheral
+            .discover_characteristics()
+            .map_err(ble_error("discover characteristics"))?;
+        let find = |uuid: &str| {
+            characteristics
+                .iter()
+                .find(|c| normalize_uuid(&c.uuid.to_string()) == uuid)
+                .cloned()
+                .ok_or_else(|| Error::LedgerError(format!("characteristic {} not found", uuid)))
+        };
+        let notify = find(NOTIFY_UUID)?;
+        let write = find(WRITE_UUID)?;
+
+        let (sender, receiver) = channel();
+        let sender = Mutex::new(sender);
+        peripheral.on_notification(Box::new(move |n: ValueNotification| {
+            if let Ok(sender) = sender.lock() {
+                let _ = sender.send(n.value);
+            }
+        }));
+        peripheral
+            .subscribe(&notify)
+            .map_err(ble_error("subscribe notification"))?;
+        Ok(Self {
+            peripheral,
+            write,
+            notifications: Mutex::new(receiver),
+        })
+    }
+
+    fn exchange_blocking(&self, apdu: &[u8]) -> Result<Vec<u8>, Error> {
+        for frame in frames(apdu) {
+            self.peripheral
+                .command(&self.write, &frame)
+                .map_err(ble_error("ble write"))?;
+        }
+        let notifications = self
+            .notifications
+            .lock()
+            .map_err(|_e| Error::LedgerError("ble lock poisoned".to_string()))?;
+        let mut answer = vec![];
+        let mut expected = None;
+        let mut sequence = 0u16;
+        loop {
+            let frame = notifications
+                .recv_timeout(READ_TIMEOUT)
+                .map_err(ble_error("ble read"))?;
+            if frame.len() < 3 || frame[0] != TAG_APDU {
+                return Err(Error::LedgerError("invalid ble frame".to_string()));
+            }
+            if u16::from_be_bytes([frame[1], frame[2]]) != sequence {
+                return Err(Error::LedgerError(
+                    "unexpected ble frame sequence".to_string(),
+                ));
+            }
+            let data = if sequence == 0 {
+                if frame.len() < 5 {
+                    return Err(Error::LedgerError("invalid ble frame".to_string()));
+                }
+                expected = Some(u16::from_be_bytes([frame[3], frame[4]]) as usize);
+                &frame[5..]
+            } else {
+                &frame[3..]
+            };
+            answer.extend_from_slice(data);
+            sequence += 1;
+            if let Some(expected) = expected {
+                if answer.len() >= expected {
+                    answer.truncate(expected);
+                    return Ok(answer);
+                }
+            }
+        }
+    }
+}
+
+/// split the apdu into ble frames
+fn frames(apdu: &[u8]) -> Vec<Vec<u8>> {
+    let mut payload = (apdu.len() as u16).to_be_bytes().to_vec();
+    payload.extend_from_slice(apdu);
+    payload
+        .chunks(FRAME_SIZE - 3)
+        .enumerate()
+        .map(|(sequence, chunk)| {
+            let mut frame = vec![TAG_APDU];
+            frame.extend_from_slice(&(sequence as u16).to_be_bytes());
+            frame.extend_from_slice(chunk);
+            frame
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Exchange for TransportBle {
+    async fn exchange(&self, command: &APDUCommand) -> Result<APDUAnswer, TransportError> {
+        self.device.exchange(command).await
+    }
+}
+
+#[async_trait]
+impl Exchange for BleDevice {
+    /// blocks, it only runs on the device thread
+    async fn exchange(&self, command: &APDUCommand) -> Result<APDUAnswer, TransportError> {
+        let mut apdu = vec![
+            command.cla,
+            command.ins,
+            command.p1,
+            command.p2,
+            command.data.len() as u8,
+        ];
+        apdu.extend_from_slice(&command.data);
+        let answer = self.exchange_blocking(&apdu).map_err(|e| {
+            log::error!("ble exchange failed: {}", e);
+            TransportError::APDUExchangeError
+        })?;
+        Ok(APDUAnswer::from_answer(answer))
+    }
+}
@@ -0,0 +1,184 @@
+//! APDU client of the official Cosmos ledger app
+use crate::error::Error;
+
+use ledger_crypto::APDUTransport;
+use ledger_transport::{APDUAnswer, APDUCommand};
+use secp256k1::Signature;
+
+const CLA: u8 = 0x55;
+const INS_GET_VERSION: u8 = 0x00;
+const INS_SIGN_SECP256K1: u8 = 0x02;
+const INS_GET_ADDR_SECP256K1: u8 = 0x04;
+
+const CLA_DASHBOARD: u8 = 0xb0;
+const INS_GET_APP_INFO: u8 = 0x01;
+
+const PAYLOAD_INIT: u8 = 0x00;
+const PAYLOAD_ADD: u8 = 0x01;
+const PAYLOAD_LAST: u8 = 0x02;
+
+const CHUNK_SIZE: usize = 250;
+const RETCODE_OK: u16 = 0x9000;
+const PUBLIC_KEY_SIZE: usize = 33;
+const HARDENED: u32 = 0x8000_0000;
+
+/// version of the cosmos app
+#[derive(Debug, Clone)]
+pub struct CosmosVersion {
+    pub test_mode: bool,
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+}
+
+/// the Cosmos ledger app (coin type 118)
+pub struct CosmosApp {
+    transport: APDUTransport,
+}
+
+impl CosmosApp {
+    pub fn new(transport: APDUTransport) -> Self {
+        Self { transport }
+    }
+
+    async fn exchange(&self, command: APDUCommand) -> Result<APDUAnswer, Error> {
+        let answer = self
+            .transport
+            .exchange(&command)
+            .await
+            .map_err(|e| Error::LedgerError(format!("apdu exchange failed: {:?}", e)))?;
+        if answer.retcode != RETCODE_OK {
+            return Err(Error::LedgerError(format!(
+                "cosmos app error, code: {:#x}",
+                answer.retcode
+            )));
+        }
+        Ok(answer)
+    }
+
+    /// name of the running app
+    pub async fn get_app_name(&self) -> Result<String, Error> {
+        let command = APDUCommand {
+            cla: CLA_DASHBOARD,
+            ins: INS_GET_APP_INFO,
+            p1: 0,
+            p2: 0,
+            data: vec![],
+        };
+        let answer = self.exchange(command).await?;
+        // format id, name length, name...
+        let data = answer.data;
+        if data.len() < 2 || data.len() < 2 + data[1] as usize {
+            return Err(Error::LedgerError("invalid app info".to_string()));
+        }
+        let name = &data[2..2 + data[1] as usize];
+        Ok(String::from_utf8_lossy(name).to_string())
+    }
+
+    pub async fn get_version(&self) -> Result<CosmosVersion, Error> {
+        let command = APDUCommand {
+            cla: CLA,
+            ins: INS_GET_VERSION,
+            p1: 0,
+            p2: 0,
+            data: vec![],
+        };
+        let data = self.exchange(command).await?.data;
+        if data.len() < 4 {
+            return Err(Error::LedgerError("invalid version".to_string()));
+        }
+        Ok(CosmosVersion {
+            test_mode: data[0] != 0,
+            major: data[1],
+            minor: data[2],
+            patch: data[3],
+        })
+    }
+
+    /// return the compressed public key and the bech32 address of the path
+    pub async fn get_address(
+        &self,
+        hrp: &str,
+        hd_path: &str,
+        require_confirmation: bool,
+    ) -> Result<([u8; PUBLIC_KEY_SIZE], String), Error> {
+        let mut data = vec![hrp.len() as u8];
+        data.extend_from_slice(hrp.as_bytes());
+        data.extend_from_slice(&serialize_path(hd_path)?);
+        let command = APDUCommand {
+            cla: CLA,
+            ins: INS_GET_ADDR_SECP256K1,
+            p1: require_confirmation as u8,
+            p2: 0,
+            data,
+        };
+        let data = self.exchange(command).await?.data;
+        if data.len() <= PUBLIC_KEY_SIZE {
+            return Err(Error::LedgerError("invalid address response".to_string()));
+        }
+        let mut public_key = [0u8; PUBLIC_KEY_SIZE];
+        public_key.copy_from_slice(&data[..PUBLIC_KEY_SIZE]);
+        let address = String::from_utf8(data[PUBLIC_KEY_SIZE..].to_vec())
+            .map_err(|_e| Error::LedgerError("invalid address".to_string()))?;
+        Ok((public_key, address))
+    }
+
+    /// sign the amino json sign doc, return the 64 bytes compact signature
+    pub async fn sign(&self, hd_path: &str, msg: &[u8]) -> Result<Vec<u8>, Error> {
+        let init = APDUCommand {
+            cla: CLA,
+            ins: INS_SIGN_SECP256K1,
+            p1: PAYLOAD_INIT,
+            p2: 0,
+            data: serialize_path(hd_path)?,
+        };
+        let mut answer = self.exchange(init).await?;
+        let chunks: Vec<&[u8]> = msg.chunks(CHUNK_SIZE).collect();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let p1 = if i + 1 == chunks.len() {
+                PAYLOAD_LAST
+            } else {
+                PAYLOAD_ADD
+            };
+            let command = APDUCommand {
+                cla: CLA,
+                ins: INS_SIGN_SECP256K1,
+                p1,
+                p2: 0,
+                data: chunk.to_vec(),
+            };
+            answer = self.exchange(command).await?;
+        }
+        // the app returns a DER signature
+        let mut signature = Signature::from_der(&answer.data)?;
+        signature.normalize_s();
+        Ok(signature.serialize_compact().to_vec())
+    }
+}
+
+/// serialize m/44'/coin'/account'/change/index as 5 little endian u32
+fn serialize_path(hd_path: &str) -> Result<Vec<u8>, Error> {
+    let invalid = || Error::InputError("input invalid hd path".to_string());
+    let mut parts = hd_path.split('/');
+    if parts.next() != Some("m") {
+        return Err(invalid());
+    }
+    let mut buf = vec![];
+    let mut count = 0;
+    for part in parts {
+        let (index, hardened) = match part.strip_suffix('\'') {
+            Some(index) => (index, HARDENED),
+            None => (part, 0),
+        };
+        let index: u32 = index.parse().map_err(|_e| invalid())?;
+        if index >= HARDENED {
+            return Err(invalid());
+        }
+        buf.extend_from_slice(&(index | hardened).to_le_bytes());
+        count += 1;
+    }
+    if count != 5 {
+        return Err(invalid());
+    }
+    Ok(buf)
+}
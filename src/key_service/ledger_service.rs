@@ -1,12 +1,26 @@
 use async_trait::async_trait;
 use ledger_crypto::Address;
 use ledger_crypto::{APDUTransport, CryptoApp};
+use ledger_transport::errors::TransportError;
+use ledger_transport::{APDUAnswer, APDUCommand, Exchange};
 use secp256k1::PublicKey as InnerPublicKey;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio::time::{self, delay_for};
 use zx_bip44::BIP44Path;
 
+use crate::constant::{COIN_TYPE, COSMOS_COIN_TYPE};
 use crate::error::Error;
+use crate::key_service::hardware_transport::{
+    DeviceThread, HardwareTransport, HidTransport, TcpTransport,
+};
+use crate::key_service::ledger_cosmos::CosmosApp;
 use crate::key_service::KeyService;
+use crate::types::address::ChainConfig;
 use crate::types::key::PublicKey;
 
 /// block to wait for the async result
@@ -20,6 +34,343 @@ macro_rules! sync {
     }};
 }
 
+/// APDU transport over the TCP apdu port of the Speculos emulator.
+///
+/// A command is sent as a 4 bytes big endian length followed by the APDU, the answer
+/// is a 4 bytes big endian length of the data followed by the data and the status word.
+pub struct TransportTcp {
+    stream: Mutex<TcpStream>,
+}
+
+impl TransportTcp {
+    /// connect to the speculos apdu port
+    pub async fn connect(address: &str) -> Result<Self, Error> {
+        let stream = TcpStream::connect(address).await.map_err(|e| {
+            Error::LedgerError(format!("can't connect to speculos {}: {}", address, e))
+        })?;
+        Ok(Self {
+            stream: Mutex::new(stream),
+        })
+    }
+
+    async fn send_apdu(&self, apdu: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut stream = self.stream.lock().await;
+        stream.write_all(&(apdu.len() as u32).to_be_bytes()).await?;
+        stream.write_all(apdu).await?;
+
+        let mut size = [0u8; 4];
+        stream.read_exact(&mut size).await?;
+        // data and the 2 bytes status word
+        let mut answer = vec![0u8; u32::from_be_bytes(size) as usize + 2];
+        stream.read_exact(&mut answer).await?;
+        Ok(answer)
+    }
+}
+
+#[async_trait]
+impl Exchange for TransportTcp {
+    async fn exchange(&self, command: &APDUCommand) -> Result<APDUAnswer, TransportError> {
+        let mut apdu = vec![
+            command.cla,
+            command.ins,
+            command.p1,
+            command.p2,
+            command.data.len() as u8,
+        ];
+        apdu.extend_from_slice(&command.data);
+        log::debug!("speculos apdu: {}", hex::encode(&apdu));
+        let answer = self.send_apdu(&apdu).await.map_err(|e| {
+            log::error!("speculos exchange failed: {}", e);
+            TransportError::APDUExchangeError
+        })?;
+        log::debug!("speculos answer: {}", hex::encode(&answer));
+        Ok(APDUAnswer::from_answer(answer))
+    }
+}
+
+/// APDU transport over usb HID. The hidapi calls block, so the device is owned by a
+/// `ledger-hid` device thread, which keeps the runtime of the caller free while the user
+/// confirms on the device.
+pub struct TransportHid {
+    device: DeviceThread,
+}
+
+impl TransportHid {
+    /// open the ledger device on a new device thread
+    pub async fn open() -> Result<Self, Error> {
+        let device = DeviceThread::spawn("ledger-hid", || {
+            ledger::TransportNativeHID::new().map_err(|e| {
+                Error::LedgerError(format!("can't find ledger device: {:?}, see more: https://support.ledger.com/hc/en-us/articles/115005165269-Fix-connection-issues", e))
+            })
+        })
+        .await?;
+        Ok(Self { device })
+    }
+}
+
+#[async_trait]
+impl Exchange for TransportHid {
+    async fn exchange(&self, command: &APDUCommand) -> Result<APDUAnswer, TransportError> {
+        self.device.exchange(command).await
+    }
+}
+
+/// the ledger app which holds the keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerAppType {
+    /// the CRO app, app name "cryp", coin type 394
+    Crypto,
+    /// the official Cosmos app, app name "Cosmos", coin type 118
+    Cosmos,
+}
+
+impl LedgerAppType {
+    /// the bip44 coin type used by the app
+    pub fn coin_type(&self) -> u32 {
+        match self {
+            LedgerAppType::Crypto => COIN_TYPE,
+            LedgerAppType::Cosmos => COSMOS_COIN_TYPE,
+        }
+    }
+}
+
+/// the sign doc format which the app can display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerSignMode {
+    /// SIGN_MODE_LEGACY_AMINO_JSON
+    LegacyAminoJson,
+    /// SIGN_MODE_TEXTUAL
+    Textual,
+}
+
+/// features of the connected app, derived from its name and version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerCapabilities {
+    pub app_name: String,
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+    /// max size of a message to be signed, not reported by the app, see
+    /// `LedgerServiceHID::set_max_message_size`
+    pub max_message_size: usize,
+    pub sign_modes: Vec<LedgerSignMode>,
+}
+
+/// oldest major version which speaks the current APDU layout
+const MIN_MAJOR_VERSION: u8 = 2;
+/// default max size of the sign doc kept by the app
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 16384;
+/// first version of the Cosmos app which supports SIGN_MODE_TEXTUAL
+const COSMOS_TEXTUAL_VERSION: (u8, u8) = (2, 34);
+
+impl LedgerCapabilities {
+    fn new(app_type: LedgerAppType, app_name: String, major: u8, minor: u8, patch: u8) -> Self {
+        let mut sign_modes = vec![LedgerSignMode::LegacyAminoJson];
+        if app_type == LedgerAppType::Cosmos && (major, minor) >= COSMOS_TEXTUAL_VERSION {
+            sign_modes.push(LedgerSignMode::Textual);
+        }
+        Self {
+            app_name,
+            major,
+            minor,
+            patch,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            sign_modes,
+        }
+    }
+
+    /// the app supports SIGN_MODE_TEXTUAL
+    pub fn textual(&self) -> bool {
+        self.sign_modes.contains(&LedgerSignMode::Textual)
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        if self.major < MIN_MAJOR_VERSION {
+            return Err(Error::LedgerError(format!(
+                "{} v{}.{}.{} is too old, please upgrade the app",
+                self.app_name, self.major, self.minor, self.patch
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// APDU client of the connected ledger app
+pub enum LedgerApp {
+    Crypto(CryptoApp),
+    Cosmos(CosmosApp),
+}
+
+impl LedgerApp {
+    /// open the app and check its name and version
+    async fn open(app_type: LedgerAppType, transport: APDUTransport) -> Result<Self, Error> {
+        let app = match app_type {
+            LedgerAppType::Crypto => LedgerApp::Crypto(CryptoApp::new(transport)),
+            LedgerAppType::Cosmos => LedgerApp::Cosmos(CosmosApp::new(transport)),
+        };
+        let capabilities = app.capabilities().await?;
+        log::debug!("app capabilities: {:?}", capabilities);
+        let expected_name = match app_type {
+            LedgerAppType::Crypto => "cryp",
+            LedgerAppType::Cosmos => "cosmos",
+        };
+        if capabilities.app_name.to_lowercase() != expected_name {
+            return Err(Error::LedgerError(format!(
+                "{} not {:?} app",
+                capabilities.app_name, app_type
+            )));
+        }
+        capabilities.check()?;
+        Ok(app)
+    }
+
+    /// query the name and version of the app
+    async fn capabilities(&self) -> Result<LedgerCapabilities, Error> {
+        match self {
+            LedgerApp::Crypto(app) => {
+                let app_info = app
+                    .get_app_info()
+                    .await
+                    .map_err(|e| Error::LedgerError(format!("get app info failed: {:?}", e)))?;
+                let version = app
+                    .get_version()
+                    .await
+                    .map_err(|e| Error::LedgerError(format!("get version failed: {:?}", e)))?;
+                Ok(LedgerCapabilities::new(
+                    LedgerAppType::Crypto,
+                    app_info.app_name,
+                    version.major,
+                    version.minor,
+                    version.patch,
+                ))
+            }
+            LedgerApp::Cosmos(app) => {
+                let app_name = app.get_app_name().await?;
+                let version = app.get_version().await?;
+                Ok(LedgerCapabilities::new(
+                    LedgerAppType::Cosmos,
+                    app_name,
+                    version.major,
+                    version.minor,
+                    version.patch,
+                ))
+            }
+        }
+    }
+
+    /// the type of the app
+    pub fn app_type(&self) -> LedgerAppType {
+        match self {
+            LedgerApp::Crypto(_) => LedgerAppType::Crypto,
+            LedgerApp::Cosmos(_) => LedgerAppType::Cosmos,
+        }
+    }
+
+    /// get public key and address of the path from the device
+    async fn get_address(
+        &self,
+        acc_address_prefix: &str,
+        chain_path: &str,
+        require_confirmation: bool,
+    ) -> Result<Address, Error> {
+        match self {
+            LedgerApp::Crypto(app) => app
+                .get_address(
+                    acc_address_prefix,
+                    &parse_path(chain_path)?,
+                    require_confirmation,
+                )
+                .await
+                .map_err(|e| Error::LedgerError(format!("get address failed: {:?}", e))),
+            LedgerApp::Cosmos(app) => {
+                let (public_key, address) = app
+                    .get_address(acc_address_prefix, chain_path, require_confirmation)
+                    .await?;
+                Ok(Address {
+                    public_key,
+                    address,
+                })
+            }
+        }
+    }
+
+    async fn sign(&self, chain_path: &str, msg: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            LedgerApp::Crypto(app) => app
+                .sign(&parse_path(chain_path)?, msg)
+                .await
+                .map_err(|e| Error::LedgerError(e.to_string())),
+            LedgerApp::Cosmos(app) => app.sign(chain_path, msg).await,
+        }
+    }
+
+    /// check the app still responds
+    async fn ping(&self) -> Result<(), Error> {
+        match self {
+            LedgerApp::Crypto(app) => app
+                .get_version()
+                .await
+                .map(|_| ())
+                .map_err(|e| Error::LedgerError(format!("get version failed: {:?}", e))),
+            LedgerApp::Cosmos(app) => app.get_version().await.map(|_| ()),
+        }
+    }
+}
+
+/// retry policy of the transient device errors (device asleep, app closed...)
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// max times to reconnect and retry an operation
+    pub max_retries: u32,
+    /// delay before reconnecting
+    pub delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// timeouts of the device operations, `None` waits forever
+#[derive(Debug, Clone)]
+pub struct LedgerTimeouts {
+    /// timeout of the requests answered without user interaction
+    pub query: Option<Duration>,
+    /// timeout of the requests waiting for the user to confirm on the device
+    /// (sign, verify address)
+    pub confirmation: Option<Duration>,
+}
+
+impl Default for LedgerTimeouts {
+    fn default() -> Self {
+        Self {
+            query: Some(Duration::from_secs(10)),
+            confirmation: Some(Duration::from_secs(120)),
+        }
+    }
+}
+
+/// how the device is connected, used to reconnect
+#[derive(Debug, Clone)]
+enum Connection {
+    Hardware(Arc<dyn HardwareTransport>),
+    /// a custom transport can't be recreated, the operation is only retried
+    Custom,
+}
+
+impl Connection {
+    async fn transport(&self) -> Result<Option<APDUTransport>, Error> {
+        match self {
+            Connection::Hardware(transport) => Ok(Some(transport.open().await?)),
+            Connection::Custom => Ok(None),
+        }
+    }
+}
+
 /// Hedger Service
 #[derive(Clone)]
 pub struct LedgerServiceHID {
@@ -27,8 +378,21 @@ pub struct LedgerServiceHID {
     pub acc_address_prefix: String,
     /// chain path
     pub path: Arc<BIP44Path>,
-    /// crypto app of ledger
-    pub app: Arc<CryptoApp>,
+    /// chain path in string format
+    hd_path: String,
+    /// app of ledger, shared by the clones and replaced on reconnection.
+    /// The lock is held during a whole operation, so the APDU chunks of concurrent
+    /// operations are never interleaved, the operations on a device run one by one.
+    pub app: Arc<Mutex<Arc<LedgerApp>>>,
+    /// type of the ledger app
+    app_type: LedgerAppType,
+    /// bip44 coin type of `with_account_index`
+    coin_type: u32,
+    connection: Connection,
+    retry_policy: RetryPolicy,
+    timeouts: LedgerTimeouts,
+    /// the larger messages are rejected before reaching the device
+    max_message_size: usize,
     /// public key and address
     pubkey_address: Address,
     /// confirmation on ledger or not
@@ -38,7 +402,7 @@ pub struct LedgerServiceHID {
 impl std::fmt::Debug for LedgerServiceHID {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("LedgerService")
-            .field("app", &"CryptoApp")
+            .field("app", &self.app_type)
             .field("require_confirmation", &self.require_confirmation)
             .finish()
     }
@@ -47,69 +411,306 @@ impl std::fmt::Debug for LedgerServiceHID {
 impl LedgerServiceHID {
     /// create a new LedgerService
     pub async fn new(
+        app_type: LedgerAppType,
         acc_address_prefix: String,
         chain_path: &str,
         require_confirmation: bool,
     ) -> Result<Self, Error> {
-        let wrapper = ledger::TransportNativeHID::new().map_err(|e| {
-            let msg = format!("can't find ledger device: {:?}, see more: https://support.ledger.com/hc/en-us/articles/115005165269-Fix-connection-issues", e);
-            Error::LedgerError(msg)
-        })?;
-        let transport = APDUTransport {
-            transport_wrapper: Box::new(wrapper),
+        Self::new_with_hardware_transport(
+            app_type,
+            Arc::new(HidTransport),
+            acc_address_prefix,
+            chain_path,
+            require_confirmation,
+        )
+        .await
+    }
+
+    /// create a new LedgerService of the first key of the chain, with its account prefix
+    /// and coin type, e.g. `ChainConfig::crypto_org_testnet()`
+    pub async fn new_with_chain_config(
+        app_type: LedgerAppType,
+        chain_config: &ChainConfig,
+        require_confirmation: bool,
+    ) -> Result<Self, Error> {
+        let chain_path = chain_config.derivation_path(0, 0, 0).to_string();
+        let mut service = Self::new(
+            app_type,
+            chain_config.account_prefix.clone(),
+            &chain_path,
+            require_confirmation,
+        )
+        .await?;
+        service.coin_type = chain_config.coin_type;
+        Ok(service)
+    }
+
+    /// create a new LedgerService connected to a Speculos emulator, `address` is the
+    /// apdu port of speculos, e.g. `127.0.0.1:9999`
+    pub async fn new_tcp(
+        address: &str,
+        app_type: LedgerAppType,
+        acc_address_prefix: String,
+        chain_path: &str,
+        require_confirmation: bool,
+    ) -> Result<Self, Error> {
+        let transport = TcpTransport {
+            address: address.to_string(),
         };
-        let app = CryptoApp::new(transport);
-        let app_info = app
-            .get_app_info()
-            .await
-            .map_err(|e| Error::LedgerError(format!("get app info failed: {:?}", e)))?;
-        log::debug!("app info: {:?}", app_info);
-        if app_info.app_name.to_lowercase() != "cryp" {
-            return Err(Error::LedgerError(format!(
-                "{} not CRO app",
-                app_info.app_name
-            )));
-        }
-        let app_version = app
-            .get_version()
-            .await
-            .map_err(|e| Error::LedgerError(format!("get version failed: {:?}", e)))?;
-        log::debug!("app version: {:?}", app_version);
-        if app_version.major != 2 {
-            return Err(Error::LedgerError(
-                "only support v2 major version".to_string(),
-            ));
-        }
+        Self::new_with_hardware_transport(
+            app_type,
+            Arc::new(transport),
+            acc_address_prefix,
+            chain_path,
+            require_confirmation,
+        )
+        .await
+    }
+
+    /// create a new LedgerService over a transport which is opened again on reconnection,
+    /// e.g. `TransportRegistry::default().create("tcp:127.0.0.1:9999")?`
+    pub async fn new_with_hardware_transport(
+        app_type: LedgerAppType,
+        transport: Arc<dyn HardwareTransport>,
+        acc_address_prefix: String,
+        chain_path: &str,
+        require_confirmation: bool,
+    ) -> Result<Self, Error> {
+        Self::connect(
+            app_type,
+            transport.open().await?,
+            Connection::Hardware(transport),
+            acc_address_prefix,
+            chain_path,
+            require_confirmation,
+        )
+        .await
+    }
 
-        let path = BIP44Path::from_string(chain_path)
-            .map_err(|_e| Error::InputError("input invalid hd path".to_string()))?;
+    /// create a new LedgerService over a custom transport which is not reopened on
+    /// reconnection, prefer `new_with_hardware_transport`
+    pub async fn new_with_transport(
+        app_type: LedgerAppType,
+        transport: APDUTransport,
+        acc_address_prefix: String,
+        chain_path: &str,
+        require_confirmation: bool,
+    ) -> Result<Self, Error> {
+        Self::connect(
+            app_type,
+            transport,
+            Connection::Custom,
+            acc_address_prefix,
+            chain_path,
+            require_confirmation,
+        )
+        .await
+    }
 
-        // get public key and address
+    async fn connect(
+        app_type: LedgerAppType,
+        transport: APDUTransport,
+        connection: Connection,
+        acc_address_prefix: String,
+        chain_path: &str,
+        require_confirmation: bool,
+    ) -> Result<Self, Error> {
+        let app = LedgerApp::open(app_type, transport).await?;
+        let path = parse_path(chain_path)?;
         let pubkey_address = app
-            .get_address(&acc_address_prefix, &path, false)
-            .await
-            .map_err(|e| Error::LedgerError(format!("get address failed: {:?}", e)))?;
+            .get_address(&acc_address_prefix, chain_path, false)
+            .await?;
 
         Ok(Self {
             acc_address_prefix,
             path: Arc::new(path),
+            hd_path: chain_path.to_string(),
             pubkey_address,
-            app: Arc::new(app),
+            app: Arc::new(Mutex::new(Arc::new(app))),
+            app_type,
+            coin_type: app_type.coin_type(),
+            connection,
+            retry_policy: RetryPolicy::default(),
+            timeouts: LedgerTimeouts::default(),
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
             require_confirmation,
         })
     }
+
+    /// set the retry policy of the transient device errors
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) -> &mut Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// set the timeouts of the device operations
+    pub fn set_timeouts(&mut self, timeouts: LedgerTimeouts) -> &mut Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// set the max size of a message to be signed, 16384 bytes by default,
+    /// e.g. for an app release which keeps larger sign docs
+    pub fn set_max_message_size(&mut self, max_message_size: usize) -> &mut Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+
+    /// run the operation, if it fails and the app doesn't respond any more,
+    /// reconnect the device and retry according to the retry policy.
+    /// The operation is cancelled if it doesn't finish in `timeout`, a timeout is not retried
+    /// but the transport is reopened, the answer of the cancelled APDU exchange may still
+    /// arrive and would be read by the next exchange.
+    async fn with_retry<F, Fut, R>(&self, timeout: Option<Duration>, op: F) -> Result<R, Error>
+    where
+        F: Fn(Arc<LedgerApp>) -> Fut,
+        Fut: Future<Output = Result<R, Error>>,
+    {
+        // serialize the operations on the device
+        let mut guard = self.app.lock().await;
+        let mut retries = 0;
+        loop {
+            let app = guard.clone();
+            let result = match timeout {
+                Some(duration) => match time::timeout(duration, op(app.clone())).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        drop(app);
+                        self.reset(&mut guard).await;
+                        return Err(Error::LedgerTimeout(duration));
+                    }
+                },
+                None => op(app.clone()).await,
+            };
+            let err = match result {
+                Ok(result) => return Ok(result),
+                Err(e) => e,
+            };
+            // the device is alive, e.g. the user rejected on the device
+            if app.ping().await.is_ok() {
+                return Err(err);
+            }
+            if retries >= self.retry_policy.max_retries {
+                return Err(Error::LedgerError(format!(
+                    "failed after {} retries: {}",
+                    retries, err
+                )));
+            }
+            retries += 1;
+            log::warn!("ledger error: {}, reconnecting ({})", err, retries);
+            delay_for(self.retry_policy.delay).await;
+            match self.reconnect().await {
+                Ok(Some(app)) => *guard = Arc::new(app),
+                Ok(None) => {}
+                Err(e) => log::warn!("ledger reconnect failed: {}", e),
+            }
+        }
+    }
+
+    /// reopen the transport after a timeout, the app is kept if it can't be reopened
+    async fn reset(&self, app: &mut Arc<LedgerApp>) {
+        let reconnect = match self.timeouts.query {
+            Some(duration) => time::timeout(duration, self.reconnect())
+                .await
+                .unwrap_or(Err(Error::LedgerTimeout(duration))),
+            None => self.reconnect().await,
+        };
+        match reconnect {
+            Ok(Some(reopened)) => *app = Arc::new(reopened),
+            Ok(None) => log::warn!("ledger timeout, a custom transport can't be reopened"),
+            Err(e) => log::warn!("ledger reconnect after the timeout failed: {}", e),
+        }
+    }
+
+    async fn reconnect(&self) -> Result<Option<LedgerApp>, Error> {
+        match self.connection.transport().await? {
+            Some(transport) => Ok(Some(LedgerApp::open(self.app_type, transport).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// create a LedgerService of another hd path which shares the connected device
+    pub async fn with_path(&self, chain_path: &str) -> Result<Self, Error> {
+        let path = parse_path(chain_path)?;
+        let prefix = &self.acc_address_prefix;
+        let pubkey_address = self
+            .with_retry(self.timeouts.query, |app| async move {
+                app.get_address(prefix, chain_path, false).await
+            })
+            .await?;
+        Ok(Self {
+            acc_address_prefix: self.acc_address_prefix.clone(),
+            path: Arc::new(path),
+            hd_path: chain_path.to_string(),
+            pubkey_address,
+            app: self.app.clone(),
+            app_type: self.app_type,
+            coin_type: self.coin_type,
+            connection: self.connection.clone(),
+            retry_policy: self.retry_policy.clone(),
+            timeouts: self.timeouts.clone(),
+            max_message_size: self.max_message_size,
+            require_confirmation: self.require_confirmation,
+        })
+    }
+
+    /// show the address on the device and wait for the user to confirm it,
+    /// return the confirmed bech32 address
+    pub async fn verify_address(&self) -> Result<String, Error> {
+        let (prefix, hd_path) = (&self.acc_address_prefix, &self.hd_path);
+        let confirmed = self
+            .with_retry(self.timeouts.confirmation, |app| async move {
+                app.get_address(prefix, hd_path, true).await
+            })
+            .await?;
+        if confirmed.address != self.pubkey_address.address {
+            return Err(Error::LedgerError(format!(
+                "address on device {} mismatches {}",
+                confirmed.address, self.pubkey_address.address
+            )));
+        }
+        Ok(confirmed.address)
+    }
+
+    /// the name, version and supported features of the connected app, with the
+    /// max message size of `set_max_message_size`
+    pub async fn capabilities(&self) -> Result<LedgerCapabilities, Error> {
+        let mut capabilities = self
+            .with_retry(self.timeouts.query, |app| async move {
+                app.capabilities().await
+            })
+            .await?;
+        capabilities.max_message_size = self.max_message_size;
+        Ok(capabilities)
+    }
+
+    /// create a LedgerService of the path m/44'/coin_type'/account'/0/index which shares
+    /// the connected device
+    pub async fn with_account_index(&self, account: u32, index: u32) -> Result<Self, Error> {
+        let chain_path = format!("m/44'/{}'/{}'/0/{}", self.coin_type, account, index);
+        self.with_path(&chain_path).await
+    }
+}
+
+fn parse_path(chain_path: &str) -> Result<BIP44Path, Error> {
+    BIP44Path::from_string(chain_path)
+        .map_err(|_e| Error::InputError("input invalid hd path".to_string()))
 }
 
 #[async_trait]
 impl KeyService for LedgerServiceHID {
-    fn public_key(&self) -> Result<PublicKey, Error> {
+    async fn public_key(&self) -> Result<PublicKey, Error> {
         let public_key_raw = self.pubkey_address.public_key;
         let pubkey = InnerPublicKey::from_slice(&public_key_raw)
             .map_err(|e| Error::InputError(format!("invalid public key: {:?}", e)))?;
         Ok(pubkey.into())
     }
 
-    fn address(&self) -> Result<stdtx::Address, Error> {
+    fn hd_path(&self) -> Option<String> {
+        Some(self.hd_path.clone())
+    }
+
+    async fn address(&self) -> Result<stdtx::Address, Error> {
         let address_str = self.pubkey_address.address.clone();
         let (_, address) = stdtx::Address::from_bech32(address_str)
             .map_err(|e| Error::InputError(format!("invalid address: {:?}", e)))?;
@@ -117,12 +718,26 @@ impl KeyService for LedgerServiceHID {
     }
 
     async fn sign(&self, msg: &[u8]) -> Result<String, Error> {
+        if msg.len() > self.max_message_size {
+            return Err(Error::LedgerError(format!(
+                "message of {} bytes exceeds the max message size {} of the app",
+                msg.len(),
+                self.max_message_size
+            )));
+        }
+        let hd_path = &self.hd_path;
         let raw = self
-            .app
-            .sign(&self.path, msg)
-            .await
-            .map_err(|e| Error::LedgerError(e.to_string()))?;
+            .with_retry(self.timeouts.confirmation, |app| async move {
+                app.sign(hd_path, msg).await
+            })
+            .await?;
         let signature_str = base64::encode(&raw);
         Ok(signature_str)
     }
+
+    async fn sign_digest(&self, _sha256_digest: [u8; 32]) -> Result<String, Error> {
+        Err(Error::LedgerError(
+            "ledger device only signs the full message".to_string(),
+        ))
+    }
 }
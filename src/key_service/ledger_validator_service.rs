@@ -0,0 +1,334 @@
+//! Ed25519 consensus signer backed by the Ledger tendermint validator app
+use crate::error::Error;
+use crate::key_service::ledger_service::TransportHid;
+use crate::key_service::ConsensusKeyService;
+
+use async_trait::async_trait;
+use ledger_crypto::APDUTransport;
+use ledger_transport::{APDUAnswer, APDUCommand};
+use tokio::sync::Mutex;
+
+const CLA: u8 = 0x56;
+const INS_GET_VERSION: u8 = 0x00;
+const INS_PUBLIC_KEY_ED25519: u8 = 0x01;
+const INS_SIGN_ED25519: u8 = 0x02;
+
+const USER_MESSAGE_CHUNK_SIZE: usize = 250;
+const RETCODE_OK: u16 = 0x9000;
+const HARDENED: u32 = 0x8000_0000;
+const MAX_PATH_SIZE: usize = 10;
+
+pub use crate::constant::VALIDATOR_PATH;
+
+/// version of the validator app
+#[derive(Debug, Clone)]
+pub struct ValidatorAppVersion {
+    pub mode: u8,
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+}
+
+/// APDU client of the tendermint validator app
+pub struct ValidatorApp {
+    transport: APDUTransport,
+}
+
+impl ValidatorApp {
+    pub fn new(transport: APDUTransport) -> Self {
+        Self { transport }
+    }
+
+    async fn exchange(&self, command: APDUCommand) -> Result<APDUAnswer, Error> {
+        let answer = self
+            .transport
+            .exchange(&command)
+            .await
+            .map_err(|e| Error::LedgerError(format!("apdu exchange failed: {:?}", e)))?;
+        if answer.retcode != RETCODE_OK {
+            return Err(Error::LedgerError(format!(
+                "validator app error, code: {:#x}",
+                answer.retcode
+            )));
+        }
+        Ok(answer)
+    }
+
+    pub async fn get_version(&self) -> Result<ValidatorAppVersion, Error> {
+        let command = APDUCommand {
+            cla: CLA,
+            ins: INS_GET_VERSION,
+            p1: 0,
+            p2: 0,
+            data: vec![],
+        };
+        let data = self.exchange(command).await?.data;
+        if data.len() < 4 {
+            return Err(Error::LedgerError("invalid version".to_string()));
+        }
+        Ok(ValidatorAppVersion {
+            mode: data[0],
+            major: data[1],
+            minor: data[2],
+            patch: data[3],
+        })
+    }
+
+    /// get the ed25519 public key of the derivation path
+    pub async fn public_key(&self, path: &[u32]) -> Result<[u8; 32], Error> {
+        let command = APDUCommand {
+            cla: CLA,
+            ins: INS_PUBLIC_KEY_ED25519,
+            p1: 0,
+            p2: 0,
+            data: to_bip32array(path)?,
+        };
+        let data = self.exchange(command).await?.data;
+        if data.len() != 32 {
+            return Err(Error::LedgerError("received an invalid PK".to_string()));
+        }
+        let mut public_key = [0u8; 32];
+        public_key.copy_from_slice(&data);
+        Ok(public_key)
+    }
+
+    /// sign the message with the key of the derivation path,
+    /// the first packet carries the path and the message chunks follow
+    pub async fn sign(&self, path: &[u32], message: &[u8]) -> Result<[u8; 64], Error> {
+        if message.is_empty() {
+            return Err(Error::InputError("message cannot be empty".to_string()));
+        }
+        let bip32path = to_bip32array(path)?;
+        let packets: Vec<&[u8]> = std::iter::once(&bip32path[..])
+            .chain(message.chunks(USER_MESSAGE_CHUNK_SIZE))
+            .collect();
+        if packets.len() > 255 {
+            return Err(Error::InputError("message is too big".to_string()));
+        }
+
+        let packet_count = packets.len() as u8;
+        let mut data = vec![];
+        for (packet_idx, chunk) in packets.into_iter().enumerate() {
+            let command = APDUCommand {
+                cla: CLA,
+                ins: INS_SIGN_ED25519,
+                p1: (packet_idx + 1) as u8,
+                p2: packet_count,
+                data: chunk.to_vec(),
+            };
+            data = self.exchange(command).await?.data;
+        }
+
+        // the last answer contains the signature
+        if data.len() != 64 {
+            return Err(Error::LedgerError(
+                "received an invalid signature".to_string(),
+            ));
+        }
+        let mut signature = [0u8; 64];
+        signature.copy_from_slice(&data);
+        Ok(signature)
+    }
+}
+
+/// u8 length followed by the little endian indexes
+fn to_bip32array(path: &[u32]) -> Result<Vec<u8>, Error> {
+    if path.len() > MAX_PATH_SIZE {
+        return Err(Error::InputError("invalid derivation path".to_string()));
+    }
+    let mut answer = vec![path.len() as u8];
+    for index in path {
+        answer.extend_from_slice(&index.to_le_bytes());
+    }
+    Ok(answer)
+}
+
+/// parse `m/44'/118'/0'/0'/0'` into the indexes
+fn parse_path(hd_path: &str) -> Result<Vec<u32>, Error> {
+    let invalid = || Error::InputError("input invalid hd path".to_string());
+    let mut parts = hd_path.split('/');
+    if parts.next() != Some("m") {
+        return Err(invalid());
+    }
+    parts
+        .map(|part| {
+            let (index, hardened) = match part.strip_suffix('\'') {
+                Some(index) => (index, HARDENED),
+                None => (part, 0),
+            };
+            let index: u32 = index.parse().map_err(|_e| invalid())?;
+            if index >= HARDENED {
+                return Err(invalid());
+            }
+            Ok(index | hardened)
+        })
+        .collect()
+}
+
+/// ConsensusKeyService of the validator app, the device is shared by the clones
+/// and the operations on it run one by one
+#[derive(Clone)]
+pub struct LedgerValidatorService {
+    app: std::sync::Arc<Mutex<ValidatorApp>>,
+    path: Vec<u32>,
+    public_key: [u8; 32],
+}
+
+impl std::fmt::Debug for LedgerValidatorService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LedgerValidatorService")
+            .field("path", &self.path)
+            .field("public_key", &base64::encode(&self.public_key))
+            .finish()
+    }
+}
+
+impl LedgerValidatorService {
+    /// connect the validator app over hid, `hd_path` defaults to `VALIDATOR_PATH`
+    pub async fn new(hd_path: Option<&str>) -> Result<Self, Error> {
+        let transport = APDUTransport {
+            transport_wrapper: Box::new(TransportHid::open().await?),
+        };
+        Self::new_with_transport(transport, hd_path).await
+    }
+
+    /// create a new LedgerValidatorService over a custom transport
+    pub async fn new_with_transport(
+        transport: APDUTransport,
+        hd_path: Option<&str>,
+    ) -> Result<Self, Error> {
+        let path = parse_path(hd_path.unwrap_or(VALIDATOR_PATH))?;
+        let app = ValidatorApp::new(transport);
+        let version = app.get_version().await?;
+        log::debug!("validator app version: {:?}", version);
+        let public_key = app.public_key(&path).await?;
+        Ok(Self {
+            app: std::sync::Arc::new(Mutex::new(app)),
+            path,
+            public_key,
+        })
+    }
+
+    /// create a LedgerValidatorService of another hd path which shares the connected device,
+    /// e.g. `m/44'/118'/0'/0'/1'` for a second validator on the same Ledger
+    pub async fn with_path(&self, hd_path: &str) -> Result<Self, Error> {
+        let path = parse_path(hd_path)?;
+        let public_key = self.app.lock().await.public_key(&path).await?;
+        Ok(Self {
+            app: self.app.clone(),
+            path,
+            public_key,
+        })
+    }
+}
+
+#[async_trait]
+impl ConsensusKeyService for LedgerValidatorService {
+    async fn public_key(&self) -> Result<[u8; 32], Error> {
+        Ok(self.public_key)
+    }
+
+    async fn sign(&self, msg: &[u8]) -> Result<String, Error> {
+        let app = self.app.lock().await;
+        let signature = app.sign(&self.path, msg).await?;
+        Ok(base64::encode(&signature[..]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ledger_transport::errors::TransportError;
+    use ledger_transport::Exchange;
+
+    /// validator app whose keys and signatures are filled with the last index of the path
+    #[derive(Default)]
+    struct FakeValidatorApp {
+        sign_path: std::sync::Mutex<Vec<u8>>,
+    }
+
+    fn key_byte(bip32path: &[u8]) -> u8 {
+        bip32path[bip32path.len() - 4]
+    }
+
+    #[async_trait]
+    impl Exchange for FakeValidatorApp {
+        async fn exchange(&self, command: &APDUCommand) -> Result<APDUAnswer, TransportError> {
+            let mut answer = match (command.ins, command.p1) {
+                (INS_GET_VERSION, _) => vec![0xff, 0, 4, 0],
+                (INS_PUBLIC_KEY_ED25519, _) => vec![key_byte(&command.data); 32],
+                (INS_SIGN_ED25519, 1) => {
+                    *self.sign_path.lock().unwrap() = command.data.clone();
+                    vec![]
+                }
+                (INS_SIGN_ED25519, _) => vec![key_byte(&self.sign_path.lock().unwrap()); 64],
+                _ => return Ok(APDUAnswer::from_answer(vec![0x6d, 0x00])),
+            };
+            answer.extend_from_slice(&[0x90, 0x00]);
+            Ok(APDUAnswer::from_answer(answer))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validator_paths() {
+        let transport = APDUTransport {
+            transport_wrapper: Box::new(FakeValidatorApp::default()),
+        };
+        let service = LedgerValidatorService::new_with_transport(transport, None)
+            .await
+            .unwrap();
+        assert_eq!(service.public_key().await.unwrap(), [0; 32]);
+        assert_eq!(
+            service.sign(b"vote").await.unwrap(),
+            base64::encode(&[0u8; 64][..])
+        );
+
+        let second = service.with_path("m/44'/118'/0'/0'/1'").await.unwrap();
+        assert_eq!(second.public_key().await.unwrap(), [1; 32]);
+        assert_eq!(
+            second.sign(b"vote").await.unwrap(),
+            base64::encode(&[1u8; 64][..])
+        );
+        assert!(second.sign(b"").await.is_err());
+        assert!(service.with_path("m/44'/118'/x").await.is_err());
+    }
+
+    #[test]
+    fn test_derivation_path() {
+        assert_eq!(to_bip32array(&[1]).unwrap(), b"\x01\x01\x00\x00\x00");
+        assert_eq!(
+            to_bip32array(&[1, 2]).unwrap(),
+            b"\x02\x01\x00\x00\x00\x02\x00\x00\x00"
+        );
+        assert_eq!(
+            to_bip32array(&[1, 2, 12345]).unwrap(),
+            b"\x03\x01\x00\x00\x00\x02\x00\x00\x00\x39\x30\x00\x00"
+        );
+        assert_eq!(
+            to_bip32array(&parse_path("m/44/118/0/0/0").unwrap()).unwrap(),
+            b"\x05\x2c\x00\x00\x00\x76\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00"
+        );
+        assert_eq!(
+            to_bip32array(&parse_path(VALIDATOR_PATH).unwrap()).unwrap(),
+            b"\x05\x2c\x00\x00\x80\x76\x00\x00\x80\x00\x00\x00\x80\x00\x00\x00\x80\x00\x00\x00\x80"
+        );
+        assert!(to_bip32array(&[0; MAX_PATH_SIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("m/44'/118'/0'/0/7").unwrap(),
+            vec![44 | HARDENED, 118 | HARDENED, HARDENED, 0, 7]
+        );
+        for invalid in &[
+            "44'/118'/0'/0'/0'",
+            "m/44'/118'/x'/0'/0'",
+            "m/44'/118'/0''/0'/0'",
+            "m/44'/118'/2147483648'/0'/0'",
+            "m/44'/118'/0'/-1/0",
+        ] {
+            assert!(parse_path(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Deterministic KeyService for unit tests
+
+use crate::error::Error;
+use crate::key_service::private_key_service::PrivateKeyService;
+use crate::key_service::KeyService;
+use crate::types::key::{PrivateKey, PublicKey};
+
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use stdtx::Address;
+
+/// base64 encoded private key of the mock, its address is cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf
+pub const MOCK_PRIVATE_KEY: &str = "1Jp5fbY7YcFI0XZ+YW/xXD3ZyDtjy6YcIY6hcvI4Yio=";
+
+#[derive(Default)]
+struct MockState {
+    /// number of the following sign calls which fail
+    sign_failures: usize,
+    /// `public_key` and `address` fail while it is set
+    key_failure: bool,
+    /// messages passed to `sign` and digests passed to `sign_digest`
+    signed_messages: Vec<Vec<u8>>,
+    /// number of `public_key` calls
+    public_key_calls: usize,
+}
+
+/// KeyService with a fixed key, programmable failures and call recording.
+/// Clones share the same state.
+#[derive(Clone)]
+pub struct MockKeyService {
+    inner: PrivateKeyService,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl Default for MockKeyService {
+    fn default() -> Self {
+        let raw = base64::decode(MOCK_PRIVATE_KEY).expect("valid mock private key");
+        let private_key = PrivateKey::from_slice(&raw).expect("valid mock private key");
+        Self::new(private_key)
+    }
+}
+
+impl MockKeyService {
+    /// create a new MockKeyService with the given key
+    pub fn new(private_key: PrivateKey) -> Self {
+        Self {
+            inner: PrivateKeyService::new(private_key),
+            state: Arc::new(Mutex::new(MockState::default())),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().expect("mock state lock poisoned")
+    }
+
+    /// make the next `times` calls of `sign` or `sign_digest` fail
+    pub fn fail_next_signs(&self, times: usize) -> &Self {
+        self.state().sign_failures = times;
+        self
+    }
+
+    /// make `public_key` and `address` fail or succeed
+    pub fn set_key_failure(&self, fail: bool) -> &Self {
+        self.state().key_failure = fail;
+        self
+    }
+
+    /// messages (or digests) passed to `sign` and `sign_digest`, including the failed calls
+    pub fn signed_messages(&self) -> Vec<Vec<u8>> {
+        self.state().signed_messages.clone()
+    }
+
+    /// number of `sign` and `sign_digest` calls
+    pub fn sign_count(&self) -> usize {
+        self.state().signed_messages.len()
+    }
+
+    /// number of `public_key` calls, including the failed calls
+    pub fn public_key_count(&self) -> usize {
+        self.state().public_key_calls
+    }
+
+    fn record_sign(&self, msg: &[u8]) -> Result<(), Error> {
+        let mut state = self.state();
+        state.signed_messages.push(msg.to_vec());
+        if state.sign_failures > 0 {
+            state.sign_failures -= 1;
+            return Err(Error::InputError("mock sign failure".to_string()));
+        }
+        Ok(())
+    }
+
+    fn check_key_failure(&self) -> Result<(), Error> {
+        if self.state().key_failure {
+            return Err(Error::InputError("mock key failure".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl KeyService for MockKeyService {
+    async fn public_key(&self) -> Result<PublicKey, Error> {
+        self.state().public_key_calls += 1;
+        self.check_key_failure()?;
+        self.inner.public_key().await
+    }
+
+    async fn address(&self) -> Result<Address, Error> {
+        self.check_key_failure()?;
+        self.inner.address().await
+    }
+
+    async fn sign(&self, msg: &[u8]) -> Result<String, Error> {
+        self.record_sign(msg)?;
+        KeyService::sign(&self.inner, msg).await
+    }
+
+    async fn sign_digest(&self, sha256_digest: [u8; 32]) -> Result<String, Error> {
+        self.record_sign(&sha256_digest)?;
+        KeyService::sign_digest(&self.inner, sha256_digest).await
+    }
+}
@@ -1,19 +1,160 @@
+pub mod ed25519_service;
+pub mod eth_secp_service;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod hardware_transport;
+#[cfg(feature = "keyring")]
+pub mod keyring_service;
+pub mod keystore_service;
+#[cfg(feature = "ledger-ble")]
+pub mod ledger_ble;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ledger_cosmos;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod ledger_service;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ledger_validator_service;
+#[cfg(any(test, feature = "test-util"))]
+pub mod mock;
+pub mod multisig_service;
+#[cfg(feature = "pkcs11")]
+pub mod pkcs11_service;
 pub mod private_key_service;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod vault_service;
+pub mod watch_only_service;
 
 use crate::error::Error;
 use crate::types::key::PublicKey;
 use async_trait::async_trait;
 use stdtx::Address;
 
+/// type url of the protobuf secp256k1 public key
+pub const SECP256K1_PUBKEY_TYPE_URL: &str = "/cosmos.crypto.secp256k1.PubKey";
+/// type url of the ethermint secp256k1 public key
+pub const ETH_SECP256K1_PUBKEY_TYPE_URL: &str = "/ethermint.crypto.v1.ethsecp256k1.PubKey";
+/// type url of the protobuf threshold multisig public key
+pub const MULTISIG_PUBKEY_TYPE_URL: &str = "/cosmos.crypto.multisig.LegacyAminoPubKey";
+
+/// the kind of key behind a KeyService
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigningAlgorithm {
+    /// cosmos secp256k1 key, sha256 digest and RIPEMD160(SHA256(pubkey)) address
+    Secp256k1,
+    /// ethermint secp256k1 key, keccak256 digest and address
+    EthSecp256k1,
+    /// threshold multisig of secp256k1 keys
+    Multisig,
+}
+
+impl SigningAlgorithm {
+    /// type url of the public key when it is packed into an `Any`
+    pub fn pubkey_type_url(&self) -> &'static str {
+        match self {
+            SigningAlgorithm::Secp256k1 => SECP256K1_PUBKEY_TYPE_URL,
+            SigningAlgorithm::EthSecp256k1 => ETH_SECP256K1_PUBKEY_TYPE_URL,
+            SigningAlgorithm::Multisig => MULTISIG_PUBKEY_TYPE_URL,
+        }
+    }
+}
+
+/// a signer of transactions, all the key access is async so that remote signers
+/// (ledger, HSM, KMS...) can fetch the key on demand
 #[async_trait]
 pub trait KeyService {
     /// return the public key
-    fn public_key(&self) -> Result<PublicKey, Error>;
+    async fn public_key(&self) -> Result<PublicKey, Error>;
+
+    /// the kind of key used to sign
+    fn algorithm(&self) -> SigningAlgorithm {
+        SigningAlgorithm::Secp256k1
+    }
+
+    /// the hd path the key is derived from, if it is known
+    fn hd_path(&self) -> Option<String> {
+        None
+    }
 
     /// Address returns a Bitcoin style account addresses: RIPEMD160(SHA256(pubkey))
-    fn address(&self) -> Result<Address, Error>;
+    async fn address(&self) -> Result<Address, Error>;
 
     /// sign a message, return base64 encoded string
     async fn sign(&self, msg: &[u8]) -> Result<String, Error>;
+
+    /// sign the sha256 digest of a message which is already hashed by the caller,
+    /// return base64 encoded string, unsupported unless the key service implements it
+    async fn sign_digest(&self, _sha256_digest: [u8; 32]) -> Result<String, Error> {
+        Err(Error::Unsupported("signing a sha256 digest".to_string()))
+    }
+}
+
+/// a signer of consensus messages (votes, proposals) with an ed25519 key
+#[async_trait]
+pub trait ConsensusKeyService {
+    /// return the ed25519 public key
+    async fn public_key(&self) -> Result<[u8; 32], Error>;
+
+    /// sign the canonical sign bytes of a consensus message, return base64 encoded string
+    async fn sign(&self, msg: &[u8]) -> Result<String, Error>;
+}
+
+/// a shared key service, e.g. `SignerKeyService`, signs as the key service it points to
+#[async_trait]
+impl<T: KeyService + Send + Sync + ?Sized> KeyService for std::sync::Arc<T> {
+    async fn public_key(&self) -> Result<PublicKey, Error> {
+        self.as_ref().public_key().await
+    }
+
+    fn algorithm(&self) -> SigningAlgorithm {
+        self.as_ref().algorithm()
+    }
+
+    fn hd_path(&self) -> Option<String> {
+        self.as_ref().hd_path()
+    }
+
+    async fn address(&self) -> Result<Address, Error> {
+        self.as_ref().address().await
+    }
+
+    async fn sign(&self, msg: &[u8]) -> Result<String, Error> {
+        self.as_ref().sign(msg).await
+    }
+
+    async fn sign_digest(&self, sha256_digest: [u8; 32]) -> Result<String, Error> {
+        self.as_ref().sign_digest(sha256_digest).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// a key service which only signs full messages
+    struct MessageOnly(PublicKey);
+
+    #[async_trait]
+    impl KeyService for MessageOnly {
+        async fn public_key(&self) -> Result<PublicKey, Error> {
+            Ok(self.0.clone())
+        }
+
+        async fn address(&self) -> Result<Address, Error> {
+            self.0.address()
+        }
+
+        async fn sign(&self, _msg: &[u8]) -> Result<String, Error> {
+            Ok(String::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sign_digest_unsupported() {
+        let public_key =
+            PublicKey::from_base64_str("AntL+UxMyJ9NZ9DGLp2v7a3dlSxiNXMaItyOXSRw8iYi").unwrap();
+        let key_service = MessageOnly(public_key);
+        assert!(matches!(
+            key_service.sign_digest([0; 32]).await,
+            Err(Error::Unsupported(_))
+        ));
+    }
 }
@@ -0,0 +1,256 @@
+use crate::error::Error;
+use crate::key_service::{KeyService, SigningAlgorithm};
+use crate::proto::cosmos::crypto::multisig::v1beta1::{CompactBitArray, MultiSignature};
+use crate::proto::cosmos::crypto::multisig::LegacyAminoPubKey;
+use crate::proto::cosmos::tx::v1beta1::{mode_info, ModeInfo, SignerInfo};
+use crate::types::key::PublicKey;
+
+use async_trait::async_trait;
+use bitcoin_hashes::{sha256, Hash};
+use std::sync::Arc;
+use stdtx::address::{Address, ADDRESS_SIZE};
+
+/// amino prefix of `tendermint/PubKeyMultisigThreshold`
+const MULTISIG_AMINO_PREFIX: [u8; 4] = [0x22, 0xc1, 0xf7, 0xe2];
+/// amino prefix of `tendermint/PubKeySecp256k1`
+const SECP256K1_AMINO_PREFIX: [u8; 4] = [0xeb, 0x5a, 0xe9, 0x87];
+
+/// a member KeyService of the multisig account
+pub type MemberKeyService = Arc<dyn KeyService + Send + Sync>;
+
+/// KeyService of a `LegacyAminoPubKey` threshold multisig account.
+///
+/// It holds the ordered member public keys, collects the partial signatures from the
+/// registered member KeyServices and aggregates them into a `MultiSignature`.
+#[derive(Clone)]
+pub struct MultisigKeyService {
+    threshold: u32,
+    public_keys: Vec<PublicKey>,
+    /// registered signers with their index in `public_keys`, sorted by index
+    signers: Vec<(usize, MemberKeyService)>,
+}
+
+fn encode<T: prost::Message>(msg: &T) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![];
+    prost::Message::encode(msg, &mut buf)?;
+    Ok(buf)
+}
+
+fn encode_uvarint(mut value: u64, buf: &mut Vec<u8>) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+/// bit array with the bits of the signed member indexes set
+pub fn compact_bit_array(size: usize, indexes: &[usize]) -> CompactBitArray {
+    let mut elems = vec![0u8; (size + 7) / 8];
+    for index in indexes {
+        elems[index / 8] |= 1 << (7 - index % 8);
+    }
+    CompactBitArray {
+        extra_bits_stored: (size % 8) as u32,
+        elems,
+    }
+}
+
+/// encode the base64 partial signatures into a base64 protobuf `MultiSignature`
+fn aggregate(partial_signatures: Vec<String>) -> Result<String, Error> {
+    let mut signatures = vec![];
+    for signature in partial_signatures {
+        let raw = base64::decode(signature).map_err(|e| {
+            Error::SerializeError(format!("invalid base64 signature, decode error: {:?}", e))
+        })?;
+        signatures.push(raw);
+    }
+    let multi_signature = encode(&MultiSignature { signatures })?;
+    Ok(base64::encode(multi_signature))
+}
+
+impl MultisigKeyService {
+    /// create a new threshold multisig key, the order of public keys matters
+    pub fn new(threshold: u32, public_keys: Vec<PublicKey>) -> Result<Self, Error> {
+        if threshold == 0 || threshold as usize > public_keys.len() {
+            return Err(Error::InputError(format!(
+                "invalid threshold {} for {} public keys",
+                threshold,
+                public_keys.len()
+            )));
+        }
+        Ok(Self {
+            threshold,
+            public_keys,
+            signers: vec![],
+        })
+    }
+
+    /// register a member KeyService which will provide a partial signature
+    pub async fn add_signer(&mut self, key_service: MemberKeyService) -> Result<&mut Self, Error> {
+        let public_key = key_service.public_key().await?;
+        let index = self
+            .public_keys
+            .iter()
+            .position(|pk| pk == &public_key)
+            .ok_or_else(|| Error::InputError("signer is not a multisig member".to_string()))?;
+        if self.signers.iter().any(|(i, _)| *i == index) {
+            return Err(Error::InputError("signer already added".to_string()));
+        }
+        self.signers.push((index, key_service));
+        self.signers.sort_by_key(|(i, _)| *i);
+        Ok(self)
+    }
+
+    /// the multisig threshold
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    /// the ordered member public keys
+    pub fn public_keys(&self) -> &[PublicKey] {
+        &self.public_keys
+    }
+
+    fn signer_indexes(&self) -> Vec<usize> {
+        self.signers.iter().map(|(index, _)| *index).collect()
+    }
+
+    /// protobuf `LegacyAminoPubKey` of the multisig account
+    pub fn legacy_amino_pubkey(&self) -> Result<LegacyAminoPubKey, Error> {
+        let mut public_keys = vec![];
+        for pk in &self.public_keys {
+            public_keys.push(pk.to_any()?);
+        }
+        Ok(LegacyAminoPubKey {
+            threshold: self.threshold,
+            public_keys,
+        })
+    }
+
+    /// the multisig public key packed into an `Any`, used in `SignerInfo`
+    pub fn pk_any(&self) -> Result<prost_types::Any, Error> {
+        Ok(prost_types::Any {
+            type_url: SigningAlgorithm::Multisig.pubkey_type_url().to_string(),
+            value: encode(&self.legacy_amino_pubkey()?)?,
+        })
+    }
+
+    /// amino binary encoding of the multisig public key, the address is derived from it
+    pub fn amino_bytes(&self) -> Vec<u8> {
+        let mut buf = MULTISIG_AMINO_PREFIX.to_vec();
+        // field 1: threshold
+        buf.push(0x08);
+        encode_uvarint(self.threshold as u64, &mut buf);
+        // field 2: public keys
+        for pk in &self.public_keys {
+            let raw = pk.as_ref().serialize();
+            buf.push(0x12);
+            encode_uvarint(
+                (SECP256K1_AMINO_PREFIX.len() + 1 + raw.len()) as u64,
+                &mut buf,
+            );
+            buf.extend_from_slice(&SECP256K1_AMINO_PREFIX);
+            buf.push(raw.len() as u8);
+            buf.extend_from_slice(&raw);
+        }
+        buf
+    }
+
+    /// `ModeInfo::Multi` describing which members signed with `sign_mode`
+    pub fn mode_info(&self, sign_mode: i32) -> ModeInfo {
+        let single = ModeInfo {
+            sum: Some(mode_info::Sum::Single(mode_info::Single {
+                mode: sign_mode,
+            })),
+        };
+        let multi = mode_info::Multi {
+            bitarray: Some(compact_bit_array(
+                self.public_keys.len(),
+                &self.signer_indexes(),
+            )),
+            mode_infos: vec![single; self.signers.len()],
+        };
+        ModeInfo {
+            sum: Some(mode_info::Sum::Multi(multi)),
+        }
+    }
+
+    fn check_threshold(&self) -> Result<(), Error> {
+        if self.signers.len() < self.threshold as usize {
+            return Err(Error::InputError(format!(
+                "{} signers registered, threshold is {}",
+                self.signers.len(),
+                self.threshold
+            )));
+        }
+        Ok(())
+    }
+
+    /// `SignerInfo` of the multisig account, members must sign with `sign_mode`
+    /// (the cosmos-sdk only accepts SIGN_MODE_LEGACY_AMINO_JSON for multisig members)
+    pub fn signer_info(&self, sequence: u64, sign_mode: i32) -> Result<SignerInfo, Error> {
+        Ok(SignerInfo {
+            public_key: Some(self.pk_any()?),
+            mode_info: Some(self.mode_info(sign_mode)),
+            sequence,
+        })
+    }
+}
+
+#[async_trait]
+impl KeyService for MultisigKeyService {
+    async fn public_key(&self) -> Result<PublicKey, Error> {
+        Err(Error::InputError(
+            "multisig key has no single secp256k1 public key".to_string(),
+        ))
+    }
+
+    fn algorithm(&self) -> SigningAlgorithm {
+        SigningAlgorithm::Multisig
+    }
+
+    /// Address returns the first 20 bytes of SHA256(amino encoded multisig public key)
+    async fn address(&self) -> Result<Address, Error> {
+        let hash = sha256::Hash::hash(&self.amino_bytes());
+        let mut raw = [0; ADDRESS_SIZE];
+        raw.copy_from_slice(&hash[..ADDRESS_SIZE]);
+        Ok(raw.into())
+    }
+
+    /// collect the partial signatures of the registered members,
+    /// return the base64 encoded protobuf `MultiSignature`
+    async fn sign(&self, msg: &[u8]) -> Result<String, Error> {
+        self.check_threshold()?;
+        let mut signatures = vec![];
+        for (_, signer) in &self.signers {
+            signatures.push(signer.sign(msg).await?);
+        }
+        aggregate(signatures)
+    }
+
+    async fn sign_digest(&self, sha256_digest: [u8; 32]) -> Result<String, Error> {
+        self.check_threshold()?;
+        let mut signatures = vec![];
+        for (_, signer) in &self.signers {
+            signatures.push(signer.sign_digest(sha256_digest).await?);
+        }
+        aggregate(signatures)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compact_bit_array() {
+        let bit_array = compact_bit_array(3, &[0, 2]);
+        assert_eq!(bit_array.extra_bits_stored, 3);
+        assert_eq!(bit_array.elems, vec![0b1010_0000]);
+
+        let bit_array = compact_bit_array(9, &[1, 8]);
+        assert_eq!(bit_array.extra_bits_stored, 1);
+        assert_eq!(bit_array.elems, vec![0b0100_0000, 0b1000_0000]);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::error::Error;
+use crate::key_service::KeyService;
+use crate::types::key::PublicKey;
+
+use async_trait::async_trait;
+use bitcoin_hashes::{sha256, Hash};
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use secp256k1::{PublicKey as InnerPublicKey, Signature};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use stdtx::Address;
+
+/// size of an uncompressed secp256k1 point
+const UNCOMPRESSED_POINT_SIZE: usize = 65;
+
+/// configuration of the PKCS#11 module and key
+#[derive(Debug, Clone)]
+pub struct Pkcs11Config {
+    /// path of the PKCS#11 module library, e.g. `/usr/lib/pkcs11/yubihsm_pkcs11.so`
+    pub module_path: PathBuf,
+    /// id of the slot which holds the token
+    pub slot_id: u64,
+    /// user pin of the token
+    pub pin: String,
+    /// label of the secp256k1 key pair
+    pub key_label: String,
+}
+
+/// KeyService which signs via a PKCS#11 module, the private key never leaves the HSM
+#[derive(Clone)]
+pub struct Pkcs11Service {
+    session: Arc<Mutex<Session>>,
+    private_key: ObjectHandle,
+    public_key: PublicKey,
+}
+
+impl std::fmt::Debug for Pkcs11Service {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pkcs11Service")
+            .field("public_key", &self.public_key.to_string())
+            .finish()
+    }
+}
+
+fn hsm_error<E: std::fmt::Display>(context: &str) -> impl Fn(E) -> Error + '_ {
+    move |e| Error::HsmError(format!("{}: {}", context, e))
+}
+
+impl Pkcs11Service {
+    /// open a session on the configured slot, login and look up the key pair by label
+    pub fn new(config: &Pkcs11Config) -> Result<Self, Error> {
+        let pkcs11 = Pkcs11::new(&config.module_path).map_err(hsm_error("load module"))?;
+        pkcs11
+            .initialize(CInitializeArgs::OsThreads)
+            .map_err(hsm_error("initialize module"))?;
+        let slot = pkcs11
+            .get_slots_with_token()
+            .map_err(hsm_error("list slots"))?
+            .into_iter()
+            .find(|slot| slot.id() == config.slot_id)
+            .ok_or_else(|| Error::HsmError(format!("slot {} not found", config.slot_id)))?;
+        let session = pkcs11
+            .open_session_no_callback(slot, true)
+            .map_err(hsm_error("open session"))?;
+        session
+            .login(UserType::User, Some(&config.pin))
+            .map_err(hsm_error("login"))?;
+
+        let private_key = find_key(&session, ObjectClass::PRIVATE_KEY, &config.key_label)?;
+        let public_key_handle = find_key(&session, ObjectClass::PUBLIC_KEY, &config.key_label)?;
+        let attributes = session
+            .get_attributes(public_key_handle, &[AttributeType::EcPoint])
+            .map_err(hsm_error("get public key"))?;
+        let ec_point = attributes
+            .into_iter()
+            .find_map(|attribute| match attribute {
+                Attribute::EcPoint(point) => Some(point),
+                _ => None,
+            })
+            .ok_or_else(|| Error::HsmError("public key has no EC point".to_string()))?;
+        let public_key = parse_ec_point(&ec_point)?;
+
+        Ok(Self {
+            session: Arc::new(Mutex::new(session)),
+            private_key,
+            public_key,
+        })
+    }
+}
+
+fn find_key(session: &Session, class: ObjectClass, label: &str) -> Result<ObjectHandle, Error> {
+    let template = [
+        Attribute::Class(class),
+        Attribute::Label(label.as_bytes().to_vec()),
+    ];
+    session
+        .find_objects(&template)
+        .map_err(hsm_error("find key"))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::HsmError(format!("key {} not found", label)))
+}
+
+/// CKA_EC_POINT is a DER encoded OCTET STRING wrapping the uncompressed point
+fn parse_ec_point(ec_point: &[u8]) -> Result<PublicKey, Error> {
+    if ec_point.len() < UNCOMPRESSED_POINT_SIZE {
+        return Err(Error::HsmError("invalid EC point length".to_string()));
+    }
+    let raw = &ec_point[ec_point.len() - UNCOMPRESSED_POINT_SIZE..];
+    let inner = InnerPublicKey::from_slice(raw)
+        .map_err(|e| Error::HsmError(format!("invalid public key: {:?}", e)))?;
+    Ok(inner.into())
+}
+
+#[async_trait]
+impl KeyService for Pkcs11Service {
+    async fn public_key(&self) -> Result<PublicKey, Error> {
+        Ok(self.public_key.clone())
+    }
+
+    async fn address(&self) -> Result<Address, Error> {
+        self.public_key.address()
+    }
+
+    /// sign the sha256 digest of msg with CKM_ECDSA, the signature is normalized to low-s
+    async fn sign(&self, msg: &[u8]) -> Result<String, Error> {
+        let digest = sha256::Hash::hash(msg);
+        self.sign_digest(digest.into_inner()).await
+    }
+
+    async fn sign_digest(&self, sha256_digest: [u8; 32]) -> Result<String, Error> {
+        let raw = {
+            let session = self
+                .session
+                .lock()
+                .map_err(|_e| Error::HsmError("session lock poisoned".to_string()))?;
+            session
+                .sign(&Mechanism::Ecdsa, self.private_key, &sha256_digest)
+                .map_err(hsm_error("sign"))?
+        };
+        let mut signature = Signature::from_compact(&raw)
+            .map_err(|e| Error::HsmError(format!("invalid signature: {:?}", e)))?;
+        signature.normalize_s();
+        Ok(base64::encode(&signature.serialize_compact()[..]))
+    }
+}
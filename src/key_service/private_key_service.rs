@@ -1,30 +1,112 @@
+use crate::constant::FUNDRAISER_PATH;
 use crate::error::Error;
-use crate::hd_wallet::mnemonic::Mnemonic;
-use crate::types::key::{PrivateKey, PublicKey};
+use crate::hd_wallet::mnemonic::{ExtendedKey, Mnemonic};
+use crate::types::address::ChainConfig;
+use crate::types::key::{PrivateKey, PublicKey, SECP256K1};
 
 use crate::key_service::KeyService;
 use async_trait::async_trait;
-use bitcoin_hashes::{ripemd160, sha256};
+use bitcoin_hashes::sha256;
 use bitcoin_hashes::{Hash, HashEngine};
 use secp256k1::Message;
-use stdtx::address::{Address, ADDRESS_SIZE};
+use stdtx::address::Address;
+use zeroize::Zeroize;
 
 /// stores private key
 #[derive(Clone)]
 pub struct PrivateKeyService {
-    pub private_key: PrivateKey,
+    pub(crate) private_key: PrivateKey,
+    hd_path: Option<String>,
+    /// extended key of the external chain m/44'/394'/account'/0 and its path,
+    /// used to derive sibling addresses
+    chain_key: Option<(String, ExtendedKey)>,
 }
 
 impl PrivateKeyService {
     /// create a new KeyService from Mnemonic
     pub fn new_from_mnemonic(mnemonic: Mnemonic) -> Result<Self, Error> {
         let private_key = mnemonic.private_key()?;
-        Ok(Self { private_key })
+        Ok(Self {
+            private_key,
+            hd_path: Some(FUNDRAISER_PATH.to_string()),
+            chain_key: None,
+        })
+    }
+
+    /// create a new KeyService from Mnemonic with the path m/44'/394'/account'/0/index
+    pub fn new_from_mnemonic_with_index(
+        mnemonic: Mnemonic,
+        account: u32,
+        index: u32,
+    ) -> Result<Self, Error> {
+        Self::new_from_mnemonic_with_config(mnemonic, &ChainConfig::default(), account, index)
+    }
+
+    /// create a new KeyService from Mnemonic with the path
+    /// m/44'/coin_type'/account'/0/index of the chain
+    pub fn new_from_mnemonic_with_config(
+        mnemonic: Mnemonic,
+        chain_config: &ChainConfig,
+        account: u32,
+        index: u32,
+    ) -> Result<Self, Error> {
+        let chain_path = format!("m/44'/{}'/{}'/0", chain_config.coin_type, account);
+        let chain_key = mnemonic.derive_extended_key(&chain_path)?;
+        Self::from_chain_key(chain_path, chain_key, index)
+    }
+
+    fn from_chain_key(
+        chain_path: String,
+        chain_key: ExtendedKey,
+        index: u32,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            private_key: chain_key.child_key(index)?,
+            hd_path: Some(format!("{}/{}", chain_path, index)),
+            chain_key: Some((chain_path, chain_key)),
+        })
+    }
+
+    /// derive the KeyService of another address index in the same account,
+    /// only available when created by `new_from_mnemonic_with_index`
+    pub fn derive_child(&self, index: u32) -> Result<Self, Error> {
+        let (chain_path, chain_key) = self.chain_key.clone().ok_or_else(|| {
+            Error::InputError("key service is not created with an account index".to_string())
+        })?;
+        Self::from_chain_key(chain_path, chain_key, index)
     }
 
     /// create a new KeyService from a PrivateKey
     pub fn new(private_key: PrivateKey) -> Self {
-        Self { private_key }
+        Self {
+            private_key,
+            hd_path: None,
+            chain_key: None,
+        }
+    }
+
+    /// create a new KeyService from a hex encoded private key
+    pub fn from_hex(private_key_hex: &str) -> Result<Self, Error> {
+        let mut raw = hex::decode(private_key_hex.trim())
+            .map_err(|_e| Error::InputError("invalid hex private key".to_string()))?;
+        let private_key = PrivateKey::from_slice(&raw);
+        raw.zeroize();
+        Ok(Self::new(private_key?))
+    }
+
+    /// create a new KeyService from a base64 encoded private key
+    pub fn from_base64(private_key_base64: &str) -> Result<Self, Error> {
+        let mut raw = base64::decode(private_key_base64.trim())
+            .map_err(|_e| Error::InputError("invalid base64 private key".to_string()))?;
+        let private_key = PrivateKey::from_slice(&raw);
+        raw.zeroize();
+        Ok(Self::new(private_key?))
+    }
+
+    /// export the private key in base64 format, the caller is responsible for the secret
+    #[cfg(feature = "key-export")]
+    pub fn export_base64(&self) -> String {
+        base64::encode(&self.private_key.expose_secret()[..])
     }
 
     /// sig msg
@@ -32,9 +114,13 @@ impl PrivateKeyService {
         let mut engine = sha256::Hash::engine();
         engine.input(msg);
         let hash = sha256::Hash::from_engine(engine);
-        let message = Message::from_slice(hash.as_inner())?;
-        let signer = secp256k1::Secp256k1::signing_only();
-        let signature = signer.sign(&message, self.private_key.as_ref());
+        self.sign_digest(hash.into_inner())
+    }
+
+    /// sig the sha256 digest of msg
+    fn sign_digest(&self, digest: [u8; 32]) -> Result<String, Error> {
+        let message = Message::from_slice(&digest)?;
+        let signature = SECP256K1.sign(&message, self.private_key.secret_key());
         let raw = signature.serialize_compact();
         let signature_str = base64::encode(&raw);
         Ok(signature_str)
@@ -43,29 +129,17 @@ impl PrivateKeyService {
 
 #[async_trait]
 impl KeyService for PrivateKeyService {
-    fn public_key(&self) -> Result<PublicKey, Error> {
+    async fn public_key(&self) -> Result<PublicKey, Error> {
         Ok(PublicKey::from(&self.private_key))
     }
 
+    fn hd_path(&self) -> Option<String> {
+        self.hd_path.clone()
+    }
+
     /// Address returns a Bitcoin style account addresses: RIPEMD160(SHA256(pubkey))
-    fn address(&self) -> Result<Address, Error> {
-        let pubkey = PublicKey::from(&self.private_key);
-        let pubkey_bytes = pubkey.as_ref().serialize();
-        let mut engine = sha256::Hash::engine();
-        engine.input(&pubkey_bytes);
-        let sha = sha256::Hash::from_engine(engine);
-        let mut engine = ripemd160::Hash::engine();
-        engine.input(sha.as_inner());
-        let raw = ripemd160::Hash::from_engine(engine);
-        let bits = raw.into_inner();
-        if bits.len() != ADDRESS_SIZE {
-            return Err(Error::InputError(
-                "invalid bits length to generate address".to_string(),
-            ));
-        }
-        let mut raw = [0; ADDRESS_SIZE];
-        raw.copy_from_slice(&bits);
-        Ok(raw.into())
+    async fn address(&self) -> Result<Address, Error> {
+        PublicKey::from(&self.private_key).address()
     }
 
     /// sig msg
@@ -73,6 +147,11 @@ impl KeyService for PrivateKeyService {
         let result = self.sign(msg)?;
         Ok(result)
     }
+
+    async fn sign_digest(&self, sha256_digest: [u8; 32]) -> Result<String, Error> {
+        let result = PrivateKeyService::sign_digest(self, sha256_digest)?;
+        Ok(result)
+    }
 }
 
 #[cfg(test)]
@@ -87,7 +166,7 @@ mod test {
         let private_key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
 
         // test address
-        let address = private_key_service.address().unwrap();
+        let address = private_key_service.address().await.unwrap();
         assert_eq!(
             address.to_bech32(ACCOUNT_ADDRESS_PREFIX),
             "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf"
@@ -96,8 +175,8 @@ mod test {
         // test private key
         let private_raw = base64::decode("1Jp5fbY7YcFI0XZ+YW/xXD3ZyDtjy6YcIY6hcvI4Yio=").unwrap();
         assert_eq!(
-            private_key_service.private_key.as_ref(),
-            PrivateKey::from_slice(&private_raw).unwrap().as_ref()
+            private_key_service.private_key.secret_key(),
+            PrivateKey::from_slice(&private_raw).unwrap().secret_key()
         );
 
         // test public key
@@ -129,7 +208,58 @@ mod test {
         ];
         let s = private_key_service.sign(&sign_msg).unwrap();
         let s_expect = "bpPVZg1frGFAKM54i5Wr9PRcg31wk4vBNruYUuN9O9QvIJs+rFshRqZlhd++qBQYUvMdhHO4g/0UuB7JRaESvA==";
-        println!("{}", s);
+        // a compact 64 bytes signature
+        assert_eq!(base64::decode(&s).unwrap().len(), 64);
         assert_eq!(s, s_expect);
     }
+
+    #[test]
+    fn test_import_private_key() {
+        let from_base64 =
+            PrivateKeyService::from_base64("1Jp5fbY7YcFI0XZ+YW/xXD3ZyDtjy6YcIY6hcvI4Yio=").unwrap();
+        let from_hex = PrivateKeyService::from_hex(
+            "d49a797db63b61c148d1767e616ff15c3dd9c83b63cba61c218ea172f238622a",
+        )
+        .unwrap();
+        assert_eq!(
+            from_base64.private_key.secret_key(),
+            from_hex.private_key.secret_key()
+        );
+        assert!(PrivateKeyService::from_hex("not hex").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_derive_with_index() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let service = PrivateKeyService::new_from_mnemonic_with_index(mnemonic, 0, 0).unwrap();
+        assert_eq!(
+            service
+                .address()
+                .await
+                .unwrap()
+                .to_bech32(ACCOUNT_ADDRESS_PREFIX),
+            "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf"
+        );
+        assert_eq!(service.hd_path(), Some(FUNDRAISER_PATH.to_string()));
+
+        let child = service.derive_child(1).unwrap();
+        assert_eq!(child.hd_path(), Some("m/44'/394'/0'/0/1".to_string()));
+        assert_ne!(
+            child.address().await.unwrap(),
+            service.address().await.unwrap()
+        );
+        let same = child.derive_child(0).unwrap();
+        assert_eq!(
+            same.address().await.unwrap(),
+            service.address().await.unwrap()
+        );
+
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let cosmos_hub = ChainConfig::cosmos_hub();
+        let service =
+            PrivateKeyService::new_from_mnemonic_with_config(mnemonic, &cosmos_hub, 0, 0).unwrap();
+        let child = service.derive_child(1).unwrap();
+        assert_eq!(child.hd_path(), Some("m/44'/118'/0'/0/1".to_string()));
+    }
 }
@@ -0,0 +1,183 @@
+use crate::error::Error;
+use crate::key_service::KeyService;
+use crate::types::key::PublicKey;
+
+use async_trait::async_trait;
+use secp256k1::{PublicKey as InnerPublicKey, Signature};
+use serde_json::json;
+use stdtx::Address;
+
+/// size of an uncompressed secp256k1 point
+const UNCOMPRESSED_POINT_SIZE: usize = 65;
+const SIGNATURE_PREFIX: &str = "vault:v";
+
+/// configuration of the vault transit key
+#[derive(Debug, Clone)]
+pub struct VaultConfig {
+    /// vault address, e.g. `https://127.0.0.1:8200`
+    pub address: String,
+    /// vault token
+    pub token: String,
+    /// vault enterprise namespace
+    pub namespace: Option<String>,
+    /// mount path of the transit engine, e.g. `transit`
+    pub mount: String,
+    /// name of the transit key
+    pub key_name: String,
+}
+
+/// KeyService backed by the vault transit secrets engine, the key never leaves vault.
+///
+/// The transit engine must be able to hold secp256k1 keys (e.g. a transit compatible
+/// plugin mounted at `mount`), the builtin ecdsa key types use other curves.
+#[derive(Clone)]
+pub struct VaultService {
+    api: VaultApi,
+    public_key: PublicKey,
+}
+
+/// http access to the vault api
+#[derive(Clone)]
+struct VaultApi {
+    config: VaultConfig,
+    client: reqwest::Client,
+}
+
+impl std::fmt::Debug for VaultService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultService")
+            .field("address", &self.api.config.address)
+            .field("mount", &self.api.config.mount)
+            .field("key_name", &self.api.config.key_name)
+            .finish()
+    }
+}
+
+impl VaultService {
+    /// create a new VaultService, the public key of the latest key version is fetched from vault
+    pub async fn new(config: VaultConfig) -> Result<Self, Error> {
+        let api = VaultApi {
+            config,
+            client: reqwest::Client::builder().build()?,
+        };
+        let public_key = api.fetch_public_key().await?;
+        Ok(Self { api, public_key })
+    }
+
+    /// renew the vault token, `increment` is the requested ttl in seconds
+    pub async fn renew_token(&self, increment: Option<u64>) -> Result<(), Error> {
+        let body = match increment {
+            Some(seconds) => json!({ "increment": format!("{}s", seconds) }),
+            None => json!({}),
+        };
+        let builder = self
+            .api
+            .request(reqwest::Method::POST, "auth/token/renew-self")
+            .json(&body);
+        self.api.send(builder).await?;
+        Ok(())
+    }
+}
+
+impl VaultApi {
+    fn url(&self, path: &str) -> String {
+        format!("{}/v1/{}", self.config.address.trim_end_matches('/'), path)
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let builder = self
+            .client
+            .request(method, &self.url(path))
+            .header("X-Vault-Token", &self.config.token);
+        match &self.config.namespace {
+            Some(namespace) => builder.header("X-Vault-Namespace", namespace),
+            None => builder,
+        }
+    }
+
+    async fn send(&self, builder: reqwest::RequestBuilder) -> Result<serde_json::Value, Error> {
+        let response = builder.send().await?;
+        let status = response.status();
+        let body = response.json::<serde_json::Value>().await?;
+        if !status.is_success() {
+            return Err(Error::VaultError(format!(
+                "request failed, status: {}, errors: {}",
+                status, body["errors"]
+            )));
+        }
+        Ok(body)
+    }
+
+    async fn fetch_public_key(&self) -> Result<PublicKey, Error> {
+        let path = format!("{}/keys/{}", self.config.mount, self.config.key_name);
+        let body = self.send(self.request(reqwest::Method::GET, &path)).await?;
+        let latest = body["data"]["latest_version"]
+            .as_u64()
+            .ok_or_else(|| Error::VaultError("missing latest_version".to_string()))?;
+        let pem = body["data"]["keys"][latest.to_string()]["public_key"]
+            .as_str()
+            .ok_or_else(|| Error::VaultError("missing public_key".to_string()))?;
+        parse_pem_public_key(pem)
+    }
+
+    /// sign the input with the transit key, `prehashed` means input is a sha256 digest
+    async fn transit_sign(&self, input: &[u8], prehashed: bool) -> Result<String, Error> {
+        let config = &self.config;
+        let path = format!("{}/sign/{}/sha2-256", config.mount, config.key_name);
+        let body = json!({
+            "input": base64::encode(input),
+            "prehashed": prehashed,
+            "marshaling_algorithm": "jws",
+        });
+        let builder = self.request(reqwest::Method::POST, &path).json(&body);
+        let response = self.send(builder).await?;
+        let signature = response["data"]["signature"]
+            .as_str()
+            .ok_or_else(|| Error::VaultError("missing signature".to_string()))?;
+        // signature format: vault:v<version>:<base64url(r || s)>
+        let encoded = signature
+            .strip_prefix(SIGNATURE_PREFIX)
+            .and_then(|rest| rest.splitn(2, ':').nth(1))
+            .ok_or_else(|| Error::VaultError(format!("invalid signature: {}", signature)))?;
+        let raw = base64::decode_config(encoded, base64::URL_SAFE_NO_PAD)
+            .map_err(|_e| Error::VaultError("invalid base64 signature".to_string()))?;
+        let mut signature = Signature::from_compact(&raw)?;
+        signature.normalize_s();
+        Ok(base64::encode(&signature.serialize_compact()[..]))
+    }
+}
+
+/// the public key is a PEM encoded SubjectPublicKeyInfo ending with the uncompressed point
+fn parse_pem_public_key(pem: &str) -> Result<PublicKey, Error> {
+    let body: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    let der = base64::decode(&body)
+        .map_err(|_e| Error::VaultError("invalid PEM public key".to_string()))?;
+    if der.len() < UNCOMPRESSED_POINT_SIZE {
+        return Err(Error::VaultError("invalid public key length".to_string()));
+    }
+    let inner = InnerPublicKey::from_slice(&der[der.len() - UNCOMPRESSED_POINT_SIZE..])?;
+    Ok(inner.into())
+}
+
+#[async_trait]
+impl KeyService for VaultService {
+    async fn public_key(&self) -> Result<PublicKey, Error> {
+        Ok(self.public_key.clone())
+    }
+
+    async fn address(&self) -> Result<Address, Error> {
+        self.public_key.address()
+    }
+
+    /// sign the sha256 digest of msg in vault, the signature is normalized to low-s
+    async fn sign(&self, msg: &[u8]) -> Result<String, Error> {
+        self.api.transit_sign(msg, false).await
+    }
+
+    async fn sign_digest(&self, sha256_digest: [u8; 32]) -> Result<String, Error> {
+        self.api.transit_sign(&sha256_digest, true).await
+    }
+}
@@ -0,0 +1,149 @@
+use crate::error::Error;
+use crate::hd_wallet::mnemonic::MnemonicError;
+use crate::key_service::KeyService;
+use crate::types::key::PublicKey;
+
+use async_trait::async_trait;
+use hdwallet::{ExtendedPubKey, KeyIndex};
+use secp256k1::PublicKey as InnerPublicKey;
+use stdtx::Address;
+
+/// length of a serialized BIP32 extended key
+const EXTENDED_KEY_SIZE: usize = 78;
+const CHAIN_CODE_OFFSET: usize = 13;
+const KEY_OFFSET: usize = 45;
+/// external chain of the account
+const EXTERNAL_CHAIN: u32 = 0;
+
+/// KeyService built from an account extended public key (m/44'/394'/account'),
+/// it derives addresses and public keys but can not sign
+#[derive(Clone)]
+pub struct WatchOnlyKeyService {
+    /// extended public key of the external chain m/44'/394'/account'/0
+    chain_key: ExtendedPubKey,
+    /// address index in the external chain
+    pub index: u32,
+    public_key: PublicKey,
+}
+
+/// parse a base58check encoded BIP32 extended public key (xpub)
+fn parse_xpub(xpub: &str) -> Result<ExtendedPubKey, Error> {
+    let raw = bs58::decode(xpub)
+        .with_check(None)
+        .into_vec()
+        .map_err(|_e| Error::InputError("invalid base58check extended public key".to_string()))?;
+    if raw.len() != EXTENDED_KEY_SIZE {
+        return Err(Error::InputError(
+            "invalid extended public key length".to_string(),
+        ));
+    }
+    let public_key = InnerPublicKey::from_slice(&raw[KEY_OFFSET..])?;
+    Ok(ExtendedPubKey {
+        public_key,
+        chain_code: raw[CHAIN_CODE_OFFSET..KEY_OFFSET].to_vec(),
+    })
+}
+
+fn derive(key: &ExtendedPubKey, index: u32) -> Result<ExtendedPubKey, Error> {
+    let child = key
+        .derive_public_key(KeyIndex::Normal(index))
+        .map_err(MnemonicError::from)?;
+    Ok(child)
+}
+
+impl WatchOnlyKeyService {
+    /// create a WatchOnlyKeyService of the address m/44'/394'/account'/0/index
+    /// from the xpub of the account
+    pub fn from_xpub(xpub: &str, index: u32) -> Result<Self, Error> {
+        let account_key = parse_xpub(xpub)?;
+        let chain_key = derive(&account_key, EXTERNAL_CHAIN)?;
+        Self::from_chain_key(chain_key, index)
+    }
+
+    fn from_chain_key(chain_key: ExtendedPubKey, index: u32) -> Result<Self, Error> {
+        let child = derive(&chain_key, index)?;
+        Ok(Self {
+            chain_key,
+            index,
+            public_key: child.public_key.into(),
+        })
+    }
+
+    /// derive the WatchOnlyKeyService of another address index in the same account
+    pub fn derive_child(&self, index: u32) -> Result<Self, Error> {
+        Self::from_chain_key(self.chain_key.clone(), index)
+    }
+}
+
+#[async_trait]
+impl KeyService for WatchOnlyKeyService {
+    async fn public_key(&self) -> Result<PublicKey, Error> {
+        Ok(self.public_key.clone())
+    }
+
+    async fn address(&self) -> Result<Address, Error> {
+        self.public_key.address()
+    }
+
+    async fn sign(&self, _msg: &[u8]) -> Result<String, Error> {
+        Err(Error::SigningUnavailable)
+    }
+
+    async fn sign_digest(&self, _sha256_digest: [u8; 32]) -> Result<String, Error> {
+        Err(Error::SigningUnavailable)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hd_wallet::mnemonic::Mnemonic;
+    use crate::key_service::private_key_service::PrivateKeyService;
+
+    /// the xpub of m/44'/394'/0' of the test mnemonic
+    const XPUB: &str = "xpub6CZEdVjiGDdXr9KGVGGiTEFcvWA6UqM5pywpV3re5zvmH5zMKhLA2fY59tqGpqzDJUfwE17EjJ7TKc4FRTXrh76r55CvgSzMxia2EsGMahn";
+
+    #[tokio::test]
+    async fn test_watch_only() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let key_service = WatchOnlyKeyService::from_xpub(XPUB, 0).unwrap();
+        let public_key = key_service.public_key().await.unwrap();
+        assert_eq!(
+            public_key.to_string(),
+            "AntL+UxMyJ9NZ9DGLp2v7a3dlSxiNXMaItyOXSRw8iYi"
+        );
+        let private =
+            PrivateKeyService::new_from_mnemonic(Mnemonic::from_str(words, None).unwrap()).unwrap();
+        assert_eq!(
+            key_service.address().await.unwrap(),
+            private.address().await.unwrap()
+        );
+
+        let child = key_service.derive_child(1).unwrap();
+        let private_child = PrivateKeyService::new_from_mnemonic_with_index(
+            Mnemonic::from_str(words, None).unwrap(),
+            0,
+            1,
+        )
+        .unwrap();
+        assert_eq!(child.index, 1);
+        assert_eq!(
+            child.public_key().await.unwrap(),
+            private_child.public_key().await.unwrap()
+        );
+
+        assert!(matches!(
+            key_service.sign(b"msg").await,
+            Err(Error::SigningUnavailable)
+        ));
+        assert!(matches!(
+            key_service.sign_digest([0; 32]).await,
+            Err(Error::SigningUnavailable)
+        ));
+
+        assert!(WatchOnlyKeyService::from_xpub("xpub", 0).is_err());
+        // a corrupted checksum
+        let corrupted = XPUB.replace("xpub6C", "xpub6D");
+        assert!(WatchOnlyKeyService::from_xpub(&corrupted, 0).is_err());
+    }
+}
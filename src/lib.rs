@@ -1,11 +1,16 @@
+#[cfg(feature = "airgap")]
+pub mod airgap;
 pub mod client;
 pub mod constant;
 pub mod error;
 pub mod hd_wallet;
 pub mod key_service;
 pub mod message;
-#[cfg(feature = "grpc")]
 pub mod proto;
+#[cfg(all(feature = "signer-server", not(target_arch = "wasm32")))]
+pub mod signer_server;
+#[cfg(test)]
+mod test_util;
 pub mod tx_builder;
 pub mod types;
 pub mod utils;
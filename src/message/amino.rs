@@ -1,10 +1,10 @@
-use crate::constant::ACCOUNT_ADDRESS_PREFIX;
+use crate::types::address::{AccountAddress, ChainConfig};
 use crate::types::basic::Amount;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use stdtx::Address;
 
 /// the message in Tx
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Message<V: Serialize> {
     #[serde(rename = "type")]
     pub transfer_type: String,
@@ -12,7 +12,7 @@ pub struct Message<V: Serialize> {
 }
 
 /// the value in Transfer
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct TransferValue {
     pub from_address: String,
     pub to_address: String,
@@ -22,9 +22,19 @@ pub struct TransferValue {
 impl TransferValue {
     /// create a new TransferValue
     pub fn new(from_address: Address, to_address: Address, amount: Amount) -> Self {
+        Self::new_with_config(&ChainConfig::default(), from_address, to_address, amount)
+    }
+
+    /// create a new TransferValue with the account prefix of the chain
+    pub fn new_with_config(
+        chain_config: &ChainConfig,
+        from_address: Address,
+        to_address: Address,
+        amount: Amount,
+    ) -> Self {
         Self {
-            from_address: from_address.to_bech32(ACCOUNT_ADDRESS_PREFIX),
-            to_address: to_address.to_bech32(ACCOUNT_ADDRESS_PREFIX),
+            from_address: chain_config.account_address(&from_address),
+            to_address: chain_config.account_address(&to_address),
             amount: vec![amount],
         }
     }
@@ -36,10 +46,37 @@ pub type Transfer = Message<TransferValue>;
 impl Transfer {
     /// create a new transfer message
     pub fn new(from_address: Address, to_address: Address, amount: Amount) -> Self {
-        let transfer_value = TransferValue::new(from_address.into(), to_address.into(), amount);
+        Self::new_with_config(&ChainConfig::default(), from_address, to_address, amount)
+    }
+
+    /// create a new transfer message with the account prefix of the chain
+    pub fn new_with_config(
+        chain_config: &ChainConfig,
+        from_address: Address,
+        to_address: Address,
+        amount: Amount,
+    ) -> Self {
+        let transfer_value =
+            TransferValue::new_with_config(chain_config, from_address, to_address, amount);
         Self {
             transfer_type: "cosmos-sdk/MsgSend".into(),
             value: transfer_value,
         }
     }
+
+    /// create a new transfer message between the accounts, each shown with its own prefix
+    pub fn from_accounts(
+        from_address: &AccountAddress,
+        to_address: &AccountAddress,
+        amount: Amount,
+    ) -> Self {
+        Self {
+            transfer_type: "cosmos-sdk/MsgSend".into(),
+            value: TransferValue {
+                from_address: from_address.to_string(),
+                to_address: to_address.to_string(),
+                amount: vec![amount],
+            },
+        }
+    }
 }
@@ -1,35 +1,202 @@
 //! Transaction messages
 
+use crate::error::Error;
+use crate::proto::cosmos::bank::v1beta1::{MsgMultiSend, MsgSend};
+use crate::proto::cosmos::base::v1beta1::Coin;
+use crate::types::address::AccountAddress;
+use crate::types::basic::Amount;
 use prost_types::Any;
+use serde_json::json;
+use std::convert::TryFrom;
 
-/// Transaction messages
-#[derive(Clone)]
-pub struct Msg(Any);
+/// type url of the bank `MsgSend`
+pub const MSG_SEND_TYPE_URL: &str = "/cosmos.bank.v1beta1.MsgSend";
+/// type url of the bank `MsgMultiSend`
+pub const MSG_MULTI_SEND_TYPE_URL: &str = "/cosmos.bank.v1beta1.MsgMultiSend";
+
+/// Transaction messages, keeps the `Any` to be broadcast and the decoded message
+#[derive(Debug, Clone, PartialEq)]
+pub struct Msg {
+    any: Any,
+    typed: TypedMsg,
+}
+
+/// a message resolved from its type url
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedMsg {
+    Send(MsgSend),
+    MultiSend(MsgMultiSend),
+    /// a message of an unknown type url
+    Unknown(Any),
+}
+
+fn validate_coins(coins: &[Coin], name: &str) -> Result<(), Error> {
+    if coins.is_empty() {
+        return Err(Error::InputError(format!("{} has no amount", name)));
+    }
+    for coin in coins {
+        Amount::try_from(coin)?;
+    }
+    Ok(())
+}
+
+fn coins_json(coins: &[Coin]) -> Vec<serde_json::Value> {
+    coins
+        .iter()
+        .map(|coin| json!({"amount": coin.amount, "denom": coin.denom}))
+        .collect()
+}
+
+fn decode_any(any: &Any) -> Result<TypedMsg, Error> {
+    match any.type_url.as_str() {
+        MSG_SEND_TYPE_URL => Ok(TypedMsg::Send(prost::Message::decode(&*any.value)?)),
+        MSG_MULTI_SEND_TYPE_URL => Ok(TypedMsg::MultiSend(prost::Message::decode(&*any.value)?)),
+        _ => Ok(TypedMsg::Unknown(any.clone())),
+    }
+}
 
 impl Msg {
     /// Create a new message type
     pub fn new(type_url: impl Into<String>, value: impl Into<Vec<u8>>) -> Self {
-        Msg(Any {
+        Msg::from(Any {
             type_url: type_url.into(),
             value: value.into(),
         })
     }
+
+    /// the type url of the message
+    pub fn type_url(&self) -> &str {
+        &self.any.type_url
+    }
+
+    /// the decoded message, a known type url with undecodable bytes is `TypedMsg::Unknown`
+    pub fn typed(&self) -> &TypedMsg {
+        &self.typed
+    }
+
+    /// decode the message if its type url is known
+    pub fn decode_typed(&self) -> Result<TypedMsg, Error> {
+        decode_any(&self.any)
+    }
+
+    /// size of the encoded message in bytes
+    pub fn encoded_len(&self) -> usize {
+        prost::Message::encoded_len(&self.any)
+    }
+
+    /// check the message before it is signed
+    pub fn validate(&self) -> Result<(), Error> {
+        match &self.typed {
+            TypedMsg::Send(msg) => {
+                msg.from_address.parse::<AccountAddress>()?;
+                msg.to_address.parse::<AccountAddress>()?;
+                validate_coins(&msg.amount, "MsgSend")
+            }
+            TypedMsg::MultiSend(msg) => {
+                if msg.inputs.is_empty() || msg.outputs.is_empty() {
+                    return Err(Error::InputError(
+                        "MsgMultiSend needs inputs and outputs".to_string(),
+                    ));
+                }
+                for input in &msg.inputs {
+                    input.address.parse::<AccountAddress>()?;
+                    validate_coins(&input.coins, "MsgMultiSend input")?;
+                }
+                for output in &msg.outputs {
+                    output.address.parse::<AccountAddress>()?;
+                    validate_coins(&output.coins, "MsgMultiSend output")?;
+                }
+                Ok(())
+            }
+            TypedMsg::Unknown(_) if self.any.type_url.is_empty() => {
+                Err(Error::InputError("message has no type url".to_string()))
+            }
+            TypedMsg::Unknown(_) => decode_any(&self.any).map(|_| ()),
+        }
+    }
+
+    /// the legacy amino json of the message with sorted keys, used by SIGN_MODE_LEGACY_AMINO_JSON
+    pub fn amino_json(&self) -> Result<serde_json::Value, Error> {
+        match &self.typed {
+            TypedMsg::Send(msg) => Ok(json!({
+                "type": "cosmos-sdk/MsgSend",
+                "value": {
+                    "amount": coins_json(&msg.amount),
+                    "from_address": msg.from_address,
+                    "to_address": msg.to_address,
+                }
+            })),
+            TypedMsg::MultiSend(msg) => {
+                let inputs: Vec<serde_json::Value> = msg
+                    .inputs
+                    .iter()
+                    .map(|input| json!({"address": input.address, "coins": coins_json(&input.coins)}))
+                    .collect();
+                let outputs: Vec<serde_json::Value> = msg
+                    .outputs
+                    .iter()
+                    .map(|output| json!({"address": output.address, "coins": coins_json(&output.coins)}))
+                    .collect();
+                Ok(json!({
+                    "type": "cosmos-sdk/MsgMultiSend",
+                    "value": {
+                        "inputs": inputs,
+                        "outputs": outputs,
+                    }
+                }))
+            }
+            TypedMsg::Unknown(any) => Err(Error::SerializeError(format!(
+                "no amino json for message {}",
+                any.type_url
+            ))),
+        }
+    }
 }
 
 impl From<Any> for Msg {
     fn from(any: Any) -> Msg {
-        Msg(any)
+        let typed = decode_any(&any).unwrap_or_else(|_e| TypedMsg::Unknown(any.clone()));
+        Msg { any, typed }
+    }
+}
+
+impl From<MsgSend> for Msg {
+    fn from(msg: MsgSend) -> Msg {
+        let mut value = Vec::with_capacity(prost::Message::encoded_len(&msg));
+        // encoding into a vec never runs out of capacity
+        let _ = prost::Message::encode(&msg, &mut value);
+        Msg {
+            any: Any {
+                type_url: MSG_SEND_TYPE_URL.to_string(),
+                value,
+            },
+            typed: TypedMsg::Send(msg),
+        }
+    }
+}
+
+impl From<MsgMultiSend> for Msg {
+    fn from(msg: MsgMultiSend) -> Msg {
+        let mut value = Vec::with_capacity(prost::Message::encoded_len(&msg));
+        let _ = prost::Message::encode(&msg, &mut value);
+        Msg {
+            any: Any {
+                type_url: MSG_MULTI_SEND_TYPE_URL.to_string(),
+                value,
+            },
+            typed: TypedMsg::MultiSend(msg),
+        }
     }
 }
 
 impl From<Msg> for Any {
     fn from(msg: Msg) -> Any {
-        msg.0
+        msg.any
     }
 }
 
 impl From<&Msg> for Any {
     fn from(msg: &Msg) -> Any {
-        msg.0.clone()
+        msg.any.clone()
     }
 }
@@ -1,9 +1,5 @@
-#[cfg(not(feature = "grpc"))]
-mod amino;
-#[cfg(not(feature = "grpc"))]
-pub use amino::*;
+pub mod amino;
+pub mod grpc;
 
-#[cfg(feature = "grpc")]
-mod grpc;
-#[cfg(feature = "grpc")]
+pub use amino::*;
 pub use grpc::*;
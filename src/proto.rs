@@ -80,10 +80,29 @@ pub mod cosmos {
     pub mod crypto {
         /// Multi-signature support.
         pub mod multisig {
+            include!("proto/cosmos.crypto.multisig.rs");
+
             pub mod v1beta1 {
                 include!("proto/cosmos.crypto.multisig.v1beta1.rs");
             }
         }
+
+        /// Secp256k1 keys.
+        pub mod secp256k1 {
+            include!("proto/cosmos.crypto.secp256k1.rs");
+        }
+
+        /// Ed25519 keys.
+        pub mod ed25519 {
+            include!("proto/cosmos.crypto.ed25519.rs");
+        }
+    }
+
+    /// Vesting accounts.
+    pub mod vesting {
+        pub mod v1beta1 {
+            include!("proto/cosmos.vesting.v1beta1.rs");
+        }
     }
 
     /// Transactions.
@@ -0,0 +1,620 @@
+//! HTTP/JSON signing service backed by any `KeyService`, each key is only usable with its
+//! own access tokens (`Authorization: Bearer <token>`):
+//! - `GET /keys/{name}/address`: the address and the public key
+//! - `POST /keys/{name}/sign`: sign `{"sign_doc": {...}}` (an amino json `StdSignDoc`,
+//!   canonicalized) or `{"sign_doc_bytes": "<base64>"}` (a protobuf `SignDoc`), anything
+//!   else is refused so the server never signs arbitrary bytes
+//! - `POST /keys/{name}/sign_and_broadcast`: sign the amino tx `{"tx": ..., "chain_id": ...}`
+//!   and broadcast it through the client of the server
+
+use crate::client::ClientApi;
+use crate::error::Error;
+use crate::proto::cosmos::tx::v1beta1::{AuthInfo, SignDoc, TxBody};
+use crate::tx_builder::amino::{SignerKeyService, TxBuilder};
+use crate::types::address::ChainConfig;
+use crate::types::basic::{validate_denom, SyncMode};
+use crate::types::chain_id::ChainId;
+use crate::types::transaction::{Transaction, Tx};
+use crate::utils::codec::{canonical_json, deserde_from_str, serde_to_str};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// the largest request body which is read
+const MAX_BODY_SIZE: usize = 1 << 20;
+
+struct SignerKey {
+    key_service: SignerKeyService,
+    tokens: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SignRequest {
+    #[serde(default)]
+    sign_doc: Option<StdSignDoc>,
+    /// base64 of a protobuf `SignDoc`
+    #[serde(default)]
+    sign_doc_bytes: Option<String>,
+}
+
+/// the amino json `StdSignDoc`, the only json which is signed
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StdSignDoc {
+    #[serde(serialize_with = "serde_to_str", deserialize_with = "deserde_from_str")]
+    account_number: u64,
+    chain_id: ChainId,
+    fee: StdFee,
+    memo: String,
+    msgs: Vec<StdMsg>,
+    #[serde(serialize_with = "serde_to_str", deserialize_with = "deserde_from_str")]
+    sequence: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timeout_height: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StdFee {
+    amount: Vec<StdCoin>,
+    #[serde(serialize_with = "serde_to_str", deserialize_with = "deserde_from_str")]
+    gas: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    granter: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    payer: Option<String>,
+}
+
+/// a coin as it is signed, the cro amounts are not converted
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StdCoin {
+    #[serde(serialize_with = "serde_to_str", deserialize_with = "deserde_from_str")]
+    amount: u128,
+    denom: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StdMsg {
+    #[serde(rename = "type")]
+    msg_type: String,
+    value: serde_json::Map<String, serde_json::Value>,
+}
+
+impl StdSignDoc {
+    /// the canonical json which is signed
+    fn sign_bytes(&self) -> Result<Vec<u8>, Error> {
+        if self.msgs.is_empty() {
+            return Err(Error::InputError("sign doc has no message".to_string()));
+        }
+        if self.msgs.iter().any(|msg| msg.msg_type.is_empty()) {
+            return Err(Error::InputError(
+                "sign doc has a message without type".to_string(),
+            ));
+        }
+        for coin in &self.fee.amount {
+            validate_denom(&coin.denom)?;
+        }
+        if let Some(timeout_height) = &self.timeout_height {
+            timeout_height.parse::<u64>().map_err(|_| {
+                Error::InputError(format!("invalid timeout height {}", timeout_height))
+            })?;
+        }
+        let value = serde_json::to_value(self).map_err(|e| Error::SerializeError(e.to_string()))?;
+        Ok(canonical_json(&value).into_bytes())
+    }
+}
+
+/// the protobuf `SignDoc` as the node encodes it, only if it is a sign doc of a tx
+fn proto_sign_bytes(sign_doc_bytes: &str) -> Result<Vec<u8>, Error> {
+    let invalid = |e: prost::DecodeError| Error::InputError(format!("invalid sign doc: {}", e));
+    let bytes = base64::decode(sign_doc_bytes)
+        .map_err(|e| Error::InputError(format!("invalid base64 sign_doc_bytes: {}", e)))?;
+    let sign_doc = <SignDoc as prost::Message>::decode(bytes.as_slice()).map_err(invalid)?;
+    sign_doc.chain_id.parse::<ChainId>()?;
+    let body =
+        <TxBody as prost::Message>::decode(sign_doc.body_bytes.as_slice()).map_err(invalid)?;
+    if body.messages.is_empty() {
+        return Err(Error::InputError("sign doc has no message".to_string()));
+    }
+    let auth_info = <AuthInfo as prost::Message>::decode(sign_doc.auth_info_bytes.as_slice())
+        .map_err(invalid)?;
+    if auth_info.signer_infos.is_empty() {
+        return Err(Error::InputError("sign doc has no signer".to_string()));
+    }
+    let mut buf = vec![];
+    prost::Message::encode(&sign_doc, &mut buf)?;
+    Ok(buf)
+}
+
+#[derive(Serialize)]
+struct SignResponse {
+    signature: String,
+    public_key: String,
+}
+
+#[derive(Deserialize)]
+struct SignAndBroadcastRequest {
+    /// the unsigned StdTx json or the bare tx json
+    tx: serde_json::Value,
+    chain_id: ChainId,
+    /// queried from the node if not set
+    #[serde(default)]
+    account_number: Option<u64>,
+    #[serde(default)]
+    sequence: Option<u64>,
+    #[serde(default)]
+    mode: Option<SyncMode>,
+}
+
+/// the signing service, e.g.
+/// `server.add_key("treasury", key_service, &token).serve(([127, 0, 0, 1], 8080).into())`
+#[derive(Default)]
+pub struct SignerServer {
+    keys: HashMap<String, SignerKey>,
+    client: Option<Arc<dyn ClientApi>>,
+    chain_config: ChainConfig,
+}
+
+impl SignerServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// serve the key under `name`, usable with `token`
+    pub fn add_key(&mut self, name: &str, key_service: SignerKeyService, token: &str) -> &mut Self {
+        self.keys.insert(
+            name.to_string(),
+            SignerKey {
+                key_service,
+                tokens: vec![token.to_string()],
+            },
+        );
+        self
+    }
+
+    /// another access token of a key, e.g. one per caller so they can be revoked separately
+    pub fn add_token(&mut self, name: &str, token: &str) -> Result<&mut Self, Error> {
+        let key = self
+            .keys
+            .get_mut(name)
+            .ok_or_else(|| Error::InputError(format!("unknown key {}", name)))?;
+        key.tokens.push(token.to_string());
+        Ok(self)
+    }
+
+    /// the client of `sign_and_broadcast`, which is disabled without it
+    pub fn set_client<C: ClientApi + 'static>(&mut self, client: C) -> &mut Self {
+        self.client = Some(Arc::new(client));
+        self
+    }
+
+    /// the account prefix of the addresses, the crypto.org mainnet by default
+    pub fn set_chain_config(&mut self, chain_config: ChainConfig) -> &mut Self {
+        self.chain_config = chain_config;
+        self
+    }
+
+    /// serve the api until the server fails
+    pub async fn serve(self, addr: SocketAddr) -> Result<(), Error> {
+        let server = Arc::new(self);
+        let make_service = make_service_fn(move |_conn| {
+            let server = server.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.handle(request).await) }
+                }))
+            }
+        });
+        Server::try_bind(&addr)
+            .map_err(|e| Error::ServerError(e.to_string()))?
+            .serve(make_service)
+            .await
+            .map_err(|e| Error::ServerError(e.to_string()))
+    }
+
+    /// handle one request, the errors are returned as `{"error": "..."}`
+    pub async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        let segments: Vec<String> = request
+            .uri()
+            .path()
+            .trim_matches('/')
+            .split('/')
+            .map(str::to_string)
+            .collect();
+        let (name, action) = match segments.as_slice() {
+            [keys, name, action] if keys == "keys" => (name.as_str(), action.as_str()),
+            _ => return error_response(StatusCode::NOT_FOUND, "not found"),
+        };
+        // unknown keys and wrong tokens look the same to the caller
+        let key = match self.keys.get(name) {
+            Some(key) if authorized(&request, &key.tokens) => key,
+            _ => return error_response(StatusCode::UNAUTHORIZED, "unauthorized"),
+        };
+        let result = match (request.method(), action) {
+            (&Method::GET, "address") => self.address(key).await,
+            (&Method::POST, "sign") => match read_json(request).await {
+                Ok(body) => sign(key, body).await,
+                Err(response) => return response,
+            },
+            (&Method::POST, "sign_and_broadcast") => match read_json(request).await {
+                Ok(body) => self.sign_and_broadcast(key, body).await,
+                Err(response) => return response,
+            },
+            _ => return error_response(StatusCode::NOT_FOUND, "not found"),
+        };
+        match result {
+            Ok(value) => json_response(StatusCode::OK, &value),
+            Err(e) => {
+                let status = match e {
+                    Error::InputError(_) | Error::SerializeError(_) => StatusCode::BAD_REQUEST,
+                    _ if e.is_retryable() => StatusCode::SERVICE_UNAVAILABLE,
+                    _ => StatusCode::INTERNAL_SERVER_ERROR,
+                };
+                error_response(status, &e.to_string())
+            }
+        }
+    }
+
+    async fn address(&self, key: &SignerKey) -> Result<serde_json::Value, Error> {
+        let address = key.key_service.address().await?;
+        let public_key = key.key_service.public_key().await?;
+        Ok(json!({
+            "address": self.chain_config.account_address(&address),
+            "public_key": public_key.to_string(),
+        }))
+    }
+
+    async fn sign_and_broadcast(
+        &self,
+        key: &SignerKey,
+        request: SignAndBroadcastRequest,
+    ) -> Result<serde_json::Value, Error> {
+        let client = self.client.as_ref().ok_or_else(|| {
+            Error::ClientError("the signer server has no client to broadcast".to_string())
+        })?;
+        let mut tx: Tx<serde_json::Value> = Tx::from_json(&request.tx.to_string())?;
+        let (account_number, sequence) = match (request.account_number, request.sequence) {
+            (Some(account_number), Some(sequence)) => (account_number, sequence),
+            _ => {
+                let address = key.key_service.address().await?;
+                client
+                    .get_account_info(&self.chain_config.account_address(&address))
+                    .await?
+            }
+        };
+        let mut builder = TxBuilder::from_tx(key.key_service.clone(), request.chain_id, tx.clone());
+        builder
+            .set_account_number(account_number)
+            .set_sequence(sequence);
+        builder.append_signature(&mut tx).await?;
+        let transaction = Transaction {
+            tx,
+            mode: request.mode.unwrap_or(SyncMode::Sync),
+        };
+        let txhash = client.broadcast_amino_tx(transaction).await?;
+        Ok(json!({ "txhash": txhash }))
+    }
+}
+
+async fn sign(key: &SignerKey, request: SignRequest) -> Result<serde_json::Value, Error> {
+    let sign_bytes = match (request.sign_doc, request.sign_doc_bytes) {
+        (Some(sign_doc), None) => sign_doc.sign_bytes()?,
+        (None, Some(sign_doc_bytes)) => proto_sign_bytes(&sign_doc_bytes)?,
+        _ => {
+            return Err(Error::InputError(
+                "one of sign_doc and sign_doc_bytes is required".to_string(),
+            ))
+        }
+    };
+    let signature = key.key_service.sign(&sign_bytes).await?;
+    let public_key = key.key_service.public_key().await?;
+    let response = SignResponse {
+        signature,
+        public_key: public_key.to_string(),
+    };
+    serde_json::to_value(response).map_err(|e| Error::SerializeError(e.to_string()))
+}
+
+fn authorized(request: &Request<Body>, tokens: &[String]) -> bool {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match token {
+        Some(token) => tokens
+            .iter()
+            .any(|expected| constant_time_eq(expected.as_bytes(), token.as_bytes())),
+        None => false,
+    }
+}
+
+/// compare the tokens without leaking the matching prefix through the timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn read_json<T: serde::de::DeserializeOwned>(
+    request: Request<Body>,
+) -> Result<T, Response<Body>> {
+    let too_large = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok())
+        .map_or(false, |length| length > MAX_BODY_SIZE);
+    if too_large {
+        return Err(error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "request body is too large",
+        ));
+    }
+    let body = hyper::body::to_bytes(request.into_body())
+        .await
+        .map_err(|e| error_response(StatusCode::BAD_REQUEST, &e.to_string()))?;
+    if body.len() > MAX_BODY_SIZE {
+        return Err(error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "request body is too large",
+        ));
+    }
+    serde_json::from_slice(&body)
+        .map_err(|e| error_response(StatusCode::BAD_REQUEST, &e.to_string()))
+}
+
+fn json_response(status: StatusCode, value: &serde_json::Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(value.to_string()))
+        .expect("valid response")
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Body> {
+    json_response(status, &json!({ "error": message }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::mock::{tx_hash, MockClient};
+    use crate::hd_wallet::mnemonic::Mnemonic;
+    use crate::key_service::private_key_service::PrivateKeyService;
+    use crate::key_service::KeyService;
+    use crate::proto::cosmos::base::v1beta1::Coin;
+    use crate::proto::cosmos::tx::v1beta1::Fee;
+    use crate::tx_builder::grpc;
+    use crate::types::account::{Account, BaseAccount};
+
+    fn request(method: Method, path: &str, token: &str, body: &str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(path)
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    async fn body_json(response: Response<Body>) -> serde_json::Value {
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_signer_server() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+        let mut server = SignerServer::new();
+        server.add_key("alice", Arc::new(key_service.clone()), "token-a");
+        server.add_token("alice", "token-b").unwrap();
+        assert!(server.add_token("bob", "token-c").is_err());
+
+        let response = server
+            .handle(request(Method::GET, "/keys/alice/address", "token-b", ""))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+        let address = key_service.address().await.unwrap();
+        assert_eq!(
+            body["address"],
+            ChainConfig::default().account_address(&address)
+        );
+
+        for (path, token) in &[
+            ("/keys/alice/address", "token-c"),
+            ("/keys/bob/address", "token-a"),
+        ] {
+            let response = server.handle(request(Method::GET, path, token, "")).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        let response = server
+            .handle(request(
+                Method::POST,
+                "/keys/alice/sign_and_broadcast",
+                "token-a",
+                r#"{"tx": {"msg": [], "fee": {"amount": [], "gas": "0"}, "memo": ""}, "chain_id": "test"}"#,
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_sign_sign_docs() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+        let mut server = SignerServer::new();
+        server.add_key("alice", Arc::new(key_service.clone()), "token-a");
+        let sign = |body: serde_json::Value| {
+            server.handle(request(
+                Method::POST,
+                "/keys/alice/sign",
+                "token-a",
+                &body.to_string(),
+            ))
+        };
+
+        let fee = Fee {
+            amount: vec![Coin {
+                denom: "basecro".to_string(),
+                amount: "10000".to_string(),
+            }],
+            gas_limit: 300000,
+            payer: String::new(),
+            granter: String::new(),
+        };
+        let mut builder = grpc::TxBuilder::new(
+            key_service.clone(),
+            "test".parse().unwrap(),
+            None,
+            0,
+            Some(fee),
+        );
+        let amount = Coin {
+            denom: "basecro".to_string(),
+            amount: "1".to_string(),
+        };
+        let msg = builder
+            .create_msg("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd".into(), amount)
+            .await
+            .unwrap();
+        builder
+            .add_message(msg)
+            .set_account_number(9)
+            .set_sequence(4);
+
+        // the amino json sign doc, signed canonicalized
+        let amino = builder.amino_sign_doc().unwrap();
+        let sign_doc: serde_json::Value = serde_json::from_slice(&amino).unwrap();
+        let response = sign(json!({ "sign_doc": sign_doc })).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let expected = key_service.sign(&amino).await.unwrap();
+        assert_eq!(body_json(response).await["signature"], expected);
+
+        // the protobuf sign doc
+        let mut proto = vec![];
+        prost::Message::encode(&builder.sign_doc().await.unwrap(), &mut proto).unwrap();
+        let response = sign(json!({ "sign_doc_bytes": base64::encode(&proto) })).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let expected = key_service.sign(&proto).await.unwrap();
+        assert_eq!(body_json(response).await["signature"], expected);
+
+        // the arbitrary bytes and json are never signed
+        let mut extra_field = sign_doc.clone();
+        extra_field["extra"] = json!("x");
+        let mut no_msgs = sign_doc.clone();
+        no_msgs["msgs"] = json!([]);
+        let mut no_chain_id = sign_doc;
+        no_chain_id["chain_id"] = json!("");
+        for body in vec![
+            json!({}),
+            json!({ "sign_doc": {"b": 1, "a": "x"} }),
+            json!({ "sign_doc": extra_field }),
+            json!({ "sign_doc": no_msgs }),
+            json!({ "sign_doc": no_chain_id }),
+            json!({ "sign_bytes": base64::encode(&amino) }),
+            json!({ "sign_doc_bytes": base64::encode(b"arbitrary bytes") }),
+            json!({ "sign_doc_bytes": base64::encode(&amino) }),
+            json!({ "sign_doc_bytes": "" }),
+        ] {
+            let response = sign(body.clone()).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", body);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_broadcast() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+        let address = ChainConfig::default().account_address(&key_service.address().await.unwrap());
+        let client = MockClient::new();
+        client.set_account(Account::BaseAccount(BaseAccount {
+            address: address.clone(),
+            pub_key: None,
+            account_number: 9,
+            sequence: 4,
+        }));
+        let mut server = SignerServer::new();
+        server
+            .add_key("alice", Arc::new(key_service.clone()), "token-a")
+            .set_client(client.clone());
+
+        let tx = json!({
+            "msg": [{
+                "type": "cosmos-sdk/MsgSend",
+                "value": {
+                    "from_address": address,
+                    "to_address": "cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd",
+                    "amount": [{"denom": "basecro", "amount": "1"}],
+                },
+            }],
+            "fee": {"amount": [], "gas": "200000"},
+            "memo": "",
+        });
+        let body = json!({ "tx": tx, "chain_id": "test", "mode": "block" });
+        let response = server
+            .handle(request(
+                Method::POST,
+                "/keys/alice/sign_and_broadcast",
+                "token-a",
+                &body.to_string(),
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+
+        // signed with the account number and the sequence of the node
+        let mut expected: Tx<serde_json::Value> = Tx::from_json(&tx.to_string()).unwrap();
+        let mut builder = TxBuilder::from_tx(
+            Arc::new(key_service) as SignerKeyService,
+            "test".parse().unwrap(),
+            expected.clone(),
+        );
+        builder.set_account_number(9).set_sequence(4);
+        builder.append_signature(&mut expected).await.unwrap();
+        let broadcasted = client.broadcasted_txs();
+        assert_eq!(broadcasted.len(), 1);
+        let transaction: Transaction<serde_json::Value> =
+            serde_json::from_slice(&broadcasted[0]).unwrap();
+        assert_eq!(transaction.tx, expected);
+        assert_eq!(transaction.mode, SyncMode::Block);
+        assert_eq!(body["txhash"], tx_hash(&broadcasted[0]));
+
+        // the rejection of the node is returned to the caller
+        client.push_tx_response(crate::types::tx_response::TxResponse {
+            height: 0,
+            txhash: String::new(),
+            codespace: "sdk".to_string(),
+            code: 5,
+            raw_log: "insufficient funds".to_string(),
+            gas_wanted: 0,
+            gas_used: 0,
+        });
+        let body = json!({ "tx": tx, "chain_id": "test" });
+        let response = server
+            .handle(request(
+                Method::POST,
+                "/keys/alice/sign_and_broadcast",
+                "token-a",
+                &body.to_string(),
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(body_json(response).await["error"]
+            .as_str()
+            .unwrap()
+            .contains("insufficient funds"));
+    }
+}
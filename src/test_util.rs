@@ -0,0 +1,23 @@
+//! Fixtures of the tests of the amino multi signer txs: the mnemonic of the tests, its keys
+//! and addresses
+
+use crate::hd_wallet::mnemonic::Mnemonic;
+use crate::key_service::private_key_service::PrivateKeyService;
+use crate::types::chain_id::ChainId;
+
+/// the mnemonic of the tests
+pub const WORDS: &str = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+/// the recipient of the transfers of the tests
+pub const RECIPIENT: &str = "cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd";
+
+/// the chain id of the txs of the tests
+pub fn chain_id() -> ChainId {
+    "test".parse().expect("invalid test chain id")
+}
+
+/// the key service of m/44'/394'/0'/0/index of `WORDS`, which derives the other indexes
+pub fn key_service_with_index(index: u32) -> PrivateKeyService {
+    let mnemonic = Mnemonic::from_str(WORDS, None).expect("invalid test mnemonic");
+    PrivateKeyService::new_from_mnemonic_with_index(mnemonic, 0, index)
+        .expect("derive the test key")
+}
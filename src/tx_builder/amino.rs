@@ -1,21 +1,39 @@
+use crate::client::ClientApi;
+use crate::constant::MAX_MEMO_LENGTH;
 use crate::error::Error;
 use crate::key_service::KeyService;
 use crate::types::basic::{Amount, Fee, SyncMode};
+use crate::types::chain_id::ChainId;
+use crate::types::fee::{sort_fee_coins, FeeBuilder};
 use crate::types::signature::Signature;
 use crate::types::transaction::{Transaction, Tx};
-use crate::utils::codec::serde_to_str;
+use crate::utils::codec::{canonical_json, serde_to_str};
 use serde::Serialize;
+use std::sync::Arc;
+
+/// the KeyService of a signer besides the key service of the builder
+pub type SignerKeyService = Arc<dyn KeyService + Send + Sync>;
+
+/// another signer of the tx, it signs with its own account number and sequence
+#[derive(Clone)]
+pub struct AdditionalSigner {
+    pub key_service: SignerKeyService,
+    pub account_number: u64,
+    pub sequence: u64,
+}
 
 #[derive(Clone)]
 pub struct TxBuilder<T: KeyService + Clone, M: Serialize + Clone> {
     pub key_service: T,
-    pub chain_id: String,
+    pub chain_id: ChainId,
     pub messages: Vec<M>,
     pub memo: String,
     pub account_number: u64,
     pub sequence: u64,
-    pub fee: Option<Amount>,
+    /// fee coins, one per denom
+    pub fee: Vec<Amount>,
     pub gas: Option<u64>,
+    pub additional_signers: Vec<AdditionalSigner>,
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -24,7 +42,7 @@ struct SignMsg<M: Serialize> {
     pub account_number: u64,
     #[serde(serialize_with = "serde_to_str")]
     pub sequence: u64,
-    pub chain_id: String,
+    pub chain_id: ChainId,
     pub memo: String,
     pub fee: Fee,
     pub msgs: Vec<M>,
@@ -37,14 +55,14 @@ where
 {
     pub fn new(
         key_service: T,
-        chain_id: String,
+        chain_id: ChainId,
         memo: Option<String>,
         fee: Option<Amount>,
         gas: Option<u64>,
     ) -> Self {
         let memo = memo.unwrap_or_default();
         Self {
-            fee,
+            fee: fee.into_iter().collect(),
             gas,
             memo,
             key_service,
@@ -52,9 +70,18 @@ where
             sequence: 0,
             account_number: 0,
             messages: vec![],
+            additional_signers: vec![],
         }
     }
 
+    /// create a builder from a loaded tx (e.g. `Tx::read_file`), so it can be signed
+    pub fn from_tx(key_service: T, chain_id: ChainId, tx: Tx<M>) -> Self {
+        let mut builder = Self::new(key_service, chain_id, Some(tx.memo), None, Some(tx.fee.gas));
+        builder.fee = tx.fee.amount;
+        builder.messages = tx.messages;
+        builder
+    }
+
     pub fn set_account_number(&mut self, account_number: u64) -> &mut Self {
         self.account_number = account_number;
         self
@@ -70,24 +97,85 @@ where
         self
     }
 
+    /// remove the added messages, so the builder can be reused for the next tx
+    pub fn clear_messages(&mut self) -> &mut Self {
+        self.messages.clear();
+        self
+    }
+
+    pub fn set_memo(&mut self, memo: String) -> &mut Self {
+        self.memo = memo;
+        self
+    }
+
+    pub fn set_chain_id(&mut self, chain_id: ChainId) -> &mut Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// set the chain id to the one of the node behind the client
+    pub async fn with_detected_chain_id<C: ClientApi>(
+        &mut self,
+        client: &C,
+    ) -> Result<&mut Self, Error> {
+        self.chain_id = client.get_node_info().await?.chain_id.parse()?;
+        Ok(self)
+    }
+
+    pub fn set_fee(&mut self, fee: Option<Amount>, gas: Option<u64>) -> &mut Self {
+        self.set_fee_coins(fee.into_iter().collect(), gas)
+    }
+
+    /// pay the fee in several denoms
+    pub fn set_fee_coins(&mut self, fee: Vec<Amount>, gas: Option<u64>) -> &mut Self {
+        self.fee = fee;
+        self.gas = gas;
+        self
+    }
+
+    /// set the fee coins and the gas limit of the fee builder
+    pub fn set_fee_builder(&mut self, fee: &FeeBuilder) -> Result<&mut Self, Error> {
+        let fee = fee.build_amino()?;
+        Ok(self.set_fee_coins(fee.amount, Some(fee.gas)))
+    }
+
+    /// add another signer (e.g. a member of a legacy multisig account),
+    /// the signatures are in the order the signers are added, after the builder's own
+    pub fn add_signer(
+        &mut self,
+        key_service: SignerKeyService,
+        account_number: u64,
+        sequence: u64,
+    ) -> &mut Self {
+        self.additional_signers.push(AdditionalSigner {
+            key_service,
+            account_number,
+            sequence,
+        });
+        self
+    }
+
     #[inline]
     fn get_fee(&self) -> Fee {
-        let amount = if self.fee.is_some() {
-            vec![self.fee.clone().unwrap()]
-        } else {
-            vec![]
-        };
+        // the sdk requires the coins sorted by denom
+        let mut amount = self.fee.clone();
+        amount.sort_by(|a, b| a.denom().as_str().cmp(b.denom().as_str()));
         Fee {
             gas: self.gas.unwrap_or(20000),
             amount,
         }
     }
 
-    async fn sign(&mut self) -> Result<Signature, Error> {
+    async fn sign<K: KeyService + ?Sized>(
+        &self,
+        key_service: &K,
+        account_number: u64,
+        sequence: u64,
+    ) -> Result<Signature, Error> {
         let fee = self.get_fee();
         let sign_msg = SignMsg {
-            account_number: self.account_number,
-            sequence: self.sequence,
+            account_number,
+            sequence,
             chain_id: self.chain_id.clone(),
             memo: self.memo.clone(),
             fee,
@@ -95,29 +183,71 @@ where
         };
         let value =
             serde_json::to_value(&sign_msg).map_err(|e| Error::SerializeError(e.to_string()))?;
-        let sign_str = sorted_json::to_json(&value)
-            .replace("\n", "")
-            .replace(" ", "");
-        let signature = self.key_service.sign(sign_str.as_bytes()).await?;
-        let public_key = self.key_service.public_key()?;
+        let sign_str = canonical_json(&value);
+        let signature = key_service.sign(sign_str.as_bytes()).await?;
+        let public_key = key_service.public_key().await?;
 
         let signature = Signature {
             signature,
             pub_key: public_key.into(),
-            account_number: self.account_number,
-            sequence: self.sequence,
+            account_number,
+            sequence,
         };
         Ok(signature)
     }
 
+    /// reject the txs which the node would refuse anyway
+    fn validate(&self) -> Result<(), Error> {
+        if self.messages.is_empty() {
+            return Err(Error::InputError("tx has no message".to_string()));
+        }
+        if self.memo.len() > MAX_MEMO_LENGTH {
+            return Err(Error::InputError(format!(
+                "memo is too long, {} > {} bytes",
+                self.memo.len(),
+                MAX_MEMO_LENGTH
+            )));
+        }
+        if !self.fee.is_empty() && self.gas == Some(0) {
+            return Err(Error::InputError("fee is set with zero gas".to_string()));
+        }
+        sort_fee_coins(self.fee.clone())?;
+        Ok(())
+    }
+
+    /// sign the tx the builder is created from by `from_tx` and append the signature,
+    /// so the signed tx can be written back
+    pub async fn append_signature(&self, tx: &mut Tx<M>) -> Result<(), Error> {
+        self.validate()?;
+        let signature = self
+            .sign(&self.key_service, self.account_number, self.sequence)
+            .await?;
+        tx.signatures.push(signature);
+        Ok(())
+    }
+
     pub async fn build(&mut self, sync_mode: SyncMode) -> Result<Transaction<M>, Error> {
-        let signature = self.sign().await?;
+        self.validate()?;
+        let mut signatures = vec![
+            self.sign(&self.key_service, self.account_number, self.sequence)
+                .await?,
+        ];
+        for signer in &self.additional_signers {
+            let signature = self
+                .sign(
+                    signer.key_service.as_ref(),
+                    signer.account_number,
+                    signer.sequence,
+                )
+                .await?;
+            signatures.push(signature);
+        }
         let fee = self.get_fee();
         let tx = Tx {
             messages: self.messages.clone(),
             fee,
             memo: self.memo.clone(),
-            signatures: vec![signature],
+            signatures,
         };
         let transaction = Transaction {
             tx,
@@ -134,8 +264,9 @@ mod test {
     use crate::hd_wallet::mnemonic::Mnemonic;
     use crate::key_service::private_key_service::PrivateKeyService;
     use crate::message::{Transfer, TransferValue};
+    use crate::test_util;
     use crate::types::basic::{Amount, Denom};
-    use crate::types::key::PublicKey;
+    use crate::types::key::{PublicKey, PublicKeyWrap};
     use stdtx::Address;
 
     #[tokio::test]
@@ -146,11 +277,11 @@ mod test {
         let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
         let mnemonic = Mnemonic::from_str(words, None).unwrap();
         let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
-        let chain_id = "test".to_string();
+        let chain_id = "test".parse().unwrap();
         let mut builder = TxBuilder::new(key_service, chain_id, memo, Some(fee.clone()), gas);
         let (_, to_address) =
             Address::from_bech32("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd").unwrap();
-        let from_address = builder.key_service.address().unwrap();
+        let from_address = builder.key_service.address().await.unwrap();
         let amount = Amount::new(100000000, Denom::Basecro);
         let msg = Transfer::new(from_address, to_address, amount);
         builder.add_message(msg);
@@ -187,4 +318,81 @@ mod test {
         };
         assert_eq!(transfer, transfer_expected);
     }
+
+    #[tokio::test]
+    async fn test_multi_signer() {
+        let key_service = test_util::key_service_with_index(0);
+        let second = key_service.derive_child(1).unwrap();
+        let second_pk = second.public_key().await.unwrap();
+        let fee = Amount::new(100000, Denom::Basecro);
+        let mut builder = TxBuilder::new(
+            key_service,
+            test_util::chain_id(),
+            None,
+            Some(fee),
+            Some(300000),
+        );
+        let (_, to_address) = Address::from_bech32(test_util::RECIPIENT).unwrap();
+        let from_address = builder.key_service.address().await.unwrap();
+        let msg = Transfer::new(from_address, to_address, Amount::new(1, Denom::Basecro));
+        builder.add_message(msg).add_signer(Arc::new(second), 10, 2);
+        let transaction = builder.build(SyncMode::Sync).await.unwrap();
+        let signatures = transaction.tx.signatures;
+        assert_eq!(signatures.len(), 2);
+        assert_eq!(signatures[1].pub_key, PublicKeyWrap::from(second_pk));
+        assert_eq!(signatures[1].account_number, 10);
+        assert_eq!(signatures[1].sequence, 2);
+        assert_ne!(signatures[0].signature, signatures[1].signature);
+    }
+
+    #[tokio::test]
+    async fn test_multi_denom_fee() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+        let mut builder = TxBuilder::new(
+            key_service,
+            "test".parse().unwrap(),
+            None,
+            None,
+            Some(300000),
+        );
+        let (_, to_address) =
+            Address::from_bech32("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd").unwrap();
+        let from_address = builder.key_service.address().await.unwrap();
+        let msg = Transfer::new(from_address, to_address, Amount::new(1, Denom::Basecro));
+        let uatom = Amount::new(10, Denom::Other("uatom".into()));
+        let basecro = Amount::new(1000, Denom::Basecro);
+        builder
+            .add_message(msg)
+            .set_fee_coins(vec![uatom.clone(), basecro.clone()], Some(300000));
+        let transaction = builder.build(SyncMode::Sync).await.unwrap();
+        assert_eq!(transaction.tx.fee.amount, vec![basecro.clone(), uatom]);
+        let json = serde_json::to_value(&transaction.tx.fee).unwrap();
+        assert_eq!(json["amount"][1]["denom"], "uatom");
+
+        builder.set_fee_coins(vec![basecro.clone(), basecro], Some(300000));
+        assert!(builder.build(SyncMode::Sync).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_export() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+        let unsigned = r#"{"type":"cosmos-sdk/StdTx","value":{"msg":[{"type":"cosmos-sdk/MsgSend","value":{"from_address":"cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf","to_address":"cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd","amount":[{"denom":"basecro","amount":"100000000"}]}}],"fee":{"amount":[{"denom":"basecro","amount":"100000"}],"gas":"300000"},"signatures":null,"memo":""}}"#;
+        let mut tx: Tx<Transfer> = Tx::from_json(unsigned).unwrap();
+        assert!(tx.signatures.is_empty());
+
+        let builder = TxBuilder::from_tx(key_service.clone(), "test".parse().unwrap(), tx.clone());
+        builder.append_signature(&mut tx).await.unwrap();
+        // same signature as the tx built by the builder in test_tx_builder
+        assert_eq!(tx.signatures[0].signature, "xi3rvdsoZMXhWq7MlgAMXpoVIZ0kv7uB00OrSRS8wxwoZhojZ5uGZ4shobn3ztOev4M1k5WVcBvVd+zTvzRHCg==");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tx.json");
+        tx.write_file(&path).unwrap();
+        let loaded: Tx<Transfer> = Tx::read_file(&path).unwrap();
+        assert_eq!(loaded, tx);
+    }
 }
@@ -0,0 +1,171 @@
+//! Signing of many independent protobuf txs of one account at once (airdrops, payouts),
+//! the txs get the consecutive sequences from the starting sequence and are signed
+//! concurrently with a bounded number of tasks
+
+use crate::error::Error;
+use crate::key_service::KeyService;
+use crate::message::Msg;
+use crate::proto::cosmos::tx::v1beta1::Fee;
+use crate::tx_builder::grpc::{BuiltTx, TxBuilder};
+use crate::types::address::ChainConfig;
+use crate::types::chain_id::ChainId;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// default number of the txs signed at the same time
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// one tx of the batch
+#[derive(Debug, Clone)]
+pub struct BatchTx {
+    pub messages: Vec<Msg>,
+    pub memo: Option<String>,
+    /// the fee of this tx instead of the fee of the `BatchSigner`
+    pub fee: Option<Fee>,
+}
+
+impl BatchTx {
+    pub fn new(messages: Vec<Msg>) -> Self {
+        Self {
+            messages,
+            memo: None,
+            fee: None,
+        }
+    }
+}
+
+/// signs the txs of a batch, the n-th tx has the sequence `start_sequence + n`, so the txs
+/// must be broadcast in order and the account must not send other txs meanwhile
+pub struct BatchSigner<T: KeyService + Clone> {
+    key_service: T,
+    chain_id: ChainId,
+    account_number: u64,
+    start_sequence: u64,
+    fee: Option<Fee>,
+    chain_config: ChainConfig,
+    concurrency: usize,
+}
+
+impl<T> BatchSigner<T>
+where
+    T: KeyService + Clone + Send + Sync + 'static,
+{
+    pub fn new(
+        key_service: T,
+        chain_id: ChainId,
+        account_number: u64,
+        start_sequence: u64,
+    ) -> Self {
+        Self {
+            key_service,
+            chain_id,
+            account_number,
+            start_sequence,
+            fee: None,
+            chain_config: ChainConfig::default(),
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// the fee of the txs which have no fee of their own
+    pub fn set_fee(&mut self, fee: Option<Fee>) -> &mut Self {
+        self.fee = fee;
+        self
+    }
+
+    pub fn set_chain_config(&mut self, chain_config: ChainConfig) -> &mut Self {
+        self.chain_config = chain_config;
+        self
+    }
+
+    /// number of the txs signed at the same time, 8 by default
+    pub fn set_concurrency(&mut self, concurrency: usize) -> &mut Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// the sequence of the next batch
+    pub fn next_sequence(&self, batch_len: usize) -> u64 {
+        self.start_sequence + batch_len as u64
+    }
+
+    /// sign and assemble the txs, returned in the order of `txs`,
+    /// the batch fails on the first tx which can't be signed
+    pub async fn sign_all(&self, txs: Vec<BatchTx>) -> Result<Vec<BuiltTx>, Error> {
+        let semaphore = Arc::new(Semaphore::new(self.concurrency));
+        let mut tasks = Vec::with_capacity(txs.len());
+        for (index, tx) in txs.into_iter().enumerate() {
+            let mut builder = TxBuilder::new(
+                self.key_service.clone(),
+                self.chain_id.clone(),
+                tx.memo,
+                0,
+                tx.fee.or_else(|| self.fee.clone()),
+            );
+            builder
+                .set_chain_config(self.chain_config.clone())
+                .set_account_number(self.account_number)
+                .set_sequence(self.start_sequence + index as u64);
+            for msg in tx.messages {
+                builder.add_message(msg);
+            }
+            let semaphore = semaphore.clone();
+            tasks.push(tokio::spawn(async move {
+                let _permit = semaphore.acquire().await;
+                builder.build_with_hash().await
+            }));
+        }
+        let mut built = Vec::with_capacity(tasks.len());
+        for (index, task) in tasks.into_iter().enumerate() {
+            let tx = task.await.map_err(|e| {
+                Error::InputError(format!("signing task of tx {} failed: {}", index, e))
+            })??;
+            built.push(tx);
+        }
+        Ok(built)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hd_wallet::mnemonic::Mnemonic;
+    use crate::key_service::private_key_service::PrivateKeyService;
+    use crate::proto::cosmos::base::v1beta1::Coin;
+    use crate::proto::cosmos::tx::v1beta1::{AuthInfo, TxRaw};
+    use prost::Message;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_batch_signer() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+        let builder = TxBuilder::new(key_service.clone(), "test".parse().unwrap(), None, 0, None);
+        let mut txs = vec![];
+        for amount in 1..=20 {
+            let amount = Coin {
+                denom: "basecro".to_string(),
+                amount: amount.to_string(),
+            };
+            let msg = builder
+                .create_msg("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd".into(), amount)
+                .await
+                .unwrap();
+            txs.push(BatchTx::new(vec![msg]));
+        }
+
+        let mut signer = BatchSigner::new(key_service, "test".parse().unwrap(), 1, 100);
+        signer.set_concurrency(4);
+        let built = signer.sign_all(txs.clone()).await.unwrap();
+        assert_eq!(built.len(), 20);
+        for (index, tx) in built.iter().enumerate() {
+            let tx_raw = TxRaw::decode(tx.bytes.as_slice()).unwrap();
+            let auth_info = AuthInfo::decode(tx_raw.auth_info_bytes.as_slice()).unwrap();
+            assert_eq!(auth_info.signer_infos[0].sequence, 100 + index as u64);
+        }
+        assert_eq!(signer.next_sequence(built.len()), 120);
+
+        txs.push(BatchTx::new(vec![]));
+        assert!(signer.sign_all(txs).await.is_err());
+    }
+}
@@ -1,34 +1,163 @@
-use crate::constant::ACCOUNT_ADDRESS_PREFIX;
-use crate::error::Error;
-use crate::key_service::KeyService;
+use crate::client::ClientApi;
+use crate::constant::MAX_MEMO_LENGTH;
+use crate::error::{BroadcastError, Error};
+use crate::key_service::multisig_service::{MemberKeyService, MultisigKeyService};
+use crate::key_service::{KeyService, SECP256K1_PUBKEY_TYPE_URL};
 use crate::message::Msg;
 use crate::proto::cosmos::bank::v1beta1::MsgSend;
 use crate::proto::cosmos::base::v1beta1::Coin;
 use crate::proto::cosmos::tx::v1beta1::{
     mode_info, AuthInfo, Fee, ModeInfo, SignDoc, SignerInfo, TxBody, TxRaw,
 };
+use crate::types::address::{AccountAddress, ChainConfig};
+use crate::types::basic::{GasPrice, SyncMode};
+use crate::types::chain_id::ChainId;
+use crate::types::fee::FeeBuilder;
+use crate::types::key::PublicKey;
+use crate::types::partial_tx::PartialTx;
+use crate::types::tx_response::TxResponse;
+use crate::utils::codec::{canonical_json, deserde_from_str, serde_to_str};
+use bitcoin_hashes::{sha256, Hash};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// the sign mode of the signers, it decides the bytes handed to `KeyService::sign`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignMode {
+    /// SIGN_MODE_DIRECT, sign the protobuf `SignDoc`
+    Direct,
+    /// SIGN_MODE_LEGACY_AMINO_JSON, sign the amino json `StdSignDoc`
+    LegacyAminoJson,
+    /// SIGN_MODE_DIRECT_AUX, sign the protobuf `SignDocDirectAux` without the fee,
+    /// only for the signers besides the fee payer (cosmos-sdk v0.46+)
+    DirectAux,
+}
+
+impl SignMode {
+    /// the value of the protobuf `SignMode`
+    pub fn as_i32(&self) -> i32 {
+        match self {
+            SignMode::Direct => 1,
+            SignMode::DirectAux => 3,
+            SignMode::LegacyAminoJson => 127,
+        }
+    }
+}
+
+/// `cosmos.tx.v1beta1.SignDocDirectAux` of cosmos-sdk v0.46, not in the generated protos
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SignDocDirectAux {
+    #[prost(bytes, tag = "1")]
+    pub body_bytes: Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub public_key: Option<prost_types::Any>,
+    #[prost(string, tag = "3")]
+    pub chain_id: String,
+    #[prost(uint64, tag = "4")]
+    pub account_number: u64,
+    #[prost(uint64, tag = "5")]
+    pub sequence: u64,
+}
+
+/// a built `TxRaw`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuiltTx {
+    pub bytes: Vec<u8>,
+    pub base64: String,
+    /// tendermint tx hash: upper case hex of SHA256(tx bytes)
+    pub hash: String,
+}
+
+impl BuiltTx {
+    pub(crate) fn new(bytes: Vec<u8>) -> Self {
+        let hash = sha256::Hash::hash(&bytes);
+        Self {
+            base64: base64::encode(&bytes),
+            hash: hex::encode_upper(&hash[..]),
+            bytes,
+        }
+    }
+}
+
+/// an unsigned tx exported for offline signing, the bytes are base64 encoded
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UnsignedTx {
+    pub chain_id: String,
+    #[serde(serialize_with = "serde_to_str", deserialize_with = "deserde_from_str")]
+    pub account_number: u64,
+    #[serde(serialize_with = "serde_to_str", deserialize_with = "deserde_from_str")]
+    pub sequence: u64,
+    /// value of the protobuf `SignMode`
+    pub sign_mode: i32,
+    /// compressed secp256k1 public key of the signer
+    pub public_key: String,
+    /// the bytes to sign
+    pub sign_bytes: String,
+    pub body_bytes: String,
+    pub auth_info_bytes: String,
+}
+
+/// a signer of the tx besides the key service of the builder
+#[derive(Clone)]
+struct AdditionalSigner {
+    public_key: PublicKey,
+    account_number: u64,
+    sequence: u64,
+    /// None for an external signer, its signature is given by `set_signature`
+    key_service: Option<MemberKeyService>,
+    signature: Option<Vec<u8>>,
+    /// None to sign in the sign mode of the builder
+    sign_mode: Option<SignMode>,
+}
 
 pub struct TxBuilder<T: KeyService + Clone> {
     pub key_service: T,
-    chain_id: String,
+    chain_id: ChainId,
     messages: Vec<Msg>,
     memo: Option<String>,
     timeout_height: u64,
     account_number: u64,
     sequence: u64,
     fee: Option<Fee>,
+    signer_info: Option<SignerInfo>,
+    sign_mode: SignMode,
+    additional_signers: Vec<AdditionalSigner>,
+    gas_adjustment: f64,
+    gas_price: Option<GasPrice>,
+    chain_config: ChainConfig,
 }
 
+/// the encoded parts of the tx shared by the signers, computed once per build
+struct EncodedTx {
+    body_bytes: Vec<u8>,
+    auth_info_bytes: Vec<u8>,
+    /// the public key of the key service, None with a custom signer info
+    public_key: Option<PublicKey>,
+}
+
+/// default multiplier applied to the simulated gas
+const DEFAULT_GAS_ADJUSTMENT: f64 = 1.3;
+
 fn encode<T: prost::Message>(msg: &T) -> Result<Vec<u8>, Error> {
     let mut buf = vec![];
     prost::Message::encode(msg, &mut buf)?;
     Ok(buf)
 }
 
+fn pubkey_any(pk: &PublicKey, type_url: &str) -> Result<prost_types::Any, Error> {
+    pk.to_any_with_type_url(type_url)
+}
+
+fn decode_signature(signature_base64: String) -> Result<Vec<u8>, Error> {
+    base64::decode(signature_base64).map_err(|e| {
+        Error::SerializeError(format!("invalid base64 signature, decode error: {:?}", e))
+    })
+}
+
 impl<T: KeyService + Clone> TxBuilder<T> {
     pub fn new(
         key_service: T,
-        chain_id: String,
+        chain_id: ChainId,
         memo: Option<String>,
         timeout_height: u64,
         fee: Option<Fee>,
@@ -42,9 +171,25 @@ impl<T: KeyService + Clone> TxBuilder<T> {
             account_number: 0,
             sequence: 0,
             fee,
+            signer_info: None,
+            sign_mode: SignMode::Direct,
+            additional_signers: vec![],
+            gas_adjustment: DEFAULT_GAS_ADJUSTMENT,
+            gas_price: None,
+            chain_config: ChainConfig::default(),
         }
     }
 
+    /// the prefixes of the chain, the crypto.org mainnet by default
+    pub fn set_chain_config(&mut self, chain_config: ChainConfig) -> &mut Self {
+        self.chain_config = chain_config;
+        self
+    }
+
+    pub fn chain_config(&self) -> &ChainConfig {
+        &self.chain_config
+    }
+
     pub fn set_account_number(&mut self, account_number: u64) -> &mut Self {
         self.account_number = account_number;
         self
@@ -60,127 +205,793 @@ impl<T: KeyService + Clone> TxBuilder<T> {
         self
     }
 
-    pub fn pk_any(&self) -> Result<prost_types::Any, Error> {
-        let pk = self.key_service.public_key()?;
-        let mut buf = Vec::new();
-        prost::Message::encode(&pk.as_ref().serialize().to_vec(), &mut buf)?;
-        let pk_any = prost_types::Any {
-            type_url: "/cosmos.crypto.secp256k1.PubKey".to_string(),
-            value: buf,
+    pub fn set_timeout_height(&mut self, timeout_height: u64) -> &mut Self {
+        self.timeout_height = timeout_height;
+        self
+    }
+
+    /// set the timeout height to `blocks` after the latest block, so the tx
+    /// expires if it is not included by then, return the timeout height
+    pub async fn set_timeout_blocks<C: ClientApi>(
+        &mut self,
+        blocks: u64,
+        client: &C,
+    ) -> Result<u64, Error> {
+        let height = client.get_latest_height().await?;
+        self.timeout_height = height.checked_add(blocks).ok_or_else(|| {
+            Error::InputError(format!(
+                "timeout height overflow: {} blocks after {}",
+                blocks, height
+            ))
+        })?;
+        Ok(self.timeout_height)
+    }
+
+    /// the added messages, `Msg::typed` gives the decoded message
+    pub fn messages(&self) -> &[Msg] {
+        &self.messages
+    }
+
+    /// remove the added messages, so the builder can be reused for the next tx
+    pub fn clear_messages(&mut self) -> &mut Self {
+        self.messages.clear();
+        self
+    }
+
+    pub fn set_memo(&mut self, memo: Option<String>) -> &mut Self {
+        self.memo = memo;
+        self
+    }
+
+    pub fn set_chain_id(&mut self, chain_id: ChainId) -> &mut Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// set the chain id to the one of the node behind the client
+    pub async fn with_detected_chain_id<C: ClientApi>(
+        &mut self,
+        client: &C,
+    ) -> Result<&mut Self, Error> {
+        self.chain_id = client.get_node_info().await?.chain_id.parse()?;
+        Ok(self)
+    }
+
+    /// replace the fee, including the fee payer and granter
+    pub fn set_fee(&mut self, fee: Option<Fee>) -> &mut Self {
+        self.fee = fee;
+        self
+    }
+
+    /// replace the fee with the one of the fee builder
+    pub fn set_fee_builder(&mut self, fee: &FeeBuilder) -> Result<&mut Self, Error> {
+        self.fee = Some(fee.build()?);
+        Ok(self)
+    }
+
+    /// set the account which pays the fee, it must be one of the signers
+    pub fn set_fee_payer(&mut self, payer: &str) -> Result<&mut Self, Error> {
+        payer.parse::<AccountAddress>()?;
+        self.fee.get_or_insert_with(Default::default).payer = payer.to_string();
+        Ok(self)
+    }
+
+    /// set the account which pays the fee from its fee allowance, the granter must
+    /// have granted an allowance to the fee payer (x/feegrant `MsgGrantAllowance`)
+    pub fn set_fee_granter(&mut self, granter: &str) -> Result<&mut Self, Error> {
+        granter.parse::<AccountAddress>()?;
+        self.fee.get_or_insert_with(Default::default).granter = granter.to_string();
+        Ok(self)
+    }
+
+    /// the fee payer must be one of the signers
+    async fn check_fee_payer(&self) -> Result<(), Error> {
+        let payer = match &self.fee {
+            Some(fee) if !fee.payer.is_empty() => fee.payer.parse::<AccountAddress>()?.address(),
+            _ => return Ok(()),
+        };
+        if self.key_service.address().await? == payer {
+            return Ok(());
+        }
+        for signer in &self.additional_signers {
+            if signer.public_key.address()? == payer {
+                return Ok(());
+            }
+        }
+        Err(Error::InputError("fee payer is not a signer".to_string()))
+    }
+
+    /// set the multiplier applied to the simulated gas by `estimate_gas`,
+    /// it must be a finite number not less than 1.0
+    pub fn set_gas_adjustment(&mut self, gas_adjustment: f64) -> Result<&mut Self, Error> {
+        if !gas_adjustment.is_finite() || gas_adjustment < 1.0 {
+            return Err(Error::InputError(format!(
+                "invalid gas adjustment {}",
+                gas_adjustment
+            )));
+        }
+        self.gas_adjustment = gas_adjustment;
+        Ok(self)
+    }
+
+    /// set the gas price used by `estimate_gas` to calculate the fee amount
+    pub fn set_gas_price(&mut self, gas_price: GasPrice) -> &mut Self {
+        self.gas_price = Some(gas_price);
+        self
+    }
+
+    /// set the gas price to the minimum gas price of the node in the denom
+    pub async fn set_gas_price_from_node<C: ClientApi>(
+        &mut self,
+        client: &C,
+        denom: &str,
+    ) -> Result<&mut Self, Error> {
+        let gas_price = client
+            .get_min_gas_prices()
+            .await?
+            .into_iter()
+            .find(|gas_price| gas_price.denom == denom)
+            .ok_or_else(|| {
+                Error::ClientError(format!("the node has no minimum gas price in {}", denom))
+            })?;
+        self.gas_price = Some(gas_price);
+        Ok(self)
+    }
+
+    /// simulate the unsigned tx, then set the gas limit to the adjusted gas used and
+    /// the fee amount from the gas price, return the gas limit
+    pub async fn estimate_gas<C: ClientApi>(&mut self, client: &C) -> Result<u64, Error> {
+        let tx_raw = TxRaw {
+            body_bytes: self.raw_tx_body()?,
+            auth_info_bytes: encode(&self.auth_info().await?)?,
+            signatures: vec![vec![]; self.additional_signers.len() + 1],
         };
-        Ok(pk_any)
+        let gas_used = client.simulate(&encode(&tx_raw)?).await?.gas_used;
+        let gas_limit = (gas_used as f64 * self.gas_adjustment).ceil() as u64;
+        let gas_price = self.gas_price.clone();
+        let fee = self.fee.get_or_insert_with(Default::default);
+        fee.gas_limit = gas_limit;
+        if let Some(gas_price) = gas_price {
+            fee.amount = vec![gas_price.fee_coin(gas_limit)?];
+        }
+        Ok(gas_limit)
     }
 
-    pub fn raw_tx_body(&self) -> Result<Vec<u8>, Error> {
-        let body = TxBody {
+    /// sign and broadcast the tx in the mode, when the node rejects it with an account
+    /// sequence mismatch (code 32, in the check or the block result), refresh the sequence
+    /// from the node, re-sign and retry up to `max_retries` times
+    pub async fn sign_and_broadcast<C: ClientApi>(
+        &mut self,
+        client: &C,
+        mode: SyncMode,
+        max_retries: usize,
+    ) -> Result<TxResponse, Error> {
+        let mut retries = 0;
+        loop {
+            let tx = self.build_bytes().await?;
+            let response = client.broadcast_raw_tx(tx, mode.clone()).await?;
+            let expected = match response.error() {
+                Some(BroadcastError::SequenceMismatch { expected, .. })
+                    if retries < max_retries =>
+                {
+                    expected
+                }
+                _ => return Ok(response),
+            };
+            retries += 1;
+            let sequence = match expected {
+                Some(sequence) => sequence,
+                None => {
+                    let address = self.key_service.address().await?;
+                    client
+                        .get_account_info(&self.chain_config.account_address(&address))
+                        .await?
+                        .1
+                }
+            };
+            log::warn!(
+                "account sequence mismatch, retry {} with sequence {}",
+                retries,
+                sequence
+            );
+            self.sequence = sequence;
+        }
+    }
+
+    /// use a custom `SignerInfo` (e.g. from `MultisigKeyService::signer_info`)
+    /// instead of the single secp256k1 signer derived from the key service
+    pub fn set_signer_info(&mut self, signer_info: SignerInfo) -> &mut Self {
+        self.signer_info = Some(signer_info);
+        self
+    }
+
+    /// sign the protobuf `SignDoc` (SIGN_MODE_DIRECT, default) or the amino json
+    /// `StdSignDoc` (SIGN_MODE_LEGACY_AMINO_JSON) which can be displayed on a Ledger,
+    /// the additional signers use it too unless `set_signer_sign_mode` is called,
+    /// the broadcasted tx is always a protobuf `TxRaw`
+    pub fn set_sign_mode(&mut self, sign_mode: SignMode) -> &mut Self {
+        self.sign_mode = sign_mode;
+        self
+    }
+
+    /// the sign mode of an additional signer, `signer_index` starts from 1,
+    /// e.g. SIGN_MODE_DIRECT_AUX for a signer which doesn't pay the fee
+    pub fn set_signer_sign_mode(
+        &mut self,
+        signer_index: usize,
+        sign_mode: SignMode,
+    ) -> Result<&mut Self, Error> {
+        let signer = signer_index
+            .checked_sub(1)
+            .and_then(|i| self.additional_signers.get_mut(i))
+            .ok_or_else(|| Error::InputError(format!("no additional signer {}", signer_index)))?;
+        signer.sign_mode = Some(sign_mode);
+        Ok(self)
+    }
+
+    /// the sign mode of a signer
+    fn signer_sign_mode(&self, signer_index: usize) -> SignMode {
+        signer_index
+            .checked_sub(1)
+            .and_then(|i| self.additional_signers.get(i))
+            .and_then(|signer| signer.sign_mode)
+            .unwrap_or(self.sign_mode)
+    }
+
+    /// add another secp256k1 signer, the signers sign in the order they are added
+    /// after the key service of the builder (signer index 0)
+    pub async fn add_signer(
+        &mut self,
+        key_service: MemberKeyService,
+        account_number: u64,
+        sequence: u64,
+    ) -> Result<&mut Self, Error> {
+        let public_key = key_service.public_key().await?;
+        self.additional_signers.push(AdditionalSigner {
+            public_key,
+            account_number,
+            sequence,
+            key_service: Some(key_service),
+            signature: None,
+            sign_mode: None,
+        });
+        Ok(self)
+    }
+
+    /// add a signer which signs elsewhere, sign `sign_bytes_for` its signer index
+    /// and give the signature back with `set_signature`
+    pub fn add_external_signer(
+        &mut self,
+        public_key: PublicKey,
+        account_number: u64,
+        sequence: u64,
+    ) -> &mut Self {
+        self.additional_signers.push(AdditionalSigner {
+            public_key,
+            account_number,
+            sequence,
+            key_service: None,
+            signature: None,
+            sign_mode: None,
+        });
+        self
+    }
+
+    /// set the raw signature of an additional signer, `signer_index` starts from 1
+    pub fn set_signature(
+        &mut self,
+        signer_index: usize,
+        signature: Vec<u8>,
+    ) -> Result<&mut Self, Error> {
+        let signer = signer_index
+            .checked_sub(1)
+            .and_then(|i| self.additional_signers.get_mut(i))
+            .ok_or_else(|| Error::InputError(format!("no additional signer {}", signer_index)))?;
+        signer.signature = Some(signature);
+        Ok(self)
+    }
+
+    /// account number and sequence of the signer
+    fn signer_data(&self, signer_index: usize) -> Result<(u64, u64), Error> {
+        if signer_index == 0 {
+            return Ok((self.account_number, self.sequence));
+        }
+        self.additional_signers
+            .get(signer_index - 1)
+            .map(|signer| (signer.account_number, signer.sequence))
+            .ok_or_else(|| Error::InputError(format!("no signer {}", signer_index)))
+    }
+
+    pub async fn pk_any(&self) -> Result<prost_types::Any, Error> {
+        let pk = self.key_service.public_key().await?;
+        self.pk_any_of(&pk)
+    }
+
+    fn pk_any_of(&self, pk: &PublicKey) -> Result<prost_types::Any, Error> {
+        pubkey_any(pk, self.key_service.algorithm().pubkey_type_url())
+    }
+
+    pub fn tx_body(&self) -> TxBody {
+        TxBody {
             messages: self.messages.iter().map(|msg| msg.clone().into()).collect(),
             memo: self.memo.clone().unwrap_or_default(),
             timeout_height: self.timeout_height,
             extension_options: Default::default(),
             non_critical_extension_options: Default::default(),
-        };
+        }
+    }
+
+    pub fn raw_tx_body(&self) -> Result<Vec<u8>, Error> {
         // A protobuf serialization of a TxBody
         let mut body_buf = Vec::new();
-        prost::Message::encode(&body, &mut body_buf)?;
+        prost::Message::encode(&self.tx_body(), &mut body_buf)?;
         Ok(body_buf)
     }
 
-    pub fn auth_info(&self) -> Result<AuthInfo, Error> {
-        let single = mode_info::Single { mode: 1 };
-
-        let mode = Some(ModeInfo {
+    fn mode_info(&self, signer_index: usize) -> Option<ModeInfo> {
+        let single = mode_info::Single {
+            mode: self.signer_sign_mode(signer_index).as_i32(),
+        };
+        Some(ModeInfo {
             sum: Some(mode_info::Sum::Single(single)),
-        });
+        })
+    }
 
-        let pk_any = self.pk_any()?;
+    pub async fn auth_info(&self) -> Result<AuthInfo, Error> {
+        let (signer_info, _) = self.key_signer_info().await?;
+        self.auth_info_with(signer_info)
+    }
 
+    /// the signer info of the key service and its public key, the public key is
+    /// not queried with a custom signer info
+    async fn key_signer_info(&self) -> Result<(SignerInfo, Option<PublicKey>), Error> {
+        if let Some(signer_info) = &self.signer_info {
+            return Ok((signer_info.clone(), None));
+        }
+        let public_key = self.key_service.public_key().await?;
         let signer_info = SignerInfo {
-            public_key: Some(pk_any),
-            mode_info: mode,
+            public_key: Some(self.pk_any_of(&public_key)?),
+            mode_info: self.mode_info(0),
             sequence: self.sequence,
         };
+        Ok((signer_info, Some(public_key)))
+    }
+
+    /// the `AuthInfo` with the signer info of the key service first
+    fn auth_info_with(&self, signer_info: SignerInfo) -> Result<AuthInfo, Error> {
+        let mut signer_infos = vec![signer_info];
+        for (i, signer) in self.additional_signers.iter().enumerate() {
+            signer_infos.push(SignerInfo {
+                public_key: Some(pubkey_any(&signer.public_key, SECP256K1_PUBKEY_TYPE_URL)?),
+                mode_info: self.mode_info(i + 1),
+                sequence: signer.sequence,
+            });
+        }
 
         Ok(AuthInfo {
-            signer_infos: vec![signer_info],
+            signer_infos,
             fee: self.fee.clone(),
         })
     }
 
-    pub fn create_msg(&self, to_address: String, amount: Coin) -> Result<Msg, Error> {
-        let from_address = self.key_service.address()?;
-        let address_str = from_address.to_bech32(ACCOUNT_ADDRESS_PREFIX);
+    /// the `MsgSend` from the key service, `to_address` must have the account prefix
+    /// of the chain config
+    pub async fn create_msg(&self, to_address: String, amount: Coin) -> Result<Msg, Error> {
+        let prefix = &self.chain_config.account_prefix;
+        let to_address = AccountAddress::from_bech32_with_prefix(&to_address, prefix)?;
+        let from_address = self.chain_config.account(self.key_service.address().await?);
         let msg = MsgSend {
-            from_address: address_str,
-            to_address,
+            from_address: from_address.to_string(),
+            to_address: to_address.to_string(),
             amount: vec![amount],
         };
-        let buf = encode(&msg)?;
-        let any = prost_types::Any {
-            type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
-            value: buf,
+        Ok(Msg::from(msg))
+    }
+
+    pub async fn sign_doc(&self) -> Result<SignDoc, Error> {
+        self.sign_doc_for(0).await
+    }
+
+    /// the `SignDoc` of a signer, only the account number differs between the signers
+    pub async fn sign_doc_for(&self, signer_index: usize) -> Result<SignDoc, Error> {
+        self.sign_doc_with(&self.encode_tx().await?, signer_index)
+    }
+
+    fn sign_doc_with(&self, encoded: &EncodedTx, signer_index: usize) -> Result<SignDoc, Error> {
+        let (account_number, _) = self.signer_data(signer_index)?;
+        Ok(SignDoc {
+            body_bytes: encoded.body_bytes.clone(),
+            auth_info_bytes: encoded.auth_info_bytes.clone(),
+            chain_id: self.chain_id.to_string(),
+            account_number,
+        })
+    }
+
+    /// the `SignDocDirectAux` of a signer
+    pub async fn sign_doc_direct_aux_for(
+        &self,
+        signer_index: usize,
+    ) -> Result<SignDocDirectAux, Error> {
+        self.sign_doc_direct_aux_with(&self.encode_tx().await?, signer_index)
+            .await
+    }
+
+    async fn sign_doc_direct_aux_with(
+        &self,
+        encoded: &EncodedTx,
+        signer_index: usize,
+    ) -> Result<SignDocDirectAux, Error> {
+        let (account_number, sequence) = self.signer_data(signer_index)?;
+        let public_key = match (signer_index, &encoded.public_key) {
+            (0, Some(public_key)) => self.pk_any_of(public_key)?,
+            (0, None) => self.pk_any().await?,
+            _ => pubkey_any(
+                &self.additional_signers[signer_index - 1].public_key,
+                SECP256K1_PUBKEY_TYPE_URL,
+            )?,
         };
-        Ok(Msg::from(any))
+        Ok(SignDocDirectAux {
+            body_bytes: encoded.body_bytes.clone(),
+            public_key: Some(public_key),
+            chain_id: self.chain_id.to_string(),
+            account_number,
+            sequence,
+        })
     }
 
-    pub fn sign_doc(&self) -> Result<SignDoc, Error> {
-        let body_bytes = self.raw_tx_body()?;
-        let auth_info_bytes = encode(&self.auth_info()?)?;
-        let sign_doc = SignDoc {
-            body_bytes: body_bytes.clone(),
-            auth_info_bytes: auth_info_bytes.clone(),
-            chain_id: self.chain_id.clone(),
+    /// the amino json `StdSignDoc`, sorted and compact
+    pub fn amino_sign_doc(&self) -> Result<Vec<u8>, Error> {
+        self.amino_sign_doc_for(0)
+    }
+
+    /// the amino json `StdSignDoc` of a signer
+    pub fn amino_sign_doc_for(&self, signer_index: usize) -> Result<Vec<u8>, Error> {
+        let (account_number, sequence) = self.signer_data(signer_index)?;
+        let fee = self.fee.clone().unwrap_or_default();
+        let fee_amount: Vec<serde_json::Value> = fee
+            .amount
+            .iter()
+            .map(|coin| json!({"amount": coin.amount, "denom": coin.denom}))
+            .collect();
+        let msgs = self
+            .messages
+            .iter()
+            .map(|msg| msg.amino_json())
+            .collect::<Result<Vec<_>, Error>>()?;
+        let mut sign_doc = json!({
+            "account_number": account_number.to_string(),
+            "chain_id": self.chain_id,
+            "fee": {
+                "amount": fee_amount,
+                "gas": fee.gas_limit.to_string(),
+            },
+            "memo": self.memo.clone().unwrap_or_default(),
+            "msgs": msgs,
+            "sequence": sequence.to_string(),
+        });
+        if self.timeout_height != 0 {
+            sign_doc["timeout_height"] = json!(self.timeout_height.to_string());
+        }
+        if !fee.granter.is_empty() {
+            sign_doc["fee"]["granter"] = json!(fee.granter);
+        }
+        if !fee.payer.is_empty() {
+            sign_doc["fee"]["payer"] = json!(fee.payer);
+        }
+        Ok(canonical_json(&sign_doc).into_bytes())
+    }
+
+    /// the bytes to be signed in the sign mode
+    pub async fn sign_bytes(&self) -> Result<Vec<u8>, Error> {
+        self.sign_bytes_for(0).await
+    }
+
+    /// the bytes to be signed by a signer in the sign mode
+    pub async fn sign_bytes_for(&self, signer_index: usize) -> Result<Vec<u8>, Error> {
+        match self.signer_sign_mode(signer_index) {
+            // the amino sign doc doesn't use the encoded tx
+            SignMode::LegacyAminoJson => self.amino_sign_doc_for(signer_index),
+            SignMode::Direct | SignMode::DirectAux => {
+                self.sign_bytes_with(&self.encode_tx().await?, signer_index)
+                    .await
+            }
+        }
+    }
+
+    async fn sign_bytes_with(
+        &self,
+        encoded: &EncodedTx,
+        signer_index: usize,
+    ) -> Result<Vec<u8>, Error> {
+        match self.signer_sign_mode(signer_index) {
+            SignMode::Direct => encode(&self.sign_doc_with(encoded, signer_index)?),
+            SignMode::LegacyAminoJson => self.amino_sign_doc_for(signer_index),
+            SignMode::DirectAux => {
+                encode(&self.sign_doc_direct_aux_with(encoded, signer_index).await?)
+            }
+        }
+    }
+
+    /// encode the body and the auth info once for all the signers of a build
+    async fn encode_tx(&self) -> Result<EncodedTx, Error> {
+        let (signer_info, public_key) = self.key_signer_info().await?;
+        Ok(EncodedTx {
+            body_bytes: self.raw_tx_body()?,
+            auth_info_bytes: encode(&self.auth_info_with(signer_info)?)?,
+            public_key,
+        })
+    }
+
+    /// the raw signature of a signer
+    async fn signature(&self, encoded: &EncodedTx, signer_index: usize) -> Result<Vec<u8>, Error> {
+        if signer_index == 0 {
+            let sign_bytes = self.sign_bytes_with(encoded, 0).await?;
+            return decode_signature(self.key_service.sign(&sign_bytes).await?);
+        }
+        let signer = &self.additional_signers[signer_index - 1];
+        match (&signer.signature, &signer.key_service) {
+            (Some(signature), _) => Ok(signature.clone()),
+            (None, Some(key_service)) => {
+                let sign_bytes = self.sign_bytes_with(encoded, signer_index).await?;
+                decode_signature(key_service.sign(&sign_bytes).await?)
+            }
+            (None, None) => Err(Error::InputError(format!(
+                "missing signature of signer {}",
+                signer_index
+            ))),
+        }
+    }
+
+    /// the bytes to be signed offline by the key of the builder
+    pub async fn unsigned_bytes(&self) -> Result<Vec<u8>, Error> {
+        self.sign_bytes_for(0).await
+    }
+
+    /// everything needed to sign the tx on an airgapped machine, in json format
+    pub async fn unsigned_json(&self) -> Result<String, Error> {
+        let encoded = self.encode_tx().await?;
+        let public_key = match &encoded.public_key {
+            Some(public_key) => public_key.clone(),
+            None => self.key_service.public_key().await?,
+        };
+        let unsigned_tx = UnsignedTx {
+            chain_id: self.chain_id.to_string(),
             account_number: self.account_number,
+            sequence: self.sequence,
+            sign_mode: self.sign_mode.as_i32(),
+            public_key: base64::encode(public_key.as_ref().serialize()),
+            sign_bytes: base64::encode(self.sign_bytes_with(&encoded, 0).await?),
+            body_bytes: base64::encode(&encoded.body_bytes),
+            auth_info_bytes: base64::encode(&encoded.auth_info_bytes),
         };
-        Ok(sign_doc)
+        serde_json::to_string(&unsigned_tx).map_err(|e| Error::SerializeError(e.to_string()))
     }
 
+    /// the partial tx which the members of the multisig account sign in
+    /// SIGN_MODE_LEGACY_AMINO_JSON and pass around, see `PartialTx::finalize`
+    pub fn partial_tx(&self, multisig: &MultisigKeyService) -> Result<PartialTx, Error> {
+        self.validate()?;
+        PartialTx::new(
+            multisig,
+            self.chain_id.to_string(),
+            self.account_number,
+            self.sequence,
+            &self.amino_sign_doc()?,
+            &self.raw_tx_body()?,
+            &self.fee.clone().unwrap_or_default(),
+        )
+    }
+
+    /// assemble the `TxRaw` with the raw signature of `unsigned_bytes` produced offline
+    pub async fn build_with_signature(&self, signature: Vec<u8>) -> Result<String, Error> {
+        let bytes = self.assemble(Some(signature)).await?;
+        Ok(base64::encode(bytes))
+    }
+
+    /// build the tx, return the base64 encoded `TxRaw`
     pub async fn build(&self) -> Result<String, Error> {
-        let sign_doc = self.sign_doc()?;
-        let signdoc_buf = encode(&sign_doc)?;
-        let signature_base64 = self.key_service.sign(&signdoc_buf).await?;
-        let signature = base64::decode(signature_base64).map_err(|e| {
-            Error::SerializeError(format!("invalid base64 signature, decode error: {:?}", e))
-        })?;
-        let body_bytes = self.raw_tx_body()?;
-        let auth_info_bytes = encode(&self.auth_info()?)?;
+        let bytes = self.build_bytes().await?;
+        Ok(base64::encode(bytes))
+    }
+
+    /// build the tx, return the encoded `TxRaw` which can be broadcast directly
+    pub async fn build_bytes(&self) -> Result<Vec<u8>, Error> {
+        self.assemble(None).await
+    }
+
+    /// size of the encoded `TxRaw` before signing, each signature is counted as 64 bytes
+    pub async fn estimate_size(&self) -> Result<usize, Error> {
         let tx_raw = TxRaw {
-            body_bytes,
-            auth_info_bytes,
-            signatures: vec![signature],
+            body_bytes: self.raw_tx_body()?,
+            auth_info_bytes: encode(&self.auth_info().await?)?,
+            signatures: vec![vec![0; 64]; self.additional_signers.len() + 1],
+        };
+        Ok(prost::Message::encoded_len(&tx_raw))
+    }
+
+    /// build the tx and compute its tendermint hash, so it can be tracked before broadcast
+    pub async fn build_with_hash(&self) -> Result<BuiltTx, Error> {
+        let bytes = self.assemble(None).await?;
+        Ok(BuiltTx::new(bytes))
+    }
+
+    /// reject the txs which the node would refuse anyway
+    fn validate(&self) -> Result<(), Error> {
+        if self.messages.is_empty() {
+            return Err(Error::InputError("tx has no message".to_string()));
+        }
+        // the fee payer must sign the fee
+        if self.sign_mode == SignMode::DirectAux {
+            return Err(Error::InputError(
+                "the fee payer (signer 0) can't sign in SIGN_MODE_DIRECT_AUX".to_string(),
+            ));
+        }
+        for msg in &self.messages {
+            msg.validate()?;
+        }
+        let memo_len = self
+            .memo
+            .as_ref()
+            .map(|memo| memo.len())
+            .unwrap_or_default();
+        if memo_len > MAX_MEMO_LENGTH {
+            return Err(Error::InputError(format!(
+                "memo is too long, {} > {} bytes",
+                memo_len, MAX_MEMO_LENGTH
+            )));
+        }
+        if let Some(fee) = &self.fee {
+            if fee.gas_limit == 0 && !fee.amount.is_empty() {
+                return Err(Error::InputError("fee is set with zero gas".to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    async fn assemble(&self, signature: Option<Vec<u8>>) -> Result<Vec<u8>, Error> {
+        self.validate()?;
+        self.check_fee_payer().await?;
+        let encoded = self.encode_tx().await?;
+        // the signatures are in the order of the signer infos
+        let mut signatures = vec![];
+        for signer_index in 0..=self.additional_signers.len() {
+            match (signer_index, &signature) {
+                (0, Some(signature)) => signatures.push(signature.clone()),
+                _ => signatures.push(self.signature(&encoded, signer_index).await?),
+            }
+        }
+        let tx_raw = TxRaw {
+            body_bytes: encoded.body_bytes,
+            auth_info_bytes: encoded.auth_info_bytes,
+            signatures,
         };
-        let bytes = encode(&tx_raw)?;
-        let string_b64 = base64::encode(bytes);
-        Ok(string_b64)
+        encode(&tx_raw)
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::client::mock::MockClient;
     use crate::hd_wallet::mnemonic::Mnemonic;
     use crate::key_service::private_key_service::PrivateKeyService;
+    use crate::message::TypedMsg;
+    use crate::types::basic::{Amount, Denom};
+    use crate::types::decoded_tx::DecodedTx;
+    use crate::types::node_info::NodeInfo;
     use prost::Message;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_pubkey_any() {
+        let pk =
+            PublicKey::from_base64_str("AntL+UxMyJ9NZ9DGLp2v7a3dlSxiNXMaItyOXSRw8iYi").unwrap();
+        let any = pk.to_any().unwrap();
+        assert_eq!(any.type_url, SECP256K1_PUBKEY_TYPE_URL);
+        let decoded =
+            crate::proto::cosmos::crypto::secp256k1::PubKey::decode(any.value.as_slice()).unwrap();
+        assert_eq!(decoded.key, pk.as_ref().serialize().to_vec());
+
+        // the bytes are the same as the raw key encoded as the field 1 before
+        let mut legacy = Vec::new();
+        pk.as_ref()
+            .serialize()
+            .to_vec()
+            .encode(&mut legacy)
+            .unwrap();
+        assert_eq!(any.value, legacy);
+    }
 
     #[tokio::test]
-    async fn test_tx_buider() {
+    async fn test_validate() {
         let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
         let mnemonic = Mnemonic::from_str(words, None).unwrap();
         let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
-        let chain_id = "test".into();
         let fee = Fee {
             amount: vec![Coin {
                 denom: "basecro".to_string(),
                 amount: 10000.to_string(),
             }],
-            gas_limit: 300000,
+            gas_limit: 0,
             payer: "".to_string(),
             granter: "".to_string(),
         };
+        let mut builder = TxBuilder::new(key_service, "test".parse().unwrap(), None, 0, Some(fee));
+        assert!(builder.build().await.is_err());
+
+        let amount = Coin {
+            denom: "basecro".to_string(),
+            amount: 1.to_string(),
+        };
+        let msg = builder
+            .create_msg("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd".into(), amount)
+            .await
+            .unwrap();
+        builder.add_message(msg);
+        // zero gas with fee
+        assert!(builder.build().await.is_err());
+
+        builder
+            .set_fee(None)
+            .set_memo(Some("m".repeat(MAX_MEMO_LENGTH + 1)));
+        assert!(builder.build().await.is_err());
+        builder.set_memo(Some("m".repeat(MAX_MEMO_LENGTH)));
+        assert!(builder.build().await.is_ok());
+
+        for invalid in &[0.0, 0.99, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(builder.set_gas_adjustment(*invalid).is_err(), "{}", invalid);
+        }
+        assert!(builder.set_gas_adjustment(1.0).is_ok());
+        assert!(builder.set_gas_adjustment(1.5).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_detected_chain_id() {
+        let mock = MockClient::new();
+        let mut node_info = NodeInfo {
+            chain_id: "crypto-org-chain-mainnet-1".to_string(),
+            moniker: "node".to_string(),
+            node_version: "0.34.0".to_string(),
+            app_version: String::new(),
+            syncing: false,
+        };
+        mock.set_node_info(node_info.clone());
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+        let mut builder = TxBuilder::new(key_service, "test".parse().unwrap(), None, 0, None);
+        builder.with_detected_chain_id(&mock).await.unwrap();
+        assert_eq!(
+            builder.sign_doc().await.unwrap().chain_id,
+            node_info.chain_id
+        );
+
+        // the chain id of a misconfigured node is rejected, the previous one is kept
+        node_info.chain_id = String::new();
+        mock.set_node_info(node_info);
+        assert!(builder.with_detected_chain_id(&mock).await.is_err());
+        assert_eq!(
+            builder.sign_doc().await.unwrap().chain_id,
+            "crypto-org-chain-mainnet-1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tx_buider() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+        let chain_id = "test".parse().unwrap();
+        let fee = FeeBuilder::new()
+            .set_gas_limit(300000)
+            .add_amount(Amount::new(10000, Denom::Basecro))
+            .build()
+            .unwrap();
         let mut builder = TxBuilder::new(key_service, chain_id, None, 1, Some(fee));
         builder.set_account_number(9).set_sequence(4);
 
         // test public key
-        let pk_any = builder.pk_any().unwrap();
+        let pk_any = builder.pk_any().await.unwrap();
         let pk_buf = vec![
             10, 33, 2, 123, 75, 249, 76, 76, 200, 159, 77, 103, 208, 198, 46, 157, 175, 237, 173,
             221, 149, 44, 98, 53, 115, 26, 34, 220, 142, 93, 36, 112, 242, 38, 34,
@@ -194,7 +1005,7 @@ mod test {
         );
 
         // test auth info
-        let auth_info = builder.auth_info().unwrap();
+        let auth_info = builder.auth_info().await.unwrap();
         let auth_info_bytes = vec![
             10, 80, 10, 70, 10, 31, 47, 99, 111, 115, 109, 111, 115, 46, 99, 114, 121, 112, 116,
             111, 46, 115, 101, 99, 112, 50, 53, 54, 107, 49, 46, 80, 117, 98, 75, 101, 121, 18, 35,
@@ -211,11 +1022,11 @@ mod test {
             denom: "basecro".into(),
             amount: 100000000.to_string(),
         };
-        let msg = builder.create_msg(to_address, amount).unwrap();
+        let msg = builder.create_msg(to_address, amount).await.unwrap();
         builder.add_message(msg);
 
         // test signature
-        let sign_doc = builder.sign_doc().unwrap();
+        let sign_doc = builder.sign_doc().await.unwrap();
         let raw_sign_doc = encode(&sign_doc).unwrap();
         let signature = builder.key_service.sign(&raw_sign_doc).await.unwrap();
         assert_eq!(signature, "jlqBo5nxRbq2RIYpjo4+gjevBEDALw+IjmqEPu4igfIgD8l4/CR3vmetHvhpyeQaYZ/bJJfehT6Z/RpxofJnxA==");
@@ -224,5 +1035,400 @@ mod test {
         let tx = builder.build().await.unwrap();
         let tx_expect = "CpMBCo4BChwvY29zbW9zLmJhbmsudjFiZXRhMS5Nc2dTZW5kEm4KKmNybzF1OXE4bWZwemh5djJzNDNqczdsNXFzZWFweDVrdDNnMnJmN3BwZhIqY3JvMWZqNmpwbXV5a3ZyYTRreHJ3MGNwMjBlNHZ4NHI4ZWRhOHEzeW45GhQKB2Jhc2Vjcm8SCTEwMDAwMDAwMBgBEmoKUApGCh8vY29zbW9zLmNyeXB0by5zZWNwMjU2azEuUHViS2V5EiMKIQJ7S/lMTMifTWfQxi6dr+2t3ZUsYjVzGiLcjl0kcPImIhIECgIIARgEEhYKEAoHYmFzZWNybxIFMTAwMDAQ4KcSGkCOWoGjmfFFurZEhimOjj6CN68EQMAvD4iOaoQ+7iKB8iAPyXj8JHe+Z60e+GnJ5Bphn9skl96FPpn9GnGh8mfE";
         assert_eq!(tx, tx_expect);
+        assert_eq!(
+            builder.estimate_size().await.unwrap(),
+            base64::decode(&tx).unwrap().len()
+        );
+
+        // test decode
+        let decoded = DecodedTx::decode_base64(&tx).unwrap();
+        assert_eq!(decoded.memo(), "");
+        assert_eq!(decoded.fee().unwrap().gas_limit, 300000);
+        match &decoded.messages().unwrap()[0] {
+            TypedMsg::Send(msg) => {
+                assert_eq!(msg.to_address, "cro1fj6jpmuykvra4kxrw0cp20e4vx4r8eda8q3yn9")
+            }
+            _ => panic!("expect MsgSend"),
+        }
+        let signers = decoded.signers().unwrap();
+        assert_eq!(signers.len(), 1);
+        assert_eq!(signers[0].sequence, 4);
+        assert_eq!(
+            signers[0].public_key,
+            Some(builder.key_service.public_key().await.unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_amino_sign_doc() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+        let fee = FeeBuilder::new()
+            .set_gas_limit(300000)
+            .add_amount(Amount::new(10000, Denom::Basecro))
+            .build()
+            .unwrap();
+        let mut builder = TxBuilder::new(key_service, "test".parse().unwrap(), None, 0, Some(fee));
+        builder
+            .set_account_number(9)
+            .set_sequence(4)
+            .set_sign_mode(SignMode::LegacyAminoJson);
+        let amount = Coin {
+            denom: "basecro".into(),
+            amount: 100000000.to_string(),
+        };
+        let msg = builder
+            .create_msg("cro1fj6jpmuykvra4kxrw0cp20e4vx4r8eda8q3yn9".into(), amount)
+            .await
+            .unwrap();
+        builder.add_message(msg);
+
+        let sign_doc = String::from_utf8(builder.amino_sign_doc().unwrap()).unwrap();
+        let sign_doc_expect = r#"{"account_number":"9","chain_id":"test","fee":{"amount":[{"amount":"10000","denom":"basecro"}],"gas":"300000"},"memo":"","msgs":[{"type":"cosmos-sdk/MsgSend","value":{"amount":[{"amount":"100000000","denom":"basecro"}],"from_address":"cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf","to_address":"cro1fj6jpmuykvra4kxrw0cp20e4vx4r8eda8q3yn9"}}],"sequence":"4"}"#;
+        assert_eq!(sign_doc, sign_doc_expect);
+
+        let auth_info = builder.auth_info().await.unwrap();
+        assert_eq!(
+            auth_info.signer_infos[0].mode_info,
+            Some(ModeInfo {
+                sum: Some(mode_info::Sum::Single(mode_info::Single { mode: 127 })),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multi_signer() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic_with_index(mnemonic, 0, 0).unwrap();
+        let second = key_service.derive_child(1).unwrap();
+        let second_pk = second.public_key().await.unwrap();
+
+        let mut builder = TxBuilder::new(key_service, "test".parse().unwrap(), None, 0, None);
+        let amount = Coin {
+            denom: "basecro".to_string(),
+            amount: 1.to_string(),
+        };
+        let msg = builder
+            .create_msg("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd".into(), amount)
+            .await
+            .unwrap();
+        builder.add_message(msg);
+        builder.set_account_number(9).set_sequence(4);
+        builder.add_external_signer(second_pk, 10, 2);
+        assert!(builder.build().await.is_err());
+
+        let sign_doc = builder.sign_doc_for(1).await.unwrap();
+        assert_eq!(sign_doc.account_number, 10);
+        let sign_bytes = builder.sign_bytes_for(1).await.unwrap();
+        let signature =
+            base64::decode(KeyService::sign(&second, &sign_bytes).await.unwrap()).unwrap();
+        builder.set_signature(1, signature.clone()).unwrap();
+        assert!(builder.set_signature(2, signature.clone()).is_err());
+
+        let auth_info = builder.auth_info().await.unwrap();
+        assert_eq!(auth_info.signer_infos.len(), 2);
+        assert_eq!(auth_info.signer_infos[1].sequence, 2);
+
+        let tx = base64::decode(builder.build().await.unwrap()).unwrap();
+        let tx_raw = TxRaw::decode(&*tx).unwrap();
+        assert_eq!(tx_raw.signatures.len(), 2);
+        assert_eq!(tx_raw.signatures[1], signature);
+    }
+
+    /// the tx as it was assembled before the encoded body and auth info were shared
+    /// between the signers: each part encoded again from the public accessors
+    async fn reference_tx<T: KeyService + Clone>(
+        builder: &TxBuilder<T>,
+        signers: &[(&PrivateKeyService, u64)],
+    ) -> Vec<u8> {
+        let body_bytes = builder.raw_tx_body().unwrap();
+        let auth_info_bytes = encode(&builder.auth_info().await.unwrap()).unwrap();
+        let mut signatures = vec![];
+        for (key_service, account_number) in signers {
+            let sign_doc = SignDoc {
+                body_bytes: body_bytes.clone(),
+                auth_info_bytes: auth_info_bytes.clone(),
+                chain_id: "test".to_string(),
+                account_number: *account_number,
+            };
+            let signature = KeyService::sign(*key_service, &encode(&sign_doc).unwrap())
+                .await
+                .unwrap();
+            signatures.push(base64::decode(signature).unwrap());
+        }
+        encode(&TxRaw {
+            body_bytes,
+            auth_info_bytes,
+            signatures,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_encoded_tx_unchanged() {
+        use crate::key_service::mock::{MockKeyService, MOCK_PRIVATE_KEY};
+        use crate::types::key::PrivateKey;
+
+        let private_key =
+            PrivateKey::from_slice(&base64::decode(MOCK_PRIVATE_KEY).unwrap()).unwrap();
+        let key_service = MockKeyService::default();
+        let reference_key = PrivateKeyService::new(private_key);
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let second = PrivateKeyService::new_from_mnemonic_with_index(mnemonic, 0, 1).unwrap();
+
+        let mut builder =
+            TxBuilder::new(key_service.clone(), "test".parse().unwrap(), None, 0, None);
+        let amount = Coin {
+            denom: "basecro".to_string(),
+            amount: 1.to_string(),
+        };
+        let msg = builder
+            .create_msg("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd".into(), amount)
+            .await
+            .unwrap();
+        builder.add_message(msg);
+        builder.set_account_number(9).set_sequence(4);
+
+        // a single signer
+        let expected = reference_tx(&builder, &[(&reference_key, 9)]).await;
+        let calls = key_service.public_key_count();
+        assert_eq!(builder.build_bytes().await.unwrap(), expected);
+        assert_eq!(key_service.public_key_count(), calls + 1);
+
+        let public_key = reference_key.public_key().await.unwrap();
+        let body_bytes = builder.raw_tx_body().unwrap();
+        let auth_info_bytes = encode(&builder.auth_info().await.unwrap()).unwrap();
+        let sign_doc = SignDoc {
+            body_bytes: body_bytes.clone(),
+            auth_info_bytes: auth_info_bytes.clone(),
+            chain_id: "test".to_string(),
+            account_number: 9,
+        };
+        let expected = UnsignedTx {
+            chain_id: "test".to_string(),
+            account_number: 9,
+            sequence: 4,
+            sign_mode: SignMode::Direct.as_i32(),
+            public_key: base64::encode(public_key.as_ref().serialize()),
+            sign_bytes: base64::encode(encode(&sign_doc).unwrap()),
+            body_bytes: base64::encode(body_bytes),
+            auth_info_bytes: base64::encode(auth_info_bytes),
+        };
+        let calls = key_service.public_key_count();
+        assert_eq!(
+            builder.unsigned_json().await.unwrap(),
+            serde_json::to_string(&expected).unwrap()
+        );
+        assert_eq!(key_service.public_key_count(), calls + 1);
+
+        // several signers sign the same body and auth info
+        builder
+            .add_signer(Arc::new(second.clone()), 10, 2)
+            .await
+            .unwrap();
+        let expected = reference_tx(&builder, &[(&reference_key, 9), (&second, 10)]).await;
+        let calls = key_service.public_key_count();
+        assert_eq!(builder.build_bytes().await.unwrap(), expected);
+        assert_eq!(key_service.public_key_count(), calls + 1);
+    }
+
+    #[tokio::test]
+    async fn test_set_timeout_blocks() {
+        use crate::client::mock::MockClient;
+        use crate::types::block::{Block, BlockHeader};
+
+        let client = MockClient::new();
+        client.add_block(Block {
+            hash: String::new(),
+            header: BlockHeader {
+                chain_id: "test".to_string(),
+                height: 100,
+                time: String::new(),
+                proposer_address: String::new(),
+            },
+            txs: vec![],
+        });
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+        let mut builder = TxBuilder::new(key_service, "test".parse().unwrap(), None, 0, None);
+        assert_eq!(builder.set_timeout_blocks(50, &client).await.unwrap(), 150);
+        assert!(builder.set_timeout_blocks(u64::MAX, &client).await.is_err());
+        assert_eq!(builder.tx_body().timeout_height, 150);
+    }
+
+    #[tokio::test]
+    async fn test_signer_sign_mode() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic_with_index(mnemonic, 0, 0).unwrap();
+        let second = key_service.derive_child(1).unwrap();
+
+        let mut builder = TxBuilder::new(key_service, "test".parse().unwrap(), None, 0, None);
+        let amount = Coin {
+            denom: "basecro".to_string(),
+            amount: 1.to_string(),
+        };
+        let msg = builder
+            .create_msg("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd".into(), amount)
+            .await
+            .unwrap();
+        builder.add_message(msg);
+        builder.add_signer(Arc::new(second), 10, 2).await.unwrap();
+        assert!(builder
+            .set_signer_sign_mode(2, SignMode::DirectAux)
+            .is_err());
+        builder
+            .set_signer_sign_mode(1, SignMode::DirectAux)
+            .unwrap();
+
+        let mode = |mode| {
+            Some(ModeInfo {
+                sum: Some(mode_info::Sum::Single(mode_info::Single { mode })),
+            })
+        };
+        let tx = base64::decode(builder.build().await.unwrap()).unwrap();
+        let tx_raw = TxRaw::decode(&*tx).unwrap();
+        let auth_info = AuthInfo::decode(&*tx_raw.auth_info_bytes).unwrap();
+        assert_eq!(auth_info.signer_infos[0].mode_info, mode(1));
+        assert_eq!(auth_info.signer_infos[1].mode_info, mode(3));
+
+        // the fee payer can't leave the fee out of its signature
+        builder.set_sign_mode(SignMode::DirectAux);
+        assert!(builder.build().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_broadcast_retry() {
+        use crate::client::mock::MockClient;
+
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+        let mut builder = TxBuilder::new(key_service, "test".parse().unwrap(), None, 0, None);
+        let amount = Coin {
+            denom: "basecro".to_string(),
+            amount: 1.to_string(),
+        };
+        let msg = builder
+            .create_msg("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd".into(), amount)
+            .await
+            .unwrap();
+        builder.add_message(msg);
+        builder.set_account_number(9).set_sequence(25);
+
+        let client = MockClient::new();
+        client.push_tx_response(TxResponse {
+            height: 0,
+            txhash: String::new(),
+            codespace: "sdk".to_string(),
+            code: 32,
+            raw_log: "account sequence mismatch, expected 26, got 25: incorrect account sequence"
+                .to_string(),
+            gas_wanted: 0,
+            gas_used: 0,
+        });
+        builder
+            .sign_and_broadcast(&client, SyncMode::Sync, 1)
+            .await
+            .unwrap();
+        assert_eq!(builder.sequence, 26);
+        let broadcasted = client.broadcasted_txs();
+        assert_eq!(broadcasted.len(), 2);
+        let decoded = DecodedTx::decode(&broadcasted[1]).unwrap();
+        assert_eq!(decoded.auth_info.signer_infos[0].sequence, 26);
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_broadcast() {
+        use crate::client::mock::MockClient;
+        use crate::types::account::{Account, BaseAccount};
+
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+        let mut builder = TxBuilder::new(key_service, "test".parse().unwrap(), None, 0, None);
+        let amount = Coin {
+            denom: "basecro".to_string(),
+            amount: 1.to_string(),
+        };
+        let msg = builder
+            .create_msg("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd".into(), amount)
+            .await
+            .unwrap();
+        builder.add_message(msg);
+        builder.set_account_number(9).set_sequence(25);
+
+        // the signed tx is broadcast once and the response of the node is returned
+        let client = MockClient::new();
+        let response = |code: u32, raw_log: &str| TxResponse {
+            height: 0,
+            txhash: "A1".to_string(),
+            codespace: if code == 0 { "" } else { "sdk" }.to_string(),
+            code,
+            raw_log: raw_log.to_string(),
+            gas_wanted: 200_000,
+            gas_used: 0,
+        };
+        let accepted = response(0, "");
+        client.push_tx_response(accepted.clone());
+        let result = builder
+            .sign_and_broadcast(&client, SyncMode::Sync, 3)
+            .await
+            .unwrap();
+        assert_eq!(result, accepted);
+        assert_eq!(
+            client.broadcasted_txs(),
+            vec![builder.build_bytes().await.unwrap()]
+        );
+
+        // without the expected sequence in the log, the sequence is queried from the node
+        let mismatch = |raw_log: &str| response(32, raw_log);
+        let client = MockClient::new();
+        client.set_account(Account::BaseAccount(BaseAccount {
+            address: "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf".to_string(),
+            pub_key: None,
+            account_number: 9,
+            sequence: 30,
+        }));
+        client.push_tx_response(mismatch("incorrect account sequence"));
+        builder
+            .sign_and_broadcast(&client, SyncMode::Sync, 1)
+            .await
+            .unwrap();
+        assert_eq!(builder.sequence, 30);
+        let broadcasted = client.broadcasted_txs();
+        assert_eq!(broadcasted.len(), 2);
+        assert_eq!(broadcasted[1], builder.build_bytes().await.unwrap());
+
+        // the mismatch is returned once the retries are used up
+        let client = MockClient::new();
+        let rejected =
+            mismatch("account sequence mismatch, expected 31, got 30: incorrect account sequence");
+        client.push_tx_response(rejected.clone());
+        let result = builder
+            .sign_and_broadcast(&client, SyncMode::Sync, 0)
+            .await
+            .unwrap();
+        assert_eq!(result, rejected);
+        assert_eq!(client.broadcasted_txs().len(), 1);
+        assert_eq!(builder.sequence, 30);
+
+        // the mismatch of a tx included in a block is recovered too
+        let client = MockClient::new();
+        let mut in_block =
+            mismatch("account sequence mismatch, expected 31, got 30: incorrect account sequence");
+        in_block.height = 100;
+        client.push_tx_response(in_block);
+        let result = builder
+            .sign_and_broadcast(&client, SyncMode::Block, 1)
+            .await
+            .unwrap();
+        assert!(result.is_success());
+        assert_eq!(builder.sequence, 31);
+        assert_eq!(client.broadcasted_txs().len(), 2);
     }
 }
@@ -1,9 +1,46 @@
-#[cfg(not(feature = "grpc"))]
-mod amino;
+use crate::error::Error;
+use std::str::FromStr;
+
+pub mod amino;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+pub mod grpc;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod multi_send;
+
 #[cfg(not(feature = "grpc"))]
 pub use amino::*;
-
-#[cfg(feature = "grpc")]
-mod grpc;
 #[cfg(feature = "grpc")]
 pub use grpc::*;
+
+/// the encoding of the txs, chosen at runtime so one binary can talk to both the
+/// amino (cosmos-sdk 0.39) and the protobuf (cosmos-sdk 0.40+) nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxFormat {
+    /// `amino::TxBuilder`, a StdTx broadcast by the rest api
+    Amino,
+    /// `grpc::TxBuilder`, a protobuf `TxRaw`
+    Protobuf,
+}
+
+impl Default for TxFormat {
+    fn default() -> Self {
+        if cfg!(feature = "grpc") {
+            TxFormat::Protobuf
+        } else {
+            TxFormat::Amino
+        }
+    }
+}
+
+impl FromStr for TxFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "amino" => Ok(TxFormat::Amino),
+            "protobuf" => Ok(TxFormat::Protobuf),
+            _ => Err(Error::InputError(format!("unknown tx format {}", s))),
+        }
+    }
+}
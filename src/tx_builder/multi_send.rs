@@ -0,0 +1,311 @@
+//! Distribution to a very large number of recipients (e.g. an airdrop), split into
+//! `MsgMultiSend` txs which stay under the tx size and gas limits of the chain
+
+use crate::error::Error;
+use crate::message::{Msg, MSG_MULTI_SEND_TYPE_URL};
+use crate::proto::cosmos::bank::v1beta1::{Input, MsgMultiSend, Output};
+use crate::proto::cosmos::base::v1beta1::Coin;
+use crate::proto::cosmos::tx::v1beta1::Fee;
+use crate::tx_builder::batch::BatchTx;
+use crate::types::address::AccountAddress;
+use crate::types::basic::GasPrice;
+use prost::encoding::encoded_len_varint;
+use std::collections::BTreeMap;
+
+/// bytes of a signed single signer tx besides its message, without memo
+const TX_OVERHEAD_BYTES: usize = 300;
+/// default size limit of a tx, well under the mempool limit of the nodes
+pub const DEFAULT_MAX_TX_BYTES: usize = 100_000;
+/// default gas limit of a tx, well under the block gas limit
+pub const DEFAULT_MAX_GAS: u64 = 10_000_000;
+const DEFAULT_BASE_GAS: u64 = 60_000;
+const DEFAULT_GAS_PER_OUTPUT: u64 = 30_000;
+
+/// an address and the coins it receives
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recipient {
+    pub address: String,
+    pub amount: Vec<Coin>,
+}
+
+impl Recipient {
+    pub fn new(address: impl Into<String>, amount: Vec<Coin>) -> Self {
+        Self {
+            address: address.into(),
+            amount,
+        }
+    }
+}
+
+/// splits the recipients into the `MsgMultiSend` txs of one sender, in the order of the
+/// recipients, the txs are signed with `BatchSigner` which gives them consecutive sequences
+#[derive(Debug, Clone)]
+pub struct MultiSendSplitter {
+    from_address: String,
+    max_tx_bytes: usize,
+    max_gas: u64,
+    base_gas: u64,
+    gas_per_output: u64,
+    gas_price: Option<GasPrice>,
+}
+
+/// the outputs of the tx being filled and the total it sends
+#[derive(Default)]
+struct Chunk {
+    outputs: Vec<Output>,
+    outputs_len: usize,
+    totals: BTreeMap<String, u128>,
+}
+
+/// encoded length of a length delimited field with a one byte key
+fn field_len(len: usize) -> usize {
+    1 + encoded_len_varint(len as u64) + len
+}
+
+fn add_coins(totals: &mut BTreeMap<String, u128>, coins: &[Coin]) -> Result<(), Error> {
+    for coin in coins {
+        let amount: u128 = coin
+            .amount
+            .parse()
+            .map_err(|_| Error::InputError(format!("invalid amount {}", coin.amount)))?;
+        let total = totals.entry(coin.denom.clone()).or_default();
+        *total = total
+            .checked_add(amount)
+            .ok_or_else(|| Error::InputError("total amount overflow".to_string()))?;
+    }
+    Ok(())
+}
+
+/// the coins sorted by denom, as the sdk requires
+fn to_coins(totals: &BTreeMap<String, u128>) -> Vec<Coin> {
+    totals
+        .iter()
+        .map(|(denom, amount)| Coin {
+            denom: denom.clone(),
+            amount: amount.to_string(),
+        })
+        .collect()
+}
+
+impl MultiSendSplitter {
+    pub fn new(from_address: &str) -> Result<Self, Error> {
+        from_address.parse::<AccountAddress>()?;
+        Ok(Self {
+            from_address: from_address.to_string(),
+            max_tx_bytes: DEFAULT_MAX_TX_BYTES,
+            max_gas: DEFAULT_MAX_GAS,
+            base_gas: DEFAULT_BASE_GAS,
+            gas_per_output: DEFAULT_GAS_PER_OUTPUT,
+            gas_price: None,
+        })
+    }
+
+    /// size limit of a signed tx
+    pub fn set_max_tx_bytes(&mut self, max_tx_bytes: usize) -> &mut Self {
+        self.max_tx_bytes = max_tx_bytes;
+        self
+    }
+
+    /// gas limit of a tx
+    pub fn set_max_gas(&mut self, max_gas: u64) -> &mut Self {
+        self.max_gas = max_gas;
+        self
+    }
+
+    /// the gas of a tx is `base_gas + gas_per_output * outputs`
+    pub fn set_gas(&mut self, base_gas: u64, gas_per_output: u64) -> &mut Self {
+        self.base_gas = base_gas;
+        self.gas_per_output = gas_per_output;
+        self
+    }
+
+    /// the fee amount of each tx is its gas at the gas price, no fee amount by default
+    pub fn set_gas_price(&mut self, gas_price: Option<GasPrice>) -> &mut Self {
+        self.gas_price = gas_price;
+        self
+    }
+
+    /// gas limit of a tx with the number of outputs
+    pub fn gas_limit(&self, outputs: usize) -> Result<u64, Error> {
+        self.gas_per_output
+            .checked_mul(outputs as u64)
+            .and_then(|gas| gas.checked_add(self.base_gas))
+            .ok_or_else(|| Error::InputError(format!("gas limit overflow: {} outputs", outputs)))
+    }
+
+    fn input(&self, totals: &BTreeMap<String, u128>) -> Input {
+        Input {
+            address: self.from_address.clone(),
+            coins: to_coins(totals),
+        }
+    }
+
+    /// estimated size of the signed tx of a message
+    fn tx_bytes(&self, msg_len: usize) -> usize {
+        let any_len = field_len(MSG_MULTI_SEND_TYPE_URL.len()) + field_len(msg_len);
+        field_len(any_len) + TX_OVERHEAD_BYTES
+    }
+
+    fn to_tx(&self, chunk: Chunk) -> Result<BatchTx, Error> {
+        let gas_limit = self.gas_limit(chunk.outputs.len())?;
+        let msg = MsgMultiSend {
+            inputs: vec![self.input(&chunk.totals)],
+            outputs: chunk.outputs,
+        };
+        let amount = match &self.gas_price {
+            Some(gas_price) => vec![gas_price.fee_coin(gas_limit)?],
+            None => vec![],
+        };
+        Ok(BatchTx {
+            messages: vec![Msg::from(msg)],
+            memo: None,
+            fee: Some(Fee {
+                amount,
+                gas_limit,
+                payer: String::new(),
+                granter: String::new(),
+            }),
+        })
+    }
+
+    /// the txs paying the recipients, each as large as the limits allow
+    pub fn split(&self, recipients: &[Recipient]) -> Result<Vec<BatchTx>, Error> {
+        let mut txs = vec![];
+        let mut chunk = Chunk::default();
+        for recipient in recipients {
+            recipient.address.parse::<AccountAddress>()?;
+            if recipient.amount.is_empty() {
+                return Err(Error::InputError(format!(
+                    "recipient {} has no amount",
+                    recipient.address
+                )));
+            }
+            // the coins of a recipient are sorted and merged by denom
+            let mut coins = BTreeMap::new();
+            add_coins(&mut coins, &recipient.amount)?;
+            let output = Output {
+                address: recipient.address.clone(),
+                coins: to_coins(&coins),
+            };
+            let output_len = field_len(prost::Message::encoded_len(&output));
+            loop {
+                let mut totals = chunk.totals.clone();
+                add_coins(&mut totals, &output.coins)?;
+                let input_len = field_len(prost::Message::encoded_len(&self.input(&totals)));
+                let msg_len = input_len + chunk.outputs_len + output_len;
+                let fits = self.tx_bytes(msg_len) <= self.max_tx_bytes
+                    && self.gas_limit(chunk.outputs.len() + 1)? <= self.max_gas;
+                if fits {
+                    chunk.totals = totals;
+                    chunk.outputs_len += output_len;
+                    chunk.outputs.push(output);
+                    break;
+                }
+                if chunk.outputs.is_empty() {
+                    return Err(Error::InputError(format!(
+                        "a tx paying {} alone exceeds the tx limits",
+                        recipient.address
+                    )));
+                }
+                txs.push(self.to_tx(std::mem::take(&mut chunk))?);
+            }
+        }
+        if !chunk.outputs.is_empty() {
+            txs.push(self.to_tx(chunk)?);
+        }
+        Ok(txs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hd_wallet::mnemonic::Mnemonic;
+    use crate::key_service::private_key_service::PrivateKeyService;
+    use crate::message::TypedMsg;
+    use crate::tx_builder::batch::BatchSigner;
+
+    fn multi_send(tx: &BatchTx) -> &MsgMultiSend {
+        match tx.messages[0].typed() {
+            TypedMsg::MultiSend(msg) => msg,
+            _ => panic!("expect MsgMultiSend"),
+        }
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_multi_send_splitter() {
+        let from = "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf";
+        let recipients: Vec<Recipient> = (1..=1000)
+            .map(|amount| {
+                let coin = Coin {
+                    denom: "basecro".to_string(),
+                    amount: amount.to_string(),
+                };
+                Recipient::new("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd", vec![coin])
+            })
+            .collect();
+
+        let mut splitter = MultiSendSplitter::new(from).unwrap();
+        splitter.set_max_gas(splitter.gas_limit(100).unwrap());
+        let txs = splitter.split(&recipients).unwrap();
+        assert_eq!(txs.len(), 10);
+        let msg = multi_send(&txs[1]);
+        assert_eq!(msg.outputs.len(), 100);
+        assert_eq!(msg.outputs[0].coins[0].amount, "101");
+        let total: u128 = (101..=200).sum();
+        assert_eq!(msg.inputs[0].coins[0].amount, total.to_string());
+        assert_eq!(
+            txs[1].fee.as_ref().unwrap().gas_limit,
+            splitter.gas_limit(100).unwrap()
+        );
+        txs[1].messages[0].validate().unwrap();
+
+        splitter
+            .set_max_gas(DEFAULT_MAX_GAS)
+            .set_max_tx_bytes(5_000);
+        let txs = splitter.split(&recipients).unwrap();
+        assert!(txs.len() > 10);
+        let outputs: usize = txs.iter().map(|tx| multi_send(tx).outputs.len()).sum();
+        assert_eq!(outputs, 1000);
+
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let key_service = PrivateKeyService::new_from_mnemonic(mnemonic).unwrap();
+        let signer = BatchSigner::new(key_service, "test".parse().unwrap(), 1, 7);
+        let built = signer.sign_all(txs).await.unwrap();
+        assert!(built.iter().all(|tx| tx.bytes.len() <= 5_000));
+
+        splitter.set_max_gas(splitter.gas_limit(0).unwrap());
+        assert!(splitter.split(&recipients).is_err());
+        assert!(splitter.split(&[]).unwrap().is_empty());
+
+        splitter.set_gas(1, u64::MAX);
+        assert!(splitter.gas_limit(2).is_err());
+        assert!(splitter.split(&recipients).is_err());
+    }
+
+    #[test]
+    fn test_multi_send_output_coins() {
+        let coin = |denom: &str, amount: u64| Coin {
+            denom: denom.to_string(),
+            amount: amount.to_string(),
+        };
+        let recipient = Recipient::new(
+            "cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd",
+            vec![coin("uatom", 1), coin("basecro", 2), coin("uatom", 3)],
+        );
+        let splitter =
+            MultiSendSplitter::new("cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf").unwrap();
+        let txs = splitter.split(&[recipient.clone(), recipient]).unwrap();
+        let msg = multi_send(&txs[0]);
+        assert_eq!(
+            msg.outputs[0].coins,
+            vec![coin("basecro", 2), coin("uatom", 4)]
+        );
+        assert_eq!(
+            msg.inputs[0].coins,
+            vec![coin("basecro", 4), coin("uatom", 8)]
+        );
+        txs[0].messages[0].validate().unwrap();
+    }
+}
@@ -1,4 +1,15 @@
+pub mod account;
+pub mod address;
 pub mod basic;
+pub mod block;
+pub mod chain_id;
+pub mod decoded_tx;
+pub mod fee;
 pub mod key;
+pub mod mempool;
+pub mod node_info;
+pub mod params;
+pub mod partial_tx;
 pub mod signature;
 pub mod transaction;
+pub mod tx_response;
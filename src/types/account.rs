@@ -0,0 +1,509 @@
+use crate::error::Error;
+use crate::key_service::{ETH_SECP256K1_PUBKEY_TYPE_URL, SECP256K1_PUBKEY_TYPE_URL};
+use crate::proto::cosmos::auth::v1beta1::{
+    BaseAccount as ProtoBaseAccount, ModuleAccount as ProtoModuleAccount,
+};
+use crate::proto::cosmos::base::v1beta1::Coin;
+use crate::proto::cosmos::crypto::secp256k1::PubKey;
+use crate::proto::cosmos::vesting::v1beta1::{
+    BaseVestingAccount as ProtoBaseVestingAccount,
+    ContinuousVestingAccount as ProtoContinuousVestingAccount,
+    DelayedVestingAccount as ProtoDelayedVestingAccount,
+};
+use crate::types::basic::Amount;
+use crate::utils::codec::deserde_u64_from_str_or_num;
+use prost_types::Any;
+use serde::Deserialize;
+use std::convert::TryFrom;
+
+pub const BASE_ACCOUNT_TYPE_URL: &str = "/cosmos.auth.v1beta1.BaseAccount";
+pub const MODULE_ACCOUNT_TYPE_URL: &str = "/cosmos.auth.v1beta1.ModuleAccount";
+pub const CONTINUOUS_VESTING_ACCOUNT_TYPE_URL: &str =
+    "/cosmos.vesting.v1beta1.ContinuousVestingAccount";
+pub const DELAYED_VESTING_ACCOUNT_TYPE_URL: &str = "/cosmos.vesting.v1beta1.DelayedVestingAccount";
+pub const ETH_ACCOUNT_TYPE_URL: &str = "/ethermint.types.v1.EthAccount";
+
+/// the ethermint account, which is not in the cosmos protos
+#[derive(Clone, PartialEq, prost::Message)]
+struct ProtoEthAccount {
+    #[prost(message, optional, tag = "1")]
+    base_account: Option<ProtoBaseAccount>,
+    #[prost(string, tag = "2")]
+    code_hash: String,
+}
+
+/// the accounts of the other types which embed the base account in their first field,
+/// e.g. the module accounts of the custom modules
+#[derive(Clone, PartialEq, prost::Message)]
+struct ProtoEmbeddedBaseAccount {
+    #[prost(message, optional, tag = "1")]
+    base_account: Option<ProtoBaseAccount>,
+}
+
+/// the vesting accounts of the other types, e.g. `PeriodicVestingAccount`
+#[derive(Clone, PartialEq, prost::Message)]
+struct ProtoEmbeddedVestingAccount {
+    #[prost(message, optional, tag = "1")]
+    base_vesting_account: Option<ProtoEmbeddedBaseAccount>,
+}
+
+/// the public key of an account, `key` is the base64 encoded key of a single key account
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccountPubKey {
+    #[serde(rename = "@type")]
+    pub type_url: String,
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BaseAccount {
+    pub address: String,
+    /// None until the account sends its first tx
+    #[serde(default)]
+    pub pub_key: Option<AccountPubKey>,
+    #[serde(default, deserialize_with = "deserde_u64_from_str_or_num")]
+    pub account_number: u64,
+    #[serde(default, deserialize_with = "deserde_u64_from_str_or_num")]
+    pub sequence: u64,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BaseVestingAccount {
+    pub base_account: BaseAccount,
+    #[serde(default)]
+    pub original_vesting: Vec<Amount>,
+    #[serde(default)]
+    pub delegated_free: Vec<Amount>,
+    #[serde(default)]
+    pub delegated_vesting: Vec<Amount>,
+    /// unix timestamp in seconds
+    #[serde(default, deserialize_with = "deserde_u64_from_str_or_num")]
+    pub end_time: u64,
+}
+
+/// the coins are vested linearly from `start_time` to `end_time`
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ContinuousVestingAccount {
+    pub base_vesting_account: BaseVestingAccount,
+    /// unix timestamp in seconds
+    #[serde(default, deserialize_with = "deserde_u64_from_str_or_num")]
+    pub start_time: u64,
+}
+
+/// all the coins are vested at `end_time`
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DelayedVestingAccount {
+    pub base_vesting_account: BaseVestingAccount,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ModuleAccount {
+    pub base_account: BaseAccount,
+    pub name: String,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+/// account of the ethermint chains
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EthAccount {
+    pub base_account: BaseAccount,
+    #[serde(default)]
+    pub code_hash: String,
+}
+
+/// an account of a type unknown to this crate (e.g. `PeriodicVestingAccount` or the account
+/// of a custom module), only the base account embedded in it is decoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownAccount {
+    pub type_url: String,
+    pub base_account: BaseAccount,
+}
+
+/// an account returned by the auth module, the kind is decided by the `@type` field
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "@type")]
+pub enum Account {
+    #[serde(rename = "/cosmos.auth.v1beta1.BaseAccount")]
+    BaseAccount(BaseAccount),
+    #[serde(rename = "/cosmos.vesting.v1beta1.ContinuousVestingAccount")]
+    ContinuousVestingAccount(ContinuousVestingAccount),
+    #[serde(rename = "/cosmos.vesting.v1beta1.DelayedVestingAccount")]
+    DelayedVestingAccount(DelayedVestingAccount),
+    #[serde(rename = "/cosmos.auth.v1beta1.ModuleAccount")]
+    ModuleAccount(ModuleAccount),
+    #[serde(rename = "/ethermint.types.v1.EthAccount")]
+    EthAccount(EthAccount),
+    /// built by `Account::from_json` and `Account::from_any`
+    #[serde(skip_deserializing)]
+    Unknown(UnknownAccount),
+}
+
+const KNOWN_ACCOUNT_TYPE_URLS: [&str; 5] = [
+    BASE_ACCOUNT_TYPE_URL,
+    MODULE_ACCOUNT_TYPE_URL,
+    CONTINUOUS_VESTING_ACCOUNT_TYPE_URL,
+    DELAYED_VESTING_ACCOUNT_TYPE_URL,
+    ETH_ACCOUNT_TYPE_URL,
+];
+
+/// the embedded base account is only taken with a valid bech32 address
+fn valid_base_account(account: Option<ProtoBaseAccount>) -> Option<BaseAccount> {
+    let account = BaseAccount::try_from(account?).ok()?;
+    bech32::decode(&account.address).ok()?;
+    Some(account)
+}
+
+/// the first object with an `address` in the json of an account, depth first
+fn base_account_json(value: &serde_json::Value) -> Option<BaseAccount> {
+    if value.get("address").is_some() {
+        return serde_json::from_value(value.clone()).ok();
+    }
+    value.as_object()?.values().find_map(base_account_json)
+}
+
+impl TryFrom<ProtoBaseAccount> for BaseAccount {
+    type Error = Error;
+
+    fn try_from(account: ProtoBaseAccount) -> Result<Self, Self::Error> {
+        let pub_key = match account.pub_key {
+            Some(any) => {
+                // only the single secp256k1 keys carry a `key`
+                let key = match any.type_url.as_str() {
+                    SECP256K1_PUBKEY_TYPE_URL | ETH_SECP256K1_PUBKEY_TYPE_URL => {
+                        let pubkey: PubKey = prost::Message::decode(&*any.value)?;
+                        Some(base64::encode(&pubkey.key))
+                    }
+                    _ => None,
+                };
+                Some(AccountPubKey {
+                    type_url: any.type_url,
+                    key,
+                })
+            }
+            None => None,
+        };
+        Ok(Self {
+            address: account.address,
+            pub_key,
+            account_number: account.account_number,
+            sequence: account.sequence,
+        })
+    }
+}
+
+fn required_base_account(account: Option<ProtoBaseAccount>) -> Result<BaseAccount, Error> {
+    account
+        .ok_or_else(|| Error::InputError("missing base account".to_string()))
+        .and_then(BaseAccount::try_from)
+}
+
+impl TryFrom<ProtoBaseVestingAccount> for BaseVestingAccount {
+    type Error = Error;
+
+    fn try_from(account: ProtoBaseVestingAccount) -> Result<Self, Self::Error> {
+        let amounts = |coins: Vec<Coin>| -> Result<Vec<Amount>, Error> {
+            coins.iter().map(Amount::try_from).collect()
+        };
+        Ok(Self {
+            base_account: required_base_account(account.base_account)?,
+            original_vesting: amounts(account.original_vesting)?,
+            delegated_free: amounts(account.delegated_free)?,
+            delegated_vesting: amounts(account.delegated_vesting)?,
+            end_time: account.end_time as u64,
+        })
+    }
+}
+
+fn required_base_vesting_account(
+    account: Option<ProtoBaseVestingAccount>,
+) -> Result<BaseVestingAccount, Error> {
+    account
+        .ok_or_else(|| Error::InputError("missing base vesting account".to_string()))
+        .and_then(BaseVestingAccount::try_from)
+}
+
+impl Account {
+    /// decode the protobuf account packed in an `Any`, e.g. of the auth query service
+    pub fn from_any(any: &Any) -> Result<Self, Error> {
+        let value = &*any.value;
+        let account = match any.type_url.as_str() {
+            BASE_ACCOUNT_TYPE_URL => {
+                let account: ProtoBaseAccount = prost::Message::decode(value)?;
+                Account::BaseAccount(BaseAccount::try_from(account)?)
+            }
+            CONTINUOUS_VESTING_ACCOUNT_TYPE_URL => {
+                let account: ProtoContinuousVestingAccount = prost::Message::decode(value)?;
+                Account::ContinuousVestingAccount(ContinuousVestingAccount {
+                    base_vesting_account: required_base_vesting_account(
+                        account.base_vesting_account,
+                    )?,
+                    start_time: account.start_time as u64,
+                })
+            }
+            DELAYED_VESTING_ACCOUNT_TYPE_URL => {
+                let account: ProtoDelayedVestingAccount = prost::Message::decode(value)?;
+                Account::DelayedVestingAccount(DelayedVestingAccount {
+                    base_vesting_account: required_base_vesting_account(
+                        account.base_vesting_account,
+                    )?,
+                })
+            }
+            MODULE_ACCOUNT_TYPE_URL => {
+                let account: ProtoModuleAccount = prost::Message::decode(value)?;
+                Account::ModuleAccount(ModuleAccount {
+                    base_account: required_base_account(account.base_account)?,
+                    name: account.name,
+                    permissions: account.permissions,
+                })
+            }
+            ETH_ACCOUNT_TYPE_URL => {
+                let account: ProtoEthAccount = prost::Message::decode(value)?;
+                Account::EthAccount(EthAccount {
+                    base_account: required_base_account(account.base_account)?,
+                    code_hash: account.code_hash,
+                })
+            }
+            type_url => {
+                let embedded = || -> Option<BaseAccount> {
+                    if let Ok(account) = <ProtoEmbeddedBaseAccount as prost::Message>::decode(value)
+                    {
+                        if let Some(account) = valid_base_account(account.base_account) {
+                            return Some(account);
+                        }
+                    }
+                    if let Ok(account) =
+                        <ProtoEmbeddedVestingAccount as prost::Message>::decode(value)
+                    {
+                        let base_account = account
+                            .base_vesting_account
+                            .and_then(|account| account.base_account);
+                        if let Some(account) = valid_base_account(base_account) {
+                            return Some(account);
+                        }
+                    }
+                    valid_base_account(prost::Message::decode(value).ok())
+                };
+                let base_account = embedded().ok_or_else(|| {
+                    Error::InputError(format!(
+                        "no base account in the account of the unknown type {}",
+                        type_url
+                    ))
+                })?;
+                Account::Unknown(UnknownAccount {
+                    type_url: type_url.to_string(),
+                    base_account,
+                })
+            }
+        };
+        Ok(account)
+    }
+
+    /// parse the json account of the rest api, the accounts of the unknown types are
+    /// parsed into `Account::Unknown` with their embedded base account
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, Error> {
+        let type_url = value["@type"].as_str().unwrap_or_default();
+        if KNOWN_ACCOUNT_TYPE_URLS.contains(&type_url) {
+            return serde_json::from_value(value.clone())
+                .map_err(|e| Error::SerializeError(format!("invalid account: {}", e)));
+        }
+        let base_account = base_account_json(value).ok_or_else(|| {
+            Error::SerializeError(format!(
+                "no base account in the account of the unknown type {}",
+                type_url
+            ))
+        })?;
+        Ok(Account::Unknown(UnknownAccount {
+            type_url: type_url.to_string(),
+            base_account,
+        }))
+    }
+
+    /// the base account embedded in every kind of account
+    pub fn base_account(&self) -> &BaseAccount {
+        match self {
+            Account::BaseAccount(account) => account,
+            Account::ContinuousVestingAccount(account) => {
+                &account.base_vesting_account.base_account
+            }
+            Account::DelayedVestingAccount(account) => &account.base_vesting_account.base_account,
+            Account::ModuleAccount(account) => &account.base_account,
+            Account::EthAccount(account) => &account.base_account,
+            Account::Unknown(account) => &account.base_account,
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.base_account().address
+    }
+
+    pub fn account_number(&self) -> u64 {
+        self.base_account().account_number
+    }
+
+    pub fn sequence(&self) -> u64 {
+        self.base_account().sequence
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::basic::Denom;
+
+    #[test]
+    fn test_deserialize() {
+        let base = r#"{"@type": "/cosmos.auth.v1beta1.BaseAccount", "address": "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf", "pub_key": {"@type": "/cosmos.crypto.secp256k1.PubKey", "key": "AntL+UxMyJ9NZ9DGLp2v7a3dlSxiNXMaItyOXSRw8iYi"}, "account_number": "9", "sequence": "25"}"#;
+        let account: Account = serde_json::from_str(base).unwrap();
+        assert_eq!(account.account_number(), 9);
+        assert_eq!(account.sequence(), 25);
+        assert_eq!(
+            account
+                .base_account()
+                .pub_key
+                .as_ref()
+                .unwrap()
+                .key
+                .as_deref(),
+            Some("AntL+UxMyJ9NZ9DGLp2v7a3dlSxiNXMaItyOXSRw8iYi")
+        );
+
+        let vesting = r#"{"@type": "/cosmos.vesting.v1beta1.ContinuousVestingAccount", "base_vesting_account": {"base_account": {"address": "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf", "pub_key": null, "account_number": "3", "sequence": "0"}, "original_vesting": [{"denom": "basecro", "amount": "1000"}], "delegated_free": [], "delegated_vesting": [], "end_time": "1700000000"}, "start_time": "1600000000"}"#;
+        let account: Account = serde_json::from_str(vesting).unwrap();
+        assert_eq!(account.account_number(), 3);
+        assert!(account.base_account().pub_key.is_none());
+        match account {
+            Account::ContinuousVestingAccount(account) => {
+                assert_eq!(account.start_time, 1_600_000_000);
+                assert_eq!(account.base_vesting_account.end_time, 1_700_000_000);
+                assert_eq!(
+                    account.base_vesting_account.original_vesting,
+                    vec![Amount::new(1000, Denom::Basecro)]
+                );
+            }
+            _ => panic!("expect a continuous vesting account"),
+        }
+
+        let module = r#"{"@type": "/cosmos.auth.v1beta1.ModuleAccount", "base_account": {"address": "cro1jv65s3grqf6v6jl3dp4t6c9t9rk99cd8lyv94w", "pub_key": null, "account_number": "5", "sequence": "0"}, "name": "bonded_tokens_pool", "permissions": ["burner", "staking"]}"#;
+        let account: Account = serde_json::from_str(module).unwrap();
+        assert_eq!(account.account_number(), 5);
+        assert_eq!(
+            account.address(),
+            "cro1jv65s3grqf6v6jl3dp4t6c9t9rk99cd8lyv94w"
+        );
+
+        let eth = r#"{"@type": "/ethermint.types.v1.EthAccount", "base_account": {"address": "eth1u9q8mfpzhyv2s43js7l5qseapx5kt3g2l5hz7d", "pub_key": null, "account_number": "7", "sequence": "2"}, "code_hash": "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"}"#;
+        let account: Account = serde_json::from_str(eth).unwrap();
+        assert_eq!(account.sequence(), 2);
+
+        let unknown = r#"{"@type": "/cosmos.auth.v1beta1.Unknown", "address": "cro1"}"#;
+        assert!(serde_json::from_str::<Account>(unknown).is_err());
+    }
+
+    #[test]
+    fn test_from_json() {
+        let base = serde_json::json!({"@type": "/cosmos.auth.v1beta1.BaseAccount", "address": "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf", "pub_key": null, "account_number": "9", "sequence": "25"});
+        assert!(matches!(
+            Account::from_json(&base).unwrap(),
+            Account::BaseAccount(_)
+        ));
+        let invalid =
+            serde_json::json!({"@type": "/cosmos.auth.v1beta1.BaseAccount", "account_number": "9"});
+        assert!(Account::from_json(&invalid).is_err());
+
+        let periodic = serde_json::json!({
+            "@type": "/cosmos.vesting.v1beta1.PeriodicVestingAccount",
+            "base_vesting_account": {
+                "base_account": {"address": "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf", "pub_key": null, "account_number": "9", "sequence": "25"},
+                "original_vesting": [{"denom": "basecro", "amount": "1000"}],
+                "end_time": "1700000000"
+            },
+            "start_time": "1600000000",
+            "vesting_periods": [{"length": "100", "amount": [{"denom": "basecro", "amount": "1000"}]}]
+        });
+        let account = Account::from_json(&periodic).unwrap();
+        assert_eq!(account.account_number(), 9);
+        assert_eq!(account.sequence(), 25);
+        match account {
+            Account::Unknown(account) => assert_eq!(
+                account.type_url,
+                "/cosmos.vesting.v1beta1.PeriodicVestingAccount"
+            ),
+            _ => panic!("expect an unknown account"),
+        }
+
+        let no_base_account = serde_json::json!({"@type": "/custom.Account", "name": "x"});
+        assert!(Account::from_json(&no_base_account).is_err());
+    }
+
+    #[test]
+    fn test_from_any() {
+        let key = base64::decode("AntL+UxMyJ9NZ9DGLp2v7a3dlSxiNXMaItyOXSRw8iYi").unwrap();
+        let mut pub_key = vec![];
+        prost::Message::encode(&PubKey { key }, &mut pub_key).unwrap();
+        let base_account = ProtoBaseAccount {
+            address: "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf".to_string(),
+            pub_key: Some(Any {
+                type_url: SECP256K1_PUBKEY_TYPE_URL.to_string(),
+                value: pub_key,
+            }),
+            account_number: 9,
+            sequence: 25,
+        };
+        let vesting = ProtoDelayedVestingAccount {
+            base_vesting_account: Some(ProtoBaseVestingAccount {
+                base_account: Some(base_account.clone()),
+                original_vesting: vec![Coin {
+                    denom: "basecro".to_string(),
+                    amount: "1000".to_string(),
+                }],
+                delegated_free: vec![],
+                delegated_vesting: vec![],
+                end_time: 1_700_000_000,
+            }),
+        };
+        let mut value = vec![];
+        prost::Message::encode(&vesting, &mut value).unwrap();
+        let any = Any {
+            type_url: DELAYED_VESTING_ACCOUNT_TYPE_URL.to_string(),
+            value,
+        };
+        let account = Account::from_any(&any).unwrap();
+        assert_eq!(account.account_number(), 9);
+        assert_eq!(account.sequence(), 25);
+
+        let json = r#"{"@type": "/cosmos.vesting.v1beta1.DelayedVestingAccount", "base_vesting_account": {"base_account": {"address": "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf", "pub_key": {"@type": "/cosmos.crypto.secp256k1.PubKey", "key": "AntL+UxMyJ9NZ9DGLp2v7a3dlSxiNXMaItyOXSRw8iYi"}, "account_number": "9", "sequence": "25"}, "original_vesting": [{"denom": "basecro", "amount": "1000"}], "end_time": "1700000000"}}"#;
+        assert_eq!(account, serde_json::from_str(json).unwrap());
+
+        // the account of an unknown type embedding the base vesting account
+        let periodic = Any {
+            type_url: "/cosmos.vesting.v1beta1.PeriodicVestingAccount".to_string(),
+            value: any.value.clone(),
+        };
+        match Account::from_any(&periodic).unwrap() {
+            Account::Unknown(unknown) => {
+                assert_eq!(unknown.type_url, periodic.type_url);
+                assert_eq!(&unknown.base_account, account.base_account());
+            }
+            _ => panic!("expect an unknown account"),
+        }
+
+        let mut value = vec![];
+        prost::Message::encode(&base_account, &mut value).unwrap();
+        let custom = Any {
+            type_url: "/custom.v1.BaseAccount".to_string(),
+            value,
+        };
+        assert_eq!(
+            Account::from_any(&custom).unwrap().base_account(),
+            account.base_account()
+        );
+
+        let unknown = Any {
+            type_url: "/cosmos.auth.v1beta1.Unknown".to_string(),
+            value: vec![],
+        };
+        assert!(Account::from_any(&unknown).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+//! Bech32 prefixes, coin type and base denom of a chain, so the crate works with the
+//! testnet and the other cosmos chains besides the crypto.org mainnet
+
+use crate::constant::{ACCOUNT_ADDRESS_PREFIX, COIN_TYPE, COSMOS_COIN_TYPE};
+use crate::error::Error;
+use crate::hd_wallet::derivation_path::DerivationPath;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use stdtx::Address;
+
+/// 20 bytes account address, RIPEMD160(SHA256(pubkey)), with the bech32 prefix it is
+/// shown with, e.g. `cro1...`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccountAddress {
+    prefix: String,
+    address: Address,
+}
+
+impl AccountAddress {
+    pub fn new(prefix: &str, address: Address) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            address,
+        }
+    }
+
+    /// decode a bech32 address, the prefix must be `prefix`
+    pub fn from_bech32_with_prefix(address: &str, prefix: &str) -> Result<Self, Error> {
+        Ok(Self::new(prefix, parse_address(address, prefix)?))
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// the 20 bytes address
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.address.as_ref()
+    }
+
+    /// the same address with another prefix, e.g. the testnet prefix
+    pub fn with_prefix(&self, prefix: &str) -> Self {
+        Self::new(prefix, self.address)
+    }
+
+    pub fn to_bech32(&self) -> String {
+        self.address.to_bech32(&self.prefix)
+    }
+}
+
+impl fmt::Display for AccountAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_bech32())
+    }
+}
+
+impl FromStr for AccountAddress {
+    type Err = Error;
+
+    /// decode a bech32 address of any prefix
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (prefix, address) = Address::from_bech32(s)
+            .map_err(|e| Error::InputError(format!("invalid bech32 address {}: {:?}", s, e)))?;
+        Ok(Self { prefix, address })
+    }
+}
+
+impl Serialize for AccountAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_bech32())
+    }
+}
+
+impl<'de> Deserialize<'de> for AccountAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let address = String::deserialize(deserializer)?;
+        address.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<AccountAddress> for Address {
+    fn from(address: AccountAddress) -> Address {
+        address.address
+    }
+}
+
+impl From<&AccountAddress> for Address {
+    fn from(address: &AccountAddress) -> Address {
+        address.address
+    }
+}
+
+/// the address formats and the key derivation of a chain
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChainConfig {
+    /// prefix of the account addresses, e.g. `cro`
+    pub account_prefix: String,
+    /// prefix of the validator operator addresses, e.g. `crocncl`
+    pub valoper_prefix: String,
+    /// prefix of the validator consensus addresses, e.g. `crocnclcons`
+    pub cons_prefix: String,
+    /// bip44 coin type, e.g. 394
+    pub coin_type: u32,
+    /// the smallest unit of the staking token, e.g. `basecro`
+    pub base_denom: String,
+}
+
+impl ChainConfig {
+    /// the prefixes of the cosmos-sdk convention: `{prefix}valoper` and `{prefix}valcons`
+    pub fn new(account_prefix: &str, coin_type: u32, base_denom: &str) -> Self {
+        Self {
+            account_prefix: account_prefix.to_string(),
+            valoper_prefix: format!("{}valoper", account_prefix),
+            cons_prefix: format!("{}valcons", account_prefix),
+            coin_type,
+            base_denom: base_denom.to_string(),
+        }
+    }
+
+    /// the crypto.org chain mainnet
+    pub fn crypto_org() -> Self {
+        Self {
+            account_prefix: ACCOUNT_ADDRESS_PREFIX.to_string(),
+            valoper_prefix: "crocncl".to_string(),
+            cons_prefix: "crocnclcons".to_string(),
+            coin_type: COIN_TYPE,
+            base_denom: "basecro".to_string(),
+        }
+    }
+
+    /// the crypto.org chain testnet (croeseid)
+    pub fn crypto_org_testnet() -> Self {
+        Self {
+            account_prefix: "tcro".to_string(),
+            valoper_prefix: "tcrocncl".to_string(),
+            cons_prefix: "tcrocnclcons".to_string(),
+            coin_type: 1,
+            base_denom: "basetcro".to_string(),
+        }
+    }
+
+    /// the cosmos hub
+    pub fn cosmos_hub() -> Self {
+        Self::new("cosmos", COSMOS_COIN_TYPE, "uatom")
+    }
+
+    /// the bech32 account address
+    pub fn account_address(&self, address: &Address) -> String {
+        address.to_bech32(&self.account_prefix)
+    }
+
+    /// the bech32 validator operator address
+    pub fn valoper_address(&self, address: &Address) -> String {
+        address.to_bech32(&self.valoper_prefix)
+    }
+
+    /// the bech32 validator consensus address
+    pub fn cons_address(&self, address: &Address) -> String {
+        address.to_bech32(&self.cons_prefix)
+    }
+
+    /// the account address with the account prefix
+    pub fn account(&self, address: Address) -> AccountAddress {
+        AccountAddress::new(&self.account_prefix, address)
+    }
+
+    /// parse a bech32 account address, the prefix must be the account prefix
+    pub fn parse_account_address(&self, address: &str) -> Result<Address, Error> {
+        parse_address(address, &self.account_prefix)
+    }
+
+    /// parse a bech32 validator operator address, the prefix must be the valoper prefix
+    pub fn parse_valoper_address(&self, address: &str) -> Result<Address, Error> {
+        parse_address(address, &self.valoper_prefix)
+    }
+
+    /// the path m/44'/coin_type'/account'/change/index
+    pub fn derivation_path(&self, account: u32, change: u32, index: u32) -> DerivationPath {
+        DerivationPath::new(self.coin_type, account, change, index)
+    }
+}
+
+impl Default for ChainConfig {
+    fn default() -> Self {
+        Self::crypto_org()
+    }
+}
+
+/// parse a bech32 address and check its prefix
+pub fn parse_address(address: &str, prefix: &str) -> Result<Address, Error> {
+    let (hrp, address) = Address::from_bech32(address)
+        .map_err(|e| Error::InputError(format!("invalid bech32 address {}: {:?}", address, e)))?;
+    if hrp != prefix {
+        return Err(Error::InputError(format!(
+            "invalid address prefix {}, expected {}",
+            hrp, prefix
+        )));
+    }
+    Ok(address)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_chain_config() {
+        let mainnet = ChainConfig::default();
+        let address = mainnet
+            .parse_account_address("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd")
+            .unwrap();
+        let testnet = ChainConfig::crypto_org_testnet();
+        let testnet_address = testnet.account_address(&address);
+        assert!(testnet_address.starts_with("tcro1"));
+        assert_eq!(
+            testnet.parse_account_address(&testnet_address).unwrap(),
+            address
+        );
+        assert!(mainnet.parse_account_address(&testnet_address).is_err());
+        assert!(mainnet.valoper_address(&address).starts_with("crocncl1"));
+        assert_eq!(
+            testnet.derivation_path(0, 0, 0).to_string(),
+            "m/44'/1'/0'/0/0"
+        );
+
+        let account: AccountAddress = "cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd"
+            .parse()
+            .unwrap();
+        assert_eq!(account, mainnet.account(address));
+        assert_eq!(account.as_bytes().len(), 20);
+        assert_eq!(account.with_prefix("tcro").to_string(), testnet_address);
+        assert!(AccountAddress::from_bech32_with_prefix(&testnet_address, "cro").is_err());
+        assert!("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najx"
+            .parse::<AccountAddress>()
+            .is_err());
+
+        let hub = ChainConfig::cosmos_hub();
+        assert_eq!(hub.valoper_prefix, "cosmosvaloper");
+        assert_eq!(hub.derivation_path(0, 0, 0), DerivationPath::cosmos());
+    }
+}
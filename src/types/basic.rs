@@ -1,9 +1,14 @@
 use crate::constant::CRO;
-use crate::utils::codec::serde_to_str;
-use serde::Serialize;
+use crate::error::Error;
+use crate::proto::cosmos::base::v1beta1::Coin;
+use crate::utils::codec::{deserde_from_str, serde_to_str};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
 
 /// sync mode when send the transaction
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum SyncMode {
     Sync,
@@ -11,44 +16,375 @@ pub enum SyncMode {
     Block,
 }
 
+impl SyncMode {
+    /// the protobuf `BroadcastMode` of the tx service
+    pub fn broadcast_mode(&self) -> crate::proto::cosmos::tx::v1beta1::BroadcastMode {
+        use crate::proto::cosmos::tx::v1beta1::BroadcastMode;
+        match self {
+            SyncMode::Sync => BroadcastMode::Sync,
+            SyncMode::Async => BroadcastMode::Async,
+            SyncMode::Block => BroadcastMode::Block,
+        }
+    }
+}
+
+impl FromStr for SyncMode {
+    type Err = Error;
+
+    /// `sync`, `async` or `block`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sync" => Ok(SyncMode::Sync),
+            "async" => Ok(SyncMode::Async),
+            "block" => Ok(SyncMode::Block),
+            _ => Err(Error::InputError(format!("unknown sync mode {}", s))),
+        }
+    }
+}
+
 /// denomination: 1Cro = 100_000_000 Basecro
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Denom {
     Basecro,
     Cro,
+    /// a base denom of another chain or token, e.g. uatom or an ibc voucher `ibc/<hash>`
+    Other(String),
+}
+
+impl Denom {
+    /// a denom of another chain or token, checked against the sdk rule
+    /// `[a-zA-Z][a-zA-Z0-9/:._-]{2,127}`
+    pub fn other(denom: &str) -> Result<Self, Error> {
+        validate_denom(denom)?;
+        Ok(Denom::Other(denom.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Denom::Basecro => "basecro",
+            Denom::Cro => "cro",
+            Denom::Other(denom) => denom,
+        }
+    }
+
+    /// an ibc voucher, `ibc/<hash of the trace>`
+    pub fn is_ibc(&self) -> bool {
+        self.as_str().starts_with("ibc/")
+    }
 }
 
-/// Amount
+/// check a denom against the sdk rule `[a-zA-Z][a-zA-Z0-9/:._-]{2,127}`
+pub fn validate_denom(denom: &str) -> Result<(), Error> {
+    let mut chars = denom.chars();
+    let valid = (3..=128).contains(&denom.len())
+        && chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c));
+    if !valid {
+        return Err(Error::InputError(format!("invalid denom {}", denom)));
+    }
+    Ok(())
+}
+
+impl Serialize for Denom {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Denom {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let denom = String::deserialize(deserializer)?;
+        denom.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl FromStr for Denom {
+    type Err = Error;
+
+    /// basecro and cro are the known denoms, the other valid denoms are `Denom::Other`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "basecro" => Ok(Denom::Basecro),
+            "cro" => Ok(Denom::Cro),
+            _ => Denom::other(s),
+        }
+    }
+}
+
+/// Amount, kept in u128 so the cro amounts and the sums don't overflow
 #[derive(Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct Amount {
     denom: Denom,
     #[serde(serialize_with = "serde_to_str")]
-    amount: u64,
+    amount: u128,
+}
+
+/// the raw json form of `Amount`, checked by `Amount::try_new` when deserialized
+#[derive(Deserialize)]
+struct RawAmount {
+    denom: Denom,
+    #[serde(deserialize_with = "deserde_from_str")]
+    amount: u128,
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    /// a cro amount is converted into basecro
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = RawAmount::deserialize(deserializer)?;
+        Amount::try_new(raw.amount, raw.denom).map_err(serde::de::Error::custom)
+    }
 }
 
 impl Amount {
-    /// create a new amount, whatever input will be a Benom::Basecro denomination result
+    /// create a new amount, a cro amount is converted into Denom::Basecro,
+    /// the other denoms are kept as they are. A u64 cro amount always fits in u128
     pub fn new(amount: u64, denom: Denom) -> Self {
-        let amount = match denom {
-            Denom::Basecro => amount,
-            Denom::Cro => amount * CRO,
+        match denom {
+            Denom::Cro => Self {
+                denom: Denom::Basecro,
+                amount: amount as u128 * CRO as u128,
+            },
+            denom => Self {
+                denom,
+                amount: amount.into(),
+            },
+        }
+    }
+
+    /// create a new amount of u128, an error if the cro amount overflows in basecro
+    pub fn try_new(amount: u128, denom: Denom) -> Result<Self, Error> {
+        match denom {
+            Denom::Cro => Ok(Self {
+                denom: Denom::Basecro,
+                amount: amount
+                    .checked_mul(CRO as u128)
+                    .ok_or_else(|| Error::AmountOverflow(format!("{}cro in basecro", amount)))?,
+            }),
+            denom => Ok(Self { denom, amount }),
+        }
+    }
+
+    pub fn denom(&self) -> &Denom {
+        &self.denom
+    }
+
+    pub fn amount(&self) -> u128 {
+        self.amount
+    }
+
+    /// the amount as u64, an error if it doesn't fit
+    pub fn amount_u64(&self) -> Result<u64, Error> {
+        u64::try_from(self.amount)
+            .map_err(|_e| Error::AmountOverflow(format!("{} doesn't fit in u64", self.amount)))
+    }
+
+    /// the amount as u64, `u64::MAX` if it doesn't fit
+    pub fn saturating_u64(&self) -> u64 {
+        u64::try_from(self.amount).unwrap_or(u64::MAX)
+    }
+
+    /// the sum of the amounts of the same denom
+    pub fn checked_add(&self, other: &Amount) -> Result<Self, Error> {
+        self.check_denom(other)?;
+        let amount = self
+            .amount
+            .checked_add(other.amount)
+            .ok_or_else(|| Error::AmountOverflow(format!("{} + {}", self.amount, other.amount)))?;
+        Ok(Self {
+            denom: self.denom.clone(),
+            amount,
+        })
+    }
+
+    /// the difference of the amounts of the same denom, an error if it is negative
+    pub fn checked_sub(&self, other: &Amount) -> Result<Self, Error> {
+        self.check_denom(other)?;
+        let amount = self
+            .amount
+            .checked_sub(other.amount)
+            .ok_or_else(|| Error::AmountOverflow(format!("{} - {}", self.amount, other.amount)))?;
+        Ok(Self {
+            denom: self.denom.clone(),
+            amount,
+        })
+    }
+
+    /// the amount multiplied by the factor
+    pub fn checked_mul(&self, factor: u128) -> Result<Self, Error> {
+        let amount = self
+            .amount
+            .checked_mul(factor)
+            .ok_or_else(|| Error::AmountOverflow(format!("{} * {}", self.amount, factor)))?;
+        Ok(Self {
+            denom: self.denom.clone(),
+            amount,
+        })
+    }
+
+    /// the basecro amount as cro with up to 8 decimals and no trailing zeros,
+    /// e.g. `1.5cro` for 150000000basecro
+    pub fn format_cro(&self) -> Result<String, Error> {
+        if self.denom != Denom::Basecro {
+            return Err(Error::InputError(format!(
+                "{} is not a cro amount",
+                self.denom.as_str()
+            )));
+        }
+        let integer = self.amount / CRO as u128;
+        let fraction = self.amount % CRO as u128;
+        if fraction == 0 {
+            return Ok(format!("{}cro", integer));
+        }
+        let decimals = format!("{:08}", fraction);
+        Ok(format!("{}.{}cro", integer, decimals.trim_end_matches('0')))
+    }
+
+    fn check_denom(&self, other: &Amount) -> Result<(), Error> {
+        if self.denom != other.denom {
+            return Err(Error::InputError(format!(
+                "denom mismatch: {} and {}",
+                self.denom.as_str(),
+                other.denom.as_str()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// the coin string of the sdk, e.g. `2500000basecro`
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.amount, self.denom.as_str())
+    }
+}
+
+impl FromStr for Amount {
+    type Err = Error;
+
+    /// parse `2500000basecro`, `1.5cro` or `10uatom`, only a cro amount can have decimals,
+    /// up to 8 of them
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || Error::InputError(format!("invalid amount {}", s));
+        let split = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .ok_or_else(invalid)?;
+        let (number, denom) = s.split_at(split);
+        // the sdk allows spaces between the amount and the denom
+        let denom: Denom = denom.trim_start().parse()?;
+        let (integer, fraction) = match number.find('.') {
+            Some(dot) => (&number[..dot], &number[dot + 1..]),
+            None => (number, ""),
         };
-        Self {
-            denom: Denom::Basecro,
-            amount: amount,
+        if integer.is_empty() || (number.contains('.') && fraction.is_empty()) {
+            return Err(invalid());
+        }
+        let integer: u128 = integer.parse().map_err(|_e| invalid())?;
+        if fraction.is_empty() {
+            return Self::try_new(integer, denom);
+        }
+        if denom != Denom::Cro || fraction.len() > 8 {
+            return Err(invalid());
+        }
+        let fraction: u128 = format!("{:0<8}", fraction)
+            .parse()
+            .map_err(|_e| invalid())?;
+        let overflow = || Error::AmountOverflow(s.to_string());
+        let amount = integer
+            .checked_mul(CRO as u128)
+            .and_then(|amount| amount.checked_add(fraction))
+            .ok_or_else(overflow)?;
+        Self::try_new(amount, Denom::Basecro)
+    }
+}
+
+impl Amount {
+    /// parse a comma separated list like `1.5cro,10uatom`, as the sdk cli takes the coins,
+    /// an empty list is allowed
+    pub fn parse_list(s: &str) -> Result<Vec<Self>, Error> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|amount| !amount.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl FromStr for Coin {
+    type Err = Error;
+
+    /// parse the sdk coin string like `100000basecro`, the denom is kept as it is
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || Error::InputError(format!("invalid coin {}", s));
+        let split = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+        let (amount, denom) = s.split_at(split);
+        let denom = denom.trim_start();
+        if amount.is_empty() {
+            return Err(invalid());
+        }
+        validate_denom(denom)?;
+        let amount: u128 = amount.parse().map_err(|_e| invalid())?;
+        Ok(Coin {
+            denom: denom.to_string(),
+            amount: amount.to_string(),
+        })
+    }
+}
+
+/// parse a comma separated list of the sdk coins like `100000basecro,10uatom`,
+/// an empty list is allowed
+pub fn parse_coins(s: &str) -> Result<Vec<Coin>, Error> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|coin| !coin.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+impl TryFrom<&Coin> for Amount {
+    type Error = Error;
+
+    /// a cro coin is converted into basecro,
+    /// the denoms other than basecro and cro are kept in `Denom::Other`
+    fn try_from(coin: &Coin) -> Result<Self, Self::Error> {
+        let amount: u128 = coin
+            .amount
+            .parse()
+            .map_err(|_e| Error::InputError(format!("invalid coin amount {}", coin.amount)))?;
+        let denom = coin.denom.parse()?;
+        Self::try_new(amount, denom)
+    }
+}
+
+impl From<&Amount> for Coin {
+    fn from(amount: &Amount) -> Self {
+        Coin {
+            denom: amount.denom.as_str().to_string(),
+            amount: amount.amount.to_string(),
         }
     }
 }
 
 /// transaction fee
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Fee {
-    #[serde(serialize_with = "serde_to_str")]
+    #[serde(serialize_with = "serde_to_str", deserialize_with = "deserde_from_str")]
     pub gas: u64,
     pub amount: Vec<Amount>,
 }
 
+impl Fee {
+    /// fee of the gas limit at the gas price, the denom must be a valid denom
+    pub fn from_gas_price(gas_limit: u64, gas_price: &GasPrice) -> Result<Self, Error> {
+        let denom = gas_price.denom.parse()?;
+        Ok(Self {
+            gas: gas_limit,
+            amount: vec![Amount::try_new(gas_price.fee_amount(gas_limit)?, denom)?],
+        })
+    }
+}
+
 impl Default for Fee {
     fn default() -> Self {
         Self {
@@ -57,3 +393,318 @@ impl Default for Fee {
         }
     }
 }
+
+/// decimals of a gas price, as the `sdk.Dec` of the minimum gas prices of the nodes
+pub const GAS_PRICE_DECIMALS: u32 = 18;
+const GAS_PRICE_ONE: u128 = 1_000_000_000_000_000_000;
+
+/// gas price, e.g. 0.025basecro, the amount is a fixed point decimal of 18 decimals
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasPrice {
+    /// the price times 10^18, e.g. 25_000_000_000_000_000 for 0.025
+    pub amount: u128,
+    pub denom: String,
+}
+
+impl GasPrice {
+    /// create a new gas price of `amount / 10^decimals`, e.g. `GasPrice::new(25, 3, "basecro")`
+    /// for 0.025basecro, up to 18 decimals
+    pub fn new(amount: u128, decimals: u32, denom: impl Into<String>) -> Result<Self, Error> {
+        let denom = denom.into();
+        let overflow =
+            || Error::AmountOverflow(format!("gas price {}e-{}{}", amount, decimals, denom));
+        let scale = GAS_PRICE_DECIMALS
+            .checked_sub(decimals)
+            .map(|scale| 10u128.pow(scale))
+            .ok_or_else(overflow)?;
+        let amount = amount.checked_mul(scale).ok_or_else(overflow)?;
+        Ok(Self { amount, denom })
+    }
+
+    /// fee amount of the gas limit, rounded up as the sdk checks the fee against
+    /// the minimum gas prices
+    pub fn fee_amount(&self, gas_limit: u64) -> Result<u128, Error> {
+        let gas_limit = gas_limit as u128;
+        // the fraction times the gas limit always fits in u128
+        let fraction = self.amount % GAS_PRICE_ONE * gas_limit;
+        let fraction = fraction / GAS_PRICE_ONE + (fraction % GAS_PRICE_ONE != 0) as u128;
+        (self.amount / GAS_PRICE_ONE)
+            .checked_mul(gas_limit)
+            .and_then(|integer| integer.checked_add(fraction))
+            .ok_or_else(|| Error::AmountOverflow(format!("fee of {} gas at {}", gas_limit, self)))
+    }
+
+    /// protobuf fee coin of the gas limit
+    pub fn fee_coin(&self, gas_limit: u64) -> Result<Coin, Error> {
+        Ok(Coin {
+            denom: self.denom.clone(),
+            amount: self.fee_amount(gas_limit)?.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for GasPrice {
+    /// the decimal without the trailing zeros, e.g. `0.025basecro`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let integer = self.amount / GAS_PRICE_ONE;
+        let fraction = self.amount % GAS_PRICE_ONE;
+        if fraction == 0 {
+            return write!(f, "{}{}", integer, self.denom);
+        }
+        let fraction = format!("{:018}", fraction);
+        write!(
+            f,
+            "{}.{}{}",
+            integer,
+            fraction.trim_end_matches('0'),
+            self.denom
+        )
+    }
+}
+
+impl FromStr for GasPrice {
+    type Err = Error;
+
+    /// parse a gas price like `0.025basecro`, up to 18 decimals
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || Error::InputError(format!("invalid gas price {}", s));
+        let split = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .ok_or_else(|| Error::InputError(format!("missing denom in gas price {}", s)))?;
+        let (number, denom) = s.split_at(split);
+        validate_denom(denom)
+            .map_err(|_e| Error::InputError(format!("invalid denom in gas price {}", s)))?;
+        let (integer, fraction) = match number.find('.') {
+            // a dot must be followed by the decimals, e.g. `1.` is invalid
+            Some(dot) if dot + 1 == number.len() => return Err(invalid()),
+            Some(dot) => (&number[..dot], &number[dot + 1..]),
+            None => (number, ""),
+        };
+        if integer.is_empty() || fraction.contains('.') {
+            return Err(invalid());
+        }
+        if fraction.len() > GAS_PRICE_DECIMALS as usize {
+            return Err(Error::InputError(format!(
+                "gas price {} has more than {} decimals",
+                s, GAS_PRICE_DECIMALS
+            )));
+        }
+        let amount: u128 = format!("{}{}", integer, fraction)
+            .parse()
+            .map_err(|_e| invalid())?;
+        Self::new(amount, fraction.len() as u32, denom)
+    }
+}
+
+impl GasPrice {
+    /// parse a comma separated list like `0.025basecro,0.1uatom`, an empty list is allowed
+    pub fn parse_list(s: &str) -> Result<Vec<Self>, Error> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|price| !price.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+/// protobuf fee of the gas limit at the gas price
+impl crate::proto::cosmos::tx::v1beta1::Fee {
+    pub fn from_gas_price(gas_limit: u64, gas_price: &GasPrice) -> Result<Self, Error> {
+        Ok(Self {
+            amount: vec![gas_price.fee_coin(gas_limit)?],
+            gas_limit,
+            payer: String::new(),
+            granter: String::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gas_price() {
+        let gas_price: GasPrice = "0.025basecro".parse().unwrap();
+        assert_eq!(gas_price, GasPrice::new(25, 3, "basecro").unwrap());
+        assert_eq!(gas_price.to_string(), "0.025basecro");
+        assert_eq!(gas_price.fee_amount(300000).unwrap(), 7500);
+        assert_eq!(gas_price.fee_amount(300001).unwrap(), 7501);
+        // exact, an f64 gives 7.000000000000001 which is rounded up to 8
+        let gas_price: GasPrice = "0.07basecro".parse().unwrap();
+        assert_eq!(gas_price.fee_amount(100).unwrap(), 7);
+        let gas_price: GasPrice = "0.000000000000000001basecro".parse().unwrap();
+        assert_eq!(gas_price.amount, 1);
+        assert_eq!(gas_price.fee_amount(1).unwrap(), 1);
+        assert_eq!(gas_price.fee_amount(0).unwrap(), 0);
+        assert!("0.0000000000000000001basecro".parse::<GasPrice>().is_err());
+        let gas_price = GasPrice::new(u128::MAX / GAS_PRICE_ONE, 0, "basecro").unwrap();
+        assert!(gas_price.fee_amount(u64::MAX).is_err());
+        assert!(GasPrice::new(1, 19, "basecro").is_err());
+        assert!("1.2.3basecro".parse::<GasPrice>().is_err());
+        let gas_price: GasPrice = "0.025basecro".parse().unwrap();
+
+        let fee = Fee::from_gas_price(300000, &gas_price).unwrap();
+        assert_eq!(fee.gas, 300000);
+        assert_eq!(fee.amount, vec![Amount::new(7500, Denom::Basecro)]);
+
+        assert!("basecro".parse::<GasPrice>().is_err());
+        assert!("0.025".parse::<GasPrice>().is_err());
+        assert!("0.025 cro".parse::<GasPrice>().is_err());
+        for invalid in &[
+            "1.basecro",
+            "0.basecro",
+            ".5basecro",
+            "1.5u",
+            "1.5uatom!",
+            "1.5/atom",
+        ] {
+            assert!(invalid.parse::<GasPrice>().is_err(), "{}", invalid);
+        }
+        let ibc = "0.1ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+        assert_eq!(ibc.parse::<GasPrice>().unwrap().to_string(), ibc);
+        let fee = Fee::from_gas_price(1, &GasPrice::new(1, 0, "uatom").unwrap()).unwrap();
+        assert_eq!(
+            fee.amount,
+            vec![Amount::new(1, Denom::Other("uatom".into()))]
+        );
+        assert!(Fee::from_gas_price(1, &GasPrice::new(1, 0, "u").unwrap()).is_err());
+
+        let gas_prices = GasPrice::parse_list("0.025000000000000000basecro, 0.1uatom").unwrap();
+        assert_eq!(
+            gas_prices,
+            vec![
+                GasPrice::new(25, 3, "basecro").unwrap(),
+                GasPrice::new(1, 1, "uatom").unwrap()
+            ]
+        );
+        assert!(GasPrice::parse_list("").unwrap().is_empty());
+        assert!(GasPrice::parse_list("0.025basecro,basecro").is_err());
+    }
+
+    #[test]
+    fn test_amount_arithmetic() {
+        let max_cro = Amount::new(u64::MAX, Denom::Cro);
+        assert_eq!(max_cro.amount(), u64::MAX as u128 * CRO as u128);
+        assert!(max_cro.amount_u64().is_err());
+        assert_eq!(max_cro.saturating_u64(), u64::MAX);
+        assert!(Amount::try_new(u128::MAX, Denom::Cro).is_err());
+
+        let one = Amount::new(1, Denom::Basecro);
+        let two = one.checked_add(&one).unwrap();
+        assert_eq!(two.amount_u64().unwrap(), 2);
+        assert_eq!(two.checked_sub(&one).unwrap(), one);
+        assert!(one.checked_sub(&two).is_err());
+        assert_eq!(two.checked_mul(3).unwrap().amount(), 6);
+        let max = Amount::try_new(u128::MAX, Denom::Basecro).unwrap();
+        assert!(max.checked_add(&one).is_err());
+        assert!(max.checked_mul(2).is_err());
+        assert!(one
+            .checked_add(&Amount::new(1, Denom::Other("uatom".into())))
+            .is_err());
+    }
+
+    #[test]
+    fn test_amount_str() {
+        let amount: Amount = "1.5cro".parse().unwrap();
+        assert_eq!(amount, Amount::new(150_000_000, Denom::Basecro));
+        assert_eq!(amount.format_cro().unwrap(), "1.5cro");
+        assert_eq!(amount.to_string(), "150000000basecro");
+        let amount: Amount = "2500000basecro".parse().unwrap();
+        assert_eq!(amount.format_cro().unwrap(), "0.025cro");
+        assert_eq!("0.00000001cro".parse::<Amount>().unwrap().amount(), 1);
+        assert_eq!(
+            "2cro".parse::<Amount>().unwrap().format_cro().unwrap(),
+            "2cro"
+        );
+        assert_eq!(
+            "10uatom".parse::<Amount>().unwrap(),
+            Amount::new(10, Denom::Other("uatom".into()))
+        );
+        assert!(Amount::new(10, Denom::Other("uatom".into()))
+            .format_cro()
+            .is_err());
+        for invalid in &[
+            "cro",
+            "1.5",
+            "1.cro",
+            ".5cro",
+            "1.000000001cro",
+            "1.5basecro",
+            "1.5uatom",
+            "1.2.3cro",
+        ] {
+            assert!(invalid.parse::<Amount>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_coin_str() {
+        let coin: Coin = "100000basecro".parse().unwrap();
+        assert_eq!(coin.denom, "basecro");
+        assert_eq!(coin.amount, "100000");
+        assert_eq!("007 uatom".parse::<Coin>().unwrap().amount, "7");
+        for invalid in &["basecro", "100000", "1.5cro", "-1cro", "10u"] {
+            assert!(invalid.parse::<Coin>().is_err(), "{}", invalid);
+        }
+
+        let coins = parse_coins("100000basecro, 10uatom,").unwrap();
+        assert_eq!(coins.len(), 2);
+        assert_eq!(coins[1].denom, "uatom");
+        assert!(parse_coins("").unwrap().is_empty());
+        assert!(parse_coins("1cro,cro").is_err());
+
+        let amounts = Amount::parse_list("1.5cro,10 uatom").unwrap();
+        assert_eq!(
+            amounts,
+            vec![
+                Amount::new(150_000_000, Denom::Basecro),
+                Amount::new(10, Denom::Other("uatom".into()))
+            ]
+        );
+        assert!(Amount::parse_list("1cro,x").is_err());
+
+        let coin = Coin {
+            denom: "cro".into(),
+            amount: "1".into(),
+        };
+        let amount = Amount::try_from(&coin).unwrap();
+        assert_eq!(amount, Amount::new(100_000_000, Denom::Basecro));
+        assert_eq!(Coin::from(&amount).denom, "basecro");
+        let coin = Coin {
+            denom: "cro".into(),
+            amount: u128::MAX.to_string(),
+        };
+        assert!(Amount::try_from(&coin).is_err());
+    }
+
+    #[test]
+    fn test_denom() {
+        let ibc = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+        let denom: Denom = ibc.parse().unwrap();
+        assert_eq!(denom, Denom::Other(ibc.to_string()));
+        assert!(denom.is_ibc());
+        assert_eq!("basecro".parse::<Denom>().unwrap(), Denom::Basecro);
+        for invalid in &["", "ab", "1atom", "u atom", "uatom!"] {
+            assert!(invalid.parse::<Denom>().is_err(), "{}", invalid);
+        }
+
+        let amount: Amount =
+            serde_json::from_str(&format!(r#"{{"denom":"{}","amount":"10"}}"#, ibc)).unwrap();
+        assert_eq!(amount, Amount::new(10, denom));
+        let coin = Coin::from(&amount);
+        assert_eq!(coin.denom, ibc);
+        assert_eq!(Amount::try_from(&coin).unwrap(), amount);
+        assert!(serde_json::from_str::<Amount>(r#"{"denom":"1x","amount":"10"}"#).is_err());
+
+        let amount: Amount = serde_json::from_str(r#"{"denom":"cro","amount":"1"}"#).unwrap();
+        assert_eq!(amount, Amount::new(100_000_000, Denom::Basecro));
+        assert_eq!(
+            serde_json::to_string(&amount).unwrap(),
+            r#"{"denom":"basecro","amount":"100000000"}"#
+        );
+        let overflow = format!(r#"{{"denom":"cro","amount":"{}"}}"#, u128::MAX);
+        assert!(serde_json::from_str::<Amount>(&overflow).is_err());
+    }
+}
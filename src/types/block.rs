@@ -0,0 +1,105 @@
+use crate::error::Error;
+use crate::utils::codec::deserde_u64_from_str_or_num;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub chain_id: String,
+    #[serde(deserialize_with = "deserde_u64_from_str_or_num")]
+    pub height: u64,
+    /// RFC 3339 time, e.g. `2021-01-04T08:12:30.123456789Z`
+    pub time: String,
+    /// base64 encoded address of the proposer
+    #[serde(default)]
+    pub proposer_address: String,
+}
+
+/// a committed block, the hashes and txs are base64 encoded as the rest api returns them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub hash: String,
+    pub header: BlockHeader,
+    pub txs: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct BlockId {
+    hash: String,
+}
+
+#[derive(Deserialize, Default)]
+struct BlockData {
+    #[serde(default)]
+    txs: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct InnerBlock {
+    header: BlockHeader,
+    #[serde(default)]
+    data: BlockData,
+}
+
+#[derive(Deserialize)]
+struct BlockResponse {
+    block_id: BlockId,
+    block: InnerBlock,
+}
+
+impl Block {
+    /// parse the response of `/cosmos/base/tendermint/v1beta1/blocks/{height}`
+    pub fn from_json(response: serde_json::Value) -> Result<Self, Error> {
+        let response: BlockResponse = serde_json::from_value(response)
+            .map_err(|e| Error::SerializeError(format!("invalid block: {}", e)))?;
+        Ok(Self {
+            hash: response.block_id.hash,
+            header: response.block.header,
+            txs: response.block.data.txs.unwrap_or_default(),
+        })
+    }
+
+    pub fn height(&self) -> u64 {
+        self.header.height
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_json() {
+        let response = serde_json::json!({
+            "block_id": {
+                "hash": "kpCFSdrHWhiPYSF/7AEckcI6gP/yhs6x4m2nrT6VMh0=",
+                "part_set_header": {"total": 1, "hash": "E8jJ3FOkxxNhlUSTv/rQnD+FKU+IKfB6YY0iXz4v1z4="}
+            },
+            "block": {
+                "header": {
+                    "version": {"block": "11", "app": "0"},
+                    "chain_id": "testnet-croeseid-2",
+                    "height": "1024",
+                    "time": "2021-01-04T08:12:30.123456789Z",
+                    "proposer_address": "YJw5EL6DF9dDrxq1l8f0f30YtyA="
+                },
+                "data": {"txs": ["CpIBCo8BChwvY29zbW9zLmJhbmsudjFiZXRhMS5Nc2dTZW5k"]},
+                "evidence": {"evidence": []},
+                "last_commit": null
+            }
+        });
+        let block = Block::from_json(response).unwrap();
+        assert_eq!(block.height(), 1024);
+        assert_eq!(block.header.chain_id, "testnet-croeseid-2");
+        assert_eq!(block.header.time, "2021-01-04T08:12:30.123456789Z");
+        assert_eq!(block.txs.len(), 1);
+
+        let empty = serde_json::json!({
+            "block_id": {"hash": "kpCFSdrHWhiPYSF/7AEckcI6gP/yhs6x4m2nrT6VMh0="},
+            "block": {
+                "header": {"chain_id": "test", "height": "1", "time": "2021-01-04T08:12:30Z"},
+                "data": {"txs": null}
+            }
+        });
+        assert!(Block::from_json(empty).unwrap().txs.is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::error::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// max length of the chain id of tendermint
+const MAX_CHAIN_ID_LENGTH: usize = 50;
+
+/// chain id, non empty without whitespace, e.g. `crypto-org-chain-mainnet-1`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChainId(String);
+
+impl ChainId {
+    /// a chain id of the cosmos `name-number` format, e.g. `testnet-croeseid-4`,
+    /// the number is the revision which is increased by the chain upgrades
+    pub fn new_with_revision(chain_id: &str) -> Result<Self, Error> {
+        let chain_id: ChainId = chain_id.parse()?;
+        if chain_id.revision_number().is_none() {
+            return Err(Error::InputError(format!(
+                "chain id {} is not in the name-number format",
+                chain_id
+            )));
+        }
+        Ok(chain_id)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// the name part of a `name-number` chain id, or the whole chain id
+    pub fn name(&self) -> &str {
+        match self.split() {
+            Some((name, _)) => name,
+            None => &self.0,
+        }
+    }
+
+    /// the number of a `name-number` chain id, e.g. 1 of `crypto-org-chain-mainnet-1`
+    pub fn revision_number(&self) -> Option<u64> {
+        self.split().map(|(_, number)| number)
+    }
+
+    fn split(&self) -> Option<(&str, u64)> {
+        let dash = self.0.rfind('-')?;
+        let (name, number) = (&self.0[..dash], &self.0[dash + 1..]);
+        let valid = !name.is_empty()
+            && !number.is_empty()
+            && number.chars().all(|c| c.is_ascii_digit())
+            && (number == "0" || !number.starts_with('0'));
+        if !valid {
+            return None;
+        }
+        Some((name, number.parse().ok()?))
+    }
+}
+
+impl FromStr for ChainId {
+    type Err = Error;
+
+    /// a non empty chain id of at most 50 bytes without whitespace
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(Error::InputError("chain id is empty".to_string()));
+        }
+        if s.len() > MAX_CHAIN_ID_LENGTH || s.chars().any(char::is_whitespace) {
+            return Err(Error::InputError(format!("invalid chain id {:?}", s)));
+        }
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl fmt::Display for ChainId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<ChainId> for String {
+    fn from(chain_id: ChainId) -> String {
+        chain_id.0
+    }
+}
+
+impl Serialize for ChainId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ChainId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let chain_id = String::deserialize(deserializer)?;
+        chain_id.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_chain_id() {
+        let chain_id = ChainId::new_with_revision("crypto-org-chain-mainnet-1").unwrap();
+        assert_eq!(chain_id.name(), "crypto-org-chain-mainnet");
+        assert_eq!(chain_id.revision_number(), Some(1));
+
+        let chain_id: ChainId = "test".parse().unwrap();
+        assert_eq!(chain_id.name(), "test");
+        assert_eq!(chain_id.revision_number(), None);
+        assert!(ChainId::new_with_revision("test").is_err());
+        assert!(ChainId::new_with_revision("test-01").is_err());
+        assert!(ChainId::new_with_revision("-1").is_err());
+
+        assert!("".parse::<ChainId>().is_err());
+        assert!("test 1".parse::<ChainId>().is_err());
+        assert!("c".repeat(51).parse::<ChainId>().is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use crate::error::Error;
+use crate::key_service::SECP256K1_PUBKEY_TYPE_URL;
+use crate::message::{Msg, TypedMsg};
+use crate::proto::cosmos::crypto::secp256k1::PubKey;
+use crate::proto::cosmos::tx::v1beta1::{AuthInfo, Fee, TxBody, TxRaw};
+use crate::types::key::PublicKey;
+use secp256k1::PublicKey as InnerPublicKey;
+
+/// a signer declared in the `AuthInfo` of a tx
+#[derive(Debug, Clone)]
+pub struct TxSigner {
+    /// the secp256k1 public key, None for other key types (e.g. multisig)
+    pub public_key: Option<PublicKey>,
+    /// type url of the public key
+    pub public_key_type: String,
+    pub sequence: u64,
+}
+
+/// a protobuf `TxRaw` decoded into its body and auth info
+#[derive(Debug, Clone)]
+pub struct DecodedTx {
+    pub body: TxBody,
+    pub auth_info: AuthInfo,
+    pub signatures: Vec<Vec<u8>>,
+}
+
+impl DecodedTx {
+    /// decode the bytes of a `TxRaw`
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let tx_raw: TxRaw = prost::Message::decode(bytes)?;
+        Ok(Self {
+            body: prost::Message::decode(&*tx_raw.body_bytes)?,
+            auth_info: prost::Message::decode(&*tx_raw.auth_info_bytes)?,
+            signatures: tx_raw.signatures,
+        })
+    }
+
+    /// decode the base64 encoded `TxRaw`, e.g. the output of `TxBuilder::build`
+    pub fn decode_base64(tx_base64: &str) -> Result<Self, Error> {
+        let bytes = base64::decode(tx_base64.trim())
+            .map_err(|_e| Error::InputError("invalid base64 tx".to_string()))?;
+        Self::decode(&bytes)
+    }
+
+    /// the messages of the tx, the known type urls are resolved into typed messages
+    pub fn messages(&self) -> Result<Vec<TypedMsg>, Error> {
+        self.body
+            .messages
+            .iter()
+            .map(|any| Msg::from(any.clone()).decode_typed())
+            .collect()
+    }
+
+    pub fn memo(&self) -> &str {
+        &self.body.memo
+    }
+
+    pub fn fee(&self) -> Option<&Fee> {
+        self.auth_info.fee.as_ref()
+    }
+
+    /// the signers in the order of the signatures
+    pub fn signers(&self) -> Result<Vec<TxSigner>, Error> {
+        let mut signers = vec![];
+        for signer_info in &self.auth_info.signer_infos {
+            let (public_key, public_key_type) = match &signer_info.public_key {
+                Some(any) if any.type_url == SECP256K1_PUBKEY_TYPE_URL => {
+                    let pubkey: PubKey = prost::Message::decode(&*any.value)?;
+                    let inner = InnerPublicKey::from_slice(&pubkey.key)?;
+                    (Some(inner.into()), any.type_url.clone())
+                }
+                Some(any) => (None, any.type_url.clone()),
+                None => (None, String::new()),
+            };
+            signers.push(TxSigner {
+                public_key,
+                public_key_type,
+                sequence: signer_info.sequence,
+            });
+        }
+        Ok(signers)
+    }
+}
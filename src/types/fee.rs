@@ -0,0 +1,159 @@
+//! Fee of both the amino and the protobuf txs
+
+use crate::error::Error;
+use crate::proto::cosmos::base::v1beta1::Coin;
+use crate::proto::cosmos::tx::v1beta1::Fee as ProtoFee;
+use crate::types::address::AccountAddress;
+use crate::types::basic::{Amount, Fee, GasPrice};
+
+/// fee built from a gas limit and a gas price or explicit coins, e.g.
+/// `FeeBuilder::new().set_gas_limit(300000).set_gas_price("0.025basecro")?.build()?`
+#[derive(Debug, Clone, Default)]
+pub struct FeeBuilder {
+    gas_limit: Option<u64>,
+    gas_price: Option<GasPrice>,
+    amount: Vec<Amount>,
+    payer: Option<AccountAddress>,
+    granter: Option<AccountAddress>,
+}
+
+impl FeeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_gas_limit(&mut self, gas_limit: u64) -> &mut Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+
+    /// the fee amount is the gas limit at the gas price, e.g. `0.025basecro`
+    pub fn set_gas_price(&mut self, gas_price: &str) -> Result<&mut Self, Error> {
+        self.gas_price = Some(gas_price.parse()?);
+        Ok(self)
+    }
+
+    /// add a fee coin, one per denom
+    pub fn add_amount(&mut self, amount: Amount) -> &mut Self {
+        self.amount.push(amount);
+        self
+    }
+
+    /// the account which pays the fee, it must be one of the signers
+    pub fn set_payer(&mut self, payer: &str) -> Result<&mut Self, Error> {
+        self.payer = Some(payer.parse()?);
+        Ok(self)
+    }
+
+    /// the account which pays the fee from the fee allowance of the payer
+    pub fn set_granter(&mut self, granter: &str) -> Result<&mut Self, Error> {
+        self.granter = Some(granter.parse()?);
+        Ok(self)
+    }
+
+    /// the fee coins sorted by denom, as the sdk requires
+    fn coins(&self, gas_limit: u64) -> Result<Vec<Amount>, Error> {
+        let mut amount = self.amount.clone();
+        if let Some(gas_price) = &self.gas_price {
+            amount.extend(Fee::from_gas_price(gas_limit, gas_price)?.amount);
+        }
+        sort_fee_coins(amount)
+    }
+
+    fn gas_limit(&self) -> Result<u64, Error> {
+        match self.gas_limit {
+            Some(0) | None if self.gas_price.is_some() || !self.amount.is_empty() => Err(
+                Error::InputError("the fee needs a non zero gas limit".to_string()),
+            ),
+            gas_limit => Ok(gas_limit.unwrap_or_default()),
+        }
+    }
+
+    /// the fee of the protobuf tx
+    pub fn build(&self) -> Result<ProtoFee, Error> {
+        let gas_limit = self.gas_limit()?;
+        let amount = self.coins(gas_limit)?.iter().map(Coin::from).collect();
+        let to_string = |address: &Option<AccountAddress>| {
+            address
+                .as_ref()
+                .map(AccountAddress::to_string)
+                .unwrap_or_default()
+        };
+        Ok(ProtoFee {
+            amount,
+            gas_limit,
+            payer: to_string(&self.payer),
+            granter: to_string(&self.granter),
+        })
+    }
+
+    /// the fee of the amino tx, which has no payer and granter
+    pub fn build_amino(&self) -> Result<Fee, Error> {
+        if self.payer.is_some() || self.granter.is_some() {
+            return Err(Error::InputError(
+                "the amino fee has no payer and granter".to_string(),
+            ));
+        }
+        let gas = self.gas_limit()?;
+        Ok(Fee {
+            gas,
+            amount: self.coins(gas)?,
+        })
+    }
+}
+
+/// sort the fee coins by denom, the denoms must be different
+pub fn sort_fee_coins(mut amount: Vec<Amount>) -> Result<Vec<Amount>, Error> {
+    amount.sort_by(|a, b| a.denom().as_str().cmp(b.denom().as_str()));
+    if amount
+        .windows(2)
+        .any(|pair| pair[0].denom() == pair[1].denom())
+    {
+        return Err(Error::InputError("duplicate fee denom".to_string()));
+    }
+    Ok(amount)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::basic::Denom;
+
+    #[test]
+    fn test_fee_builder() {
+        let payer = "cro1u9q8mfpzhyv2s43js7l5qseapx5kt3g2rf7ppf";
+        let mut builder = FeeBuilder::new();
+        builder
+            .set_gas_limit(300000)
+            .set_gas_price("0.025basecro")
+            .unwrap()
+            .add_amount(Amount::new(10, Denom::Other("uatom".into())));
+        let amino = builder.build_amino().unwrap();
+        assert_eq!(amino.gas, 300000);
+        assert_eq!(
+            amino.amount,
+            vec![
+                Amount::new(7500, Denom::Basecro),
+                Amount::new(10, Denom::Other("uatom".into()))
+            ]
+        );
+
+        builder.set_payer(payer).unwrap();
+        assert!(builder.build_amino().is_err());
+        let fee = builder.build().unwrap();
+        assert_eq!(fee.gas_limit, 300000);
+        assert_eq!(fee.amount[0].amount, "7500");
+        assert_eq!(fee.payer, payer);
+        assert!(fee.granter.is_empty());
+
+        builder.add_amount(Amount::new(1, Denom::Basecro));
+        assert!(builder.build().is_err());
+        assert!(FeeBuilder::new()
+            .set_gas_price("0.025basecro")
+            .unwrap()
+            .build()
+            .is_err());
+        assert!(FeeBuilder::new().set_payer("cro1x").is_err());
+        assert_eq!(FeeBuilder::new().build().unwrap(), ProtoFee::default());
+    }
+}
@@ -1,21 +1,73 @@
 use crate::error::Error;
+use bech32::{FromBase32, ToBase32};
+use bitcoin_hashes::{ripemd160, sha256};
+use bitcoin_hashes::{Hash, HashEngine};
 use hdwallet::ExtendedPrivKey;
+use once_cell::sync::Lazy;
 use secp256k1::rand::Rng;
 use secp256k1::{All, Secp256k1};
 use secp256k1::{Error as SecpError, PublicKey as InnerPublicKey, SecretKey};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::string::ToString;
+use std::sync::atomic::{compiler_fence, Ordering};
+use stdtx::address::{Address, ADDRESS_SIZE};
+#[cfg(feature = "key-export")]
+use zeroize::Zeroizing;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
-/// Private key
-#[derive(Debug, Clone)]
+/// the secp256k1 context shared by the signing, the public key derivation and the
+/// verification, creating a context is much slower than using it
+pub(crate) static SECP256K1: Lazy<Secp256k1<All>> = Lazy::new(Secp256k1::new);
+
+/// Private key, zeroized on drop, the Debug output is redacted
+#[derive(Clone)]
 pub struct PrivateKey(SecretKey);
 
+impl std::fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PrivateKey(<redacted>)")
+    }
+}
+
+/// overwrite the key with `ONE_KEY`, a zero secret key is invalid
+pub(crate) fn zeroize_secret_key(secret_key: &mut SecretKey) {
+    // the volatile write and the fence keep the write from being optimized away
+    unsafe { std::ptr::write_volatile(secret_key, secp256k1::key::ONE_KEY) };
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// zeroize the secret key and the chain code of an extended key
+pub(crate) fn zeroize_extended_key(extended_key: &mut ExtendedPrivKey) {
+    zeroize_secret_key(&mut extended_key.private_key);
+    extended_key.chain_code.zeroize();
+}
+
+impl Zeroize for PrivateKey {
+    fn zeroize(&mut self) {
+        zeroize_secret_key(&mut self.0);
+    }
+}
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for PrivateKey {}
+
 /// public key
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PublicKey(InnerPublicKey);
 
+/// bech32 prefix of the account public keys
+pub const ACCOUNT_PUBKEY_PREFIX: &str = "cropub";
+/// amino prefix of `tendermint/PubKeySecp256k1` and the length of the key, which
+/// are prepended to the key in the legacy bech32 encoding
+const AMINO_SECP256K1_PREFIX: [u8; 5] = [0xeb, 0x5a, 0xe9, 0x87, 0x21];
+
 /// public key format used in transfer transaction
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct PublicKeyWrap {
     #[serde(rename = "type")]
     p_type: String,
@@ -31,12 +83,6 @@ impl From<PublicKey> for PublicKeyWrap {
     }
 }
 
-impl AsRef<SecretKey> for PrivateKey {
-    fn as_ref(&self) -> &SecretKey {
-        &self.0
-    }
-}
-
 impl PrivateKey {
     pub fn new<R: Rng + ?Sized>(rng: &mut R) -> Self {
         let secret_key = SecretKey::new(rng);
@@ -47,6 +93,20 @@ impl PrivateKey {
         let secret_key = SecretKey::from_slice(slice)?;
         Ok(Self(secret_key))
     }
+
+    /// the secret key for the signing and the encryption inside the crate,
+    /// `expose_secret` is the only way out of it
+    pub(crate) fn secret_key(&self) -> &SecretKey {
+        &self.0
+    }
+
+    /// the raw secret bytes, zeroized on drop, the caller is responsible for the secret
+    #[cfg(feature = "key-export")]
+    pub fn expose_secret(&self) -> Zeroizing<[u8; 32]> {
+        let mut raw = Zeroizing::new([0; 32]);
+        raw.copy_from_slice(&self.0[..]);
+        raw
+    }
 }
 
 impl PublicKey {
@@ -56,20 +116,117 @@ impl PublicKey {
         let inner = InnerPublicKey::from_slice(&raw)?;
         Ok(Self(inner))
     }
+
+    /// parse the hex of the compressed or uncompressed key, e.g. from an explorer
+    pub fn from_hex(pubkey_hex: &str) -> Result<Self, Error> {
+        let raw = hex::decode(pubkey_hex.trim_start_matches("0x"))
+            .map_err(|_e| Error::InputError("invalid publickey hex".to_string()))?;
+        Ok(Self(InnerPublicKey::from_slice(&raw)?))
+    }
+
+    /// hex of the compressed key
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.0.serialize()[..])
+    }
+
+    /// the legacy bech32 encoding of the amino key, e.g. `cropub1...`
+    pub fn to_bech32(&self, prefix: &str) -> Result<String, Error> {
+        let mut raw = AMINO_SECP256K1_PREFIX.to_vec();
+        raw.extend_from_slice(&self.0.serialize());
+        bech32::encode(prefix, raw.to_base32())
+            .map_err(|e| Error::SerializeError(format!("bech32 encode error: {}", e)))
+    }
+
+    /// parse the legacy bech32 encoding of the amino key, the prefix must be `prefix`
+    pub fn from_bech32(pubkey: &str, prefix: &str) -> Result<Self, Error> {
+        let invalid =
+            |reason: String| Error::InputError(format!("invalid bech32 publickey: {}", reason));
+        let (hrp, data) = bech32::decode(pubkey).map_err(|e| invalid(e.to_string()))?;
+        if hrp != prefix {
+            return Err(invalid(format!("prefix {}, expected {}", hrp, prefix)));
+        }
+        let raw = Vec::<u8>::from_base32(&data).map_err(|e| invalid(e.to_string()))?;
+        if !raw.starts_with(&AMINO_SECP256K1_PREFIX) {
+            return Err(invalid("not a secp256k1 key".to_string()));
+        }
+        Ok(Self(InnerPublicKey::from_slice(
+            &raw[AMINO_SECP256K1_PREFIX.len()..],
+        )?))
+    }
+
+    /// Address returns a Bitcoin style account addresses: RIPEMD160(SHA256(pubkey))
+    pub fn address(&self) -> Result<Address, Error> {
+        let pubkey_bytes = self.0.serialize();
+        let mut engine = sha256::Hash::engine();
+        engine.input(&pubkey_bytes);
+        let sha = sha256::Hash::from_engine(engine);
+        let mut engine = ripemd160::Hash::engine();
+        engine.input(sha.as_inner());
+        let raw = ripemd160::Hash::from_engine(engine);
+        let bits = raw.into_inner();
+        if bits.len() != ADDRESS_SIZE {
+            return Err(Error::InputError(
+                "invalid bits length to generate address".to_string(),
+            ));
+        }
+        let mut raw = [0; ADDRESS_SIZE];
+        raw.copy_from_slice(&bits);
+        Ok(raw.into())
+    }
+}
+
+impl PublicKey {
+    /// pack the key into an `Any` of `cosmos.crypto.secp256k1.PubKey`
+    pub fn to_any(&self) -> Result<prost_types::Any, Error> {
+        self.to_any_with_type_url(crate::key_service::SECP256K1_PUBKEY_TYPE_URL)
+    }
+
+    /// unpack the key from an `Any` of the `PubKey { key }` layout, e.g. from an account query,
+    /// only the secp256k1 and the ethermint keys are accepted
+    pub fn from_any(any: &prost_types::Any) -> Result<Self, Error> {
+        match any.type_url.as_str() {
+            crate::key_service::SECP256K1_PUBKEY_TYPE_URL
+            | crate::key_service::ETH_SECP256K1_PUBKEY_TYPE_URL => {}
+            type_url => {
+                return Err(Error::InputError(format!(
+                    "unsupported public key type {}",
+                    type_url
+                )))
+            }
+        }
+        let pubkey: crate::proto::cosmos::crypto::secp256k1::PubKey =
+            prost::Message::decode(&*any.value)?;
+        Ok(Self(InnerPublicKey::from_slice(&pubkey.key)?))
+    }
+
+    /// pack the key into an `Any` with the type url, the ethermint key
+    /// has the same `PubKey { key }` layout
+    pub fn to_any_with_type_url(&self, type_url: &str) -> Result<prost_types::Any, Error> {
+        let pubkey = crate::proto::cosmos::crypto::secp256k1::PubKey {
+            key: self.0.serialize().to_vec(),
+        };
+        let mut value = Vec::new();
+        prost::Message::encode(&pubkey, &mut value)?;
+        Ok(prost_types::Any {
+            type_url: type_url.to_string(),
+            value,
+        })
+    }
 }
 
 impl From<&PrivateKey> for PublicKey {
     fn from(private_key: &PrivateKey) -> Self {
-        let secp = Secp256k1::<All>::new();
-        let public_key_inner = InnerPublicKey::from_secret_key(&secp, &private_key.0);
+        let public_key_inner = InnerPublicKey::from_secret_key(&SECP256K1, &private_key.0);
         Self(public_key_inner)
     }
 }
 
 impl From<ExtendedPrivKey> for PrivateKey {
-    fn from(extended_priv_key: ExtendedPrivKey) -> Self {
-        let secret_key = extended_priv_key.private_key;
-        Self(secret_key)
+    /// the consumed extended key is zeroized
+    fn from(mut extended_priv_key: ExtendedPrivKey) -> Self {
+        let private_key = Self(extended_priv_key.private_key);
+        zeroize_extended_key(&mut extended_priv_key);
+        private_key
     }
 }
 
@@ -91,3 +248,54 @@ impl From<InnerPublicKey> for PublicKey {
         Self(inner_pubkey)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_zeroize() {
+        let mut private_key = PrivateKey::from_slice(&[7; 32]).unwrap();
+        private_key.zeroize();
+        assert_eq!(private_key.secret_key(), &secp256k1::key::ONE_KEY);
+    }
+
+    #[test]
+    fn test_redacted_debug() {
+        let private_key = PrivateKey::from_slice(&[7; 32]).unwrap();
+        let debug = format!("{:?}", private_key);
+        assert_eq!(debug, "PrivateKey(<redacted>)");
+        assert!(!debug.contains("0707"));
+    }
+
+    #[test]
+    fn test_public_key_encodings() {
+        let public_key = PublicKey::from(&PrivateKey::from_slice(&[7; 32]).unwrap());
+        let pubkey_hex = public_key.to_hex();
+        assert_eq!(pubkey_hex.len(), 66);
+        assert_eq!(PublicKey::from_hex(&pubkey_hex).unwrap(), public_key);
+        let uncompressed = hex::encode(&public_key.0.serialize_uncompressed()[..]);
+        assert_eq!(PublicKey::from_hex(&uncompressed).unwrap(), public_key);
+        assert!(PublicKey::from_hex("02zz").is_err());
+
+        let bech32 = public_key.to_bech32(ACCOUNT_PUBKEY_PREFIX).unwrap();
+        assert!(bech32.starts_with("cropub1addwnpepq"));
+        assert_eq!(
+            PublicKey::from_bech32(&bech32, ACCOUNT_PUBKEY_PREFIX).unwrap(),
+            public_key
+        );
+        assert!(PublicKey::from_bech32(&bech32, "tcropub").is_err());
+
+        let any = public_key.to_any().unwrap();
+        assert_eq!(PublicKey::from_any(&any).unwrap(), public_key);
+        let eth_any = public_key
+            .to_any_with_type_url(crate::key_service::ETH_SECP256K1_PUBKEY_TYPE_URL)
+            .unwrap();
+        assert_eq!(PublicKey::from_any(&eth_any).unwrap(), public_key);
+        // e.g. an ed25519 key with the same layout
+        let ed25519_any = public_key
+            .to_any_with_type_url("/cosmos.crypto.ed25519.PubKey")
+            .unwrap();
+        assert!(PublicKey::from_any(&ed25519_any).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::error::Error;
+use crate::utils::codec::deserde_u64_from_str_or_num;
+use bitcoin_hashes::{sha256, Hash};
+use serde::Deserialize;
+
+/// the txs in the mempool of a node, as the tendermint rpc `unconfirmed_txs`
+/// and `num_unconfirmed_txs` return them
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UnconfirmedTxs {
+    /// number of the returned txs
+    #[serde(rename = "n_txs", deserialize_with = "deserde_u64_from_str_or_num")]
+    pub count: u64,
+    /// number of all the txs in the mempool
+    #[serde(deserialize_with = "deserde_u64_from_str_or_num")]
+    pub total: u64,
+    #[serde(deserialize_with = "deserde_u64_from_str_or_num")]
+    pub total_bytes: u64,
+    /// base64 encoded txs, empty for `num_unconfirmed_txs`
+    #[serde(default, deserialize_with = "deserde_txs")]
+    pub txs: Vec<String>,
+}
+
+/// the txs are null in the response of `num_unconfirmed_txs`
+fn deserde_txs<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<Vec<String>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+impl UnconfirmedTxs {
+    /// parse the json rpc response of the tendermint rpc
+    pub fn from_json(response: &serde_json::Value) -> Result<Self, Error> {
+        if !response["error"].is_null() {
+            return Err(Error::ClientError(format!(
+                "query unconfirmed txs failed: {}",
+                response["error"]
+            )));
+        }
+        serde_json::from_value(response["result"].clone())
+            .map_err(|e| Error::SerializeError(format!("invalid unconfirmed txs: {}", e)))
+    }
+
+    /// whether all the txs of the mempool are returned
+    pub fn is_complete(&self) -> bool {
+        self.txs.len() as u64 >= self.total
+    }
+
+    /// upper case hex of SHA256(tx bytes) of the returned txs
+    pub fn tx_hashes(&self) -> Result<Vec<String>, Error> {
+        self.txs
+            .iter()
+            .map(|tx| {
+                let bytes = base64::decode(tx)
+                    .map_err(|e| Error::SerializeError(format!("invalid tx {}: {}", tx, e)))?;
+                Ok(hex::encode_upper(&sha256::Hash::hash(&bytes)[..]))
+            })
+            .collect()
+    }
+
+    /// whether the tx of the hash (upper or lower case hex) is one of the returned txs
+    pub fn contains(&self, hash: &str) -> Result<bool, Error> {
+        let hash = hash.to_uppercase();
+        Ok(self.tx_hashes()?.contains(&hash))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_json() {
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": -1,
+            "result": {
+                "n_txs": "1",
+                "total": "3",
+                "total_bytes": "702",
+                "txs": ["AQID"]
+            }
+        });
+        let txs = UnconfirmedTxs::from_json(&response).unwrap();
+        assert_eq!(txs.count, 1);
+        assert_eq!(txs.total, 3);
+        assert!(!txs.is_complete());
+        // sha256 of [1, 2, 3]
+        let hash = "039058C6F2C0CB492C533B0A4D14EF77CC0F78ABCCCED5287D84A1A2011CFB81";
+        assert_eq!(txs.tx_hashes().unwrap(), vec![hash.to_string()]);
+        assert!(txs.contains(&hash.to_lowercase()).unwrap());
+
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": -1,
+            "result": {"n_txs": "0", "total": "0", "total_bytes": "0", "txs": null}
+        });
+        let txs = UnconfirmedTxs::from_json(&response).unwrap();
+        assert!(txs.txs.is_empty());
+        assert!(txs.is_complete());
+
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": -1,
+            "error": {"code": -32603, "message": "Internal error"}
+        });
+        assert!(UnconfirmedTxs::from_json(&response).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::error::Error;
+use serde::Deserialize;
+
+/// the node serving the rest api and the chain it runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeInfo {
+    pub chain_id: String,
+    pub moniker: String,
+    /// version of tendermint
+    pub node_version: String,
+    /// version of the chain app, empty if the node doesn't report it
+    pub app_version: String,
+    /// true if the node is still catching up with the chain
+    pub syncing: bool,
+}
+
+#[derive(Deserialize)]
+struct DefaultNodeInfo {
+    network: String,
+    #[serde(default)]
+    moniker: String,
+    #[serde(default)]
+    version: String,
+}
+
+#[derive(Deserialize, Default)]
+struct ApplicationVersion {
+    #[serde(default)]
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct NodeInfoResponse {
+    default_node_info: DefaultNodeInfo,
+    #[serde(default)]
+    application_version: Option<ApplicationVersion>,
+}
+
+impl NodeInfo {
+    /// parse the response of `/cosmos/base/tendermint/v1beta1/node_info`
+    pub fn from_json(node_info: serde_json::Value, syncing: bool) -> Result<Self, Error> {
+        let response: NodeInfoResponse = serde_json::from_value(node_info)
+            .map_err(|e| Error::SerializeError(format!("invalid node info: {}", e)))?;
+        Ok(Self {
+            chain_id: response.default_node_info.network,
+            moniker: response.default_node_info.moniker,
+            node_version: response.default_node_info.version,
+            app_version: response.application_version.unwrap_or_default().version,
+            syncing,
+        })
+    }
+
+    /// whether the node encodes the keys and the values of the event attributes in base64,
+    /// as tendermint did before 0.35, true if the version can't be parsed
+    pub fn base64_event_attributes(&self) -> bool {
+        let mut parts = self
+            .node_version
+            .trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse::<u64>());
+        match (parts.next(), parts.next()) {
+            (Some(Ok(major)), Some(Ok(minor))) => (major, minor) < (0, 35),
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_json() {
+        let node_info = serde_json::json!({
+            "default_node_info": {
+                "protocol_version": {"p2p": "8", "block": "11", "app": "0"},
+                "default_node_id": "a1d5a3b0a8f1a7e8b3a6c2f1e0d9c8b7a6f5e4d3",
+                "listen_addr": "tcp://0.0.0.0:26656",
+                "network": "testnet-croeseid-2",
+                "version": "0.34.2",
+                "channels": "QCAhIiMwOGBhAA==",
+                "moniker": "node0",
+                "other": {"tx_index": "on", "rpc_address": "tcp://0.0.0.0:26657"}
+            },
+            "application_version": {
+                "name": "crypto-org-chain-chain",
+                "app_name": "chain-maind",
+                "version": "0.9.0",
+                "git_commit": "",
+                "build_tags": "",
+                "go_version": "go version go1.15.5 linux/amd64",
+                "build_deps": []
+            }
+        });
+        let info = NodeInfo::from_json(node_info, false).unwrap();
+        assert_eq!(info.chain_id, "testnet-croeseid-2");
+        assert_eq!(info.moniker, "node0");
+        assert_eq!(info.node_version, "0.34.2");
+        assert_eq!(info.app_version, "0.9.0");
+        assert!(!info.syncing);
+        assert!(info.base64_event_attributes());
+
+        let with_version = |node_version: &str| NodeInfo {
+            node_version: node_version.to_string(),
+            ..info.clone()
+        };
+        assert!(!with_version("0.37.2").base64_event_attributes());
+        assert!(!with_version("v0.38.0").base64_event_attributes());
+        assert!(with_version("v0.34.24").base64_event_attributes());
+        assert!(with_version("").base64_event_attributes());
+
+        assert!(NodeInfo::from_json(serde_json::json!({"code": 12}), false).is_err());
+    }
+}
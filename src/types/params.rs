@@ -0,0 +1,140 @@
+use crate::error::Error;
+use crate::utils::codec::deserde_u64_from_str_or_num;
+use serde::{de, Deserialize, Deserializer};
+use std::time::Duration;
+
+/// parameters of the auth module
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuthParams {
+    #[serde(deserialize_with = "deserde_u64_from_str_or_num")]
+    pub max_memo_characters: u64,
+    #[serde(deserialize_with = "deserde_u64_from_str_or_num")]
+    pub tx_sig_limit: u64,
+    #[serde(deserialize_with = "deserde_u64_from_str_or_num")]
+    pub tx_size_cost_per_byte: u64,
+    #[serde(deserialize_with = "deserde_u64_from_str_or_num")]
+    pub sig_verify_cost_ed25519: u64,
+    #[serde(deserialize_with = "deserde_u64_from_str_or_num")]
+    pub sig_verify_cost_secp256k1: u64,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SendEnabled {
+    pub denom: String,
+    pub enabled: bool,
+}
+
+/// parameters of the bank module
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BankParams {
+    /// the denoms which override `default_send_enabled`
+    #[serde(default)]
+    pub send_enabled: Vec<SendEnabled>,
+    pub default_send_enabled: bool,
+}
+
+impl BankParams {
+    /// whether the coins of the denom can be transferred
+    pub fn is_send_enabled(&self, denom: &str) -> bool {
+        self.send_enabled
+            .iter()
+            .find(|send_enabled| send_enabled.denom == denom)
+            .map(|send_enabled| send_enabled.enabled)
+            .unwrap_or(self.default_send_enabled)
+    }
+}
+
+/// parameters of the staking module
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StakingParams {
+    #[serde(deserialize_with = "deserde_duration")]
+    pub unbonding_time: Duration,
+    pub max_validators: u32,
+    pub max_entries: u32,
+    #[serde(default)]
+    pub historical_entries: u32,
+    pub bond_denom: String,
+}
+
+/// parse the protobuf json duration into whole seconds and nanoseconds,
+/// e.g. `1814400s` or `0.5s`, at most 9 fractional digits
+fn parse_duration(s: &str) -> Option<Duration> {
+    let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let mut parts = s.strip_suffix('s')?.splitn(2, '.');
+    let seconds = parts.next().filter(|seconds| is_digits(seconds))?;
+    let nanos = match parts.next() {
+        Some(fraction) if is_digits(fraction) && fraction.len() <= 9 => {
+            format!("{:0<9}", fraction).parse().ok()?
+        }
+        Some(_) => return None,
+        None => 0,
+    };
+    Some(Duration::new(seconds.parse().ok()?, nanos))
+}
+
+fn deserde_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    parse_duration(&s).ok_or_else(|| de::Error::custom(format!("invalid duration {}", s)))
+}
+
+/// parse the `params` of a params query response
+pub(crate) fn params_from_json<T: de::DeserializeOwned>(
+    response: &serde_json::Value,
+) -> Result<T, Error> {
+    serde_json::from_value(response["params"].clone())
+        .map_err(|e| Error::SerializeError(format!("invalid params {}: {}", response, e)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_params_from_json() {
+        let auth = serde_json::json!({"params": {"max_memo_characters": "256", "tx_sig_limit": "7", "tx_size_cost_per_byte": "10", "sig_verify_cost_ed25519": "590", "sig_verify_cost_secp256k1": "1000"}});
+        let auth: AuthParams = params_from_json(&auth).unwrap();
+        assert_eq!(auth.max_memo_characters, 256);
+        assert_eq!(auth.tx_size_cost_per_byte, 10);
+
+        let bank = serde_json::json!({"params": {"send_enabled": [{"denom": "basecro", "enabled": false}], "default_send_enabled": true}});
+        let bank: BankParams = params_from_json(&bank).unwrap();
+        assert!(!bank.is_send_enabled("basecro"));
+        assert!(bank.is_send_enabled("uatom"));
+
+        let staking = serde_json::json!({"params": {"unbonding_time": "1814400s", "max_validators": 100, "max_entries": 7, "historical_entries": 10000, "bond_denom": "basecro"}});
+        let staking: StakingParams = params_from_json(&staking).unwrap();
+        assert_eq!(staking.unbonding_time, Duration::from_secs(1814400));
+        assert_eq!(staking.bond_denom, "basecro");
+
+        let invalid = serde_json::json!({"params": {"unbonding_time": "21d", "max_validators": 100, "max_entries": 7, "bond_denom": "basecro"}});
+        assert!(params_from_json::<StakingParams>(&invalid).is_err());
+        // too large for the seconds, it must not panic
+        let invalid = serde_json::json!({"params": {"unbonding_time": "1e300s", "max_validators": 100, "max_entries": 7, "bond_denom": "basecro"}});
+        assert!(params_from_json::<StakingParams>(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("0.5s"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1.000000001s"), Some(Duration::new(1, 1)));
+        assert_eq!(
+            parse_duration("18446744073709551615s"),
+            Some(Duration::from_secs(u64::MAX))
+        );
+        for invalid in &[
+            "18446744073709551616s",
+            "1.0000000001s",
+            "-1s",
+            "infs",
+            "NaNs",
+            ".5s",
+            "1.s",
+            "1",
+        ] {
+            assert_eq!(parse_duration(invalid), None, "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,361 @@
+//! Partially signed tx of a multisig account, passed around the co-signers as a json file
+//! (like a bitcoin PSBT): each member signs the amino json sign doc, the files are merged
+//! and the tx is finalized once the threshold is reached
+
+use crate::error::Error;
+use crate::key_service::multisig_service::{compact_bit_array, MultisigKeyService};
+use crate::key_service::KeyService;
+use crate::proto::cosmos::crypto::multisig::v1beta1::MultiSignature;
+use crate::proto::cosmos::tx::v1beta1::{mode_info, AuthInfo, Fee, ModeInfo, SignerInfo, TxRaw};
+use crate::tx_builder::grpc::{BuiltTx, SignMode};
+use crate::types::key::{PublicKey, SECP256K1};
+use crate::utils::codec::{deserde_from_str, serde_to_str};
+use bitcoin_hashes::{sha256, Hash};
+use secp256k1::{Message, Signature};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// version of the `PartialTx` json format
+pub const PARTIAL_TX_VERSION: u32 = 1;
+
+/// the unsigned tx, the signer set and the collected signatures, the bytes are base64 encoded
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PartialTx {
+    pub version: u32,
+    pub chain_id: String,
+    #[serde(serialize_with = "serde_to_str", deserialize_with = "deserde_from_str")]
+    pub account_number: u64,
+    #[serde(serialize_with = "serde_to_str", deserialize_with = "deserde_from_str")]
+    pub sequence: u64,
+    pub threshold: u32,
+    /// the ordered base64 member public keys of the multisig account
+    pub public_keys: Vec<String>,
+    /// the amino json `StdSignDoc` signed by every member
+    pub sign_doc: String,
+    /// protobuf `TxBody`
+    pub body_bytes: String,
+    /// protobuf `Fee`
+    pub fee: String,
+    /// base64 signatures by the base64 member public key
+    #[serde(default)]
+    pub signatures: BTreeMap<String, String>,
+}
+
+fn decode_base64(value: &str, name: &str) -> Result<Vec<u8>, Error> {
+    base64::decode(value)
+        .map_err(|e| Error::SerializeError(format!("invalid base64 {}: {:?}", name, e)))
+}
+
+fn encode<T: prost::Message>(msg: &T) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![];
+    prost::Message::encode(msg, &mut buf)?;
+    Ok(buf)
+}
+
+impl PartialTx {
+    /// an unsigned partial tx, see `grpc::TxBuilder::partial_tx`
+    pub fn new(
+        multisig: &MultisigKeyService,
+        chain_id: String,
+        account_number: u64,
+        sequence: u64,
+        sign_doc: &[u8],
+        body_bytes: &[u8],
+        fee: &Fee,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            version: PARTIAL_TX_VERSION,
+            chain_id,
+            account_number,
+            sequence,
+            threshold: multisig.threshold(),
+            public_keys: multisig
+                .public_keys()
+                .iter()
+                .map(PublicKey::to_string)
+                .collect(),
+            sign_doc: base64::encode(sign_doc),
+            body_bytes: base64::encode(body_bytes),
+            fee: base64::encode(encode(fee)?),
+            signatures: BTreeMap::new(),
+        })
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let partial_tx: Self =
+            serde_json::from_str(json).map_err(|e| Error::SerializeError(e.to_string()))?;
+        if partial_tx.version != PARTIAL_TX_VERSION {
+            return Err(Error::SerializeError(format!(
+                "unsupported partial tx version: {}",
+                partial_tx.version
+            )));
+        }
+        // the file may come from anyone, check all the signatures
+        for (member, signature) in &partial_tx.signatures {
+            partial_tx.verify_member(member, signature)?;
+        }
+        Ok(partial_tx)
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(self).map_err(|e| Error::SerializeError(e.to_string()))
+    }
+
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let json = std::fs::read_to_string(path)?;
+        Self::from_json(&json)
+    }
+
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// the multisig key of the signer set, without member key services
+    pub fn multisig(&self) -> Result<MultisigKeyService, Error> {
+        let public_keys = self
+            .public_keys
+            .iter()
+            .map(|public_key| PublicKey::from_base64_str(public_key))
+            .collect::<Result<Vec<_>, Error>>()?;
+        MultisigKeyService::new(self.threshold, public_keys)
+    }
+
+    /// the bytes every member signs
+    pub fn sign_bytes(&self) -> Result<Vec<u8>, Error> {
+        decode_base64(&self.sign_doc, "sign doc")
+    }
+
+    /// sign with the key of a member
+    pub async fn sign<K: KeyService + ?Sized>(
+        &mut self,
+        key_service: &K,
+    ) -> Result<&mut Self, Error> {
+        let public_key = key_service.public_key().await?;
+        let signature = key_service.sign(&self.sign_bytes()?).await?;
+        self.add_signature(&public_key, signature)
+    }
+
+    /// add the base64 signature of a member made elsewhere (e.g. a ledger), it is verified
+    pub fn add_signature(
+        &mut self,
+        public_key: &PublicKey,
+        signature: String,
+    ) -> Result<&mut Self, Error> {
+        let member = public_key.to_string();
+        self.verify_member(&member, &signature)?;
+        self.signatures.insert(member, signature);
+        Ok(self)
+    }
+
+    /// check that the base64 public key is a member and signed the sign doc
+    fn verify_member(&self, member: &str, signature: &str) -> Result<(), Error> {
+        if !self
+            .public_keys
+            .iter()
+            .any(|public_key| public_key == member)
+        {
+            return Err(Error::InputError(format!(
+                "{} is not a multisig member",
+                member
+            )));
+        }
+        self.verify(&PublicKey::from_base64_str(member)?, signature)
+    }
+
+    fn verify(&self, public_key: &PublicKey, signature: &str) -> Result<(), Error> {
+        let hash = sha256::Hash::hash(&self.sign_bytes()?);
+        let message = Message::from_slice(&hash[..])?;
+        let signature = Signature::from_compact(&decode_base64(signature, "signature")?)?;
+        SECP256K1
+            .verify(&message, &signature, public_key.as_ref())
+            .map_err(|_| Error::InputError("invalid signature of the sign doc".to_string()))
+    }
+
+    /// add the signatures of the same tx signed by the other members
+    pub fn merge(&mut self, other: &PartialTx) -> Result<&mut Self, Error> {
+        let unsigned = |tx: &PartialTx| PartialTx {
+            signatures: BTreeMap::new(),
+            ..tx.clone()
+        };
+        if unsigned(self) != unsigned(other) {
+            return Err(Error::InputError(
+                "the partial txs are not the same tx".to_string(),
+            ));
+        }
+        for (member, signature) in &other.signatures {
+            match self.signatures.get(member) {
+                Some(existing) if existing != signature => {
+                    return Err(Error::InputError(format!(
+                        "conflicting signatures of {}",
+                        member
+                    )))
+                }
+                Some(_) => {}
+                None => {
+                    // the file may come from anyone, check the signature again
+                    self.verify_member(member, signature)?;
+                    self.signatures.insert(member.clone(), signature.clone());
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// number of the members which signed, the entries of other keys are not counted
+    pub fn signed_members(&self) -> usize {
+        self.public_keys
+            .iter()
+            .filter(|member| self.signatures.contains_key(*member))
+            .count()
+    }
+
+    /// whether enough members signed
+    pub fn is_complete(&self) -> bool {
+        self.signed_members() >= self.threshold as usize
+    }
+
+    /// the `TxRaw` signed by the multisig account, the signatures are in the member order
+    pub fn finalize(&self) -> Result<BuiltTx, Error> {
+        if !self.is_complete() {
+            return Err(Error::InputError(format!(
+                "{} signatures collected, threshold is {}",
+                self.signed_members(),
+                self.threshold
+            )));
+        }
+        let mut indexes = vec![];
+        let mut signatures = vec![];
+        for (index, member) in self.public_keys.iter().enumerate() {
+            if let Some(signature) = self.signatures.get(member) {
+                indexes.push(index);
+                signatures.push(decode_base64(signature, "signature")?);
+            }
+        }
+        let single = ModeInfo {
+            sum: Some(mode_info::Sum::Single(mode_info::Single {
+                mode: SignMode::LegacyAminoJson.as_i32(),
+            })),
+        };
+        let mode_info = ModeInfo {
+            sum: Some(mode_info::Sum::Multi(mode_info::Multi {
+                bitarray: Some(compact_bit_array(self.public_keys.len(), &indexes)),
+                mode_infos: vec![single; indexes.len()],
+            })),
+        };
+        let fee: Fee = prost::Message::decode(decode_base64(&self.fee, "fee")?.as_slice())?;
+        let auth_info = AuthInfo {
+            signer_infos: vec![SignerInfo {
+                public_key: Some(self.multisig()?.pk_any()?),
+                mode_info: Some(mode_info),
+                sequence: self.sequence,
+            }],
+            fee: Some(fee),
+        };
+        let tx_raw = TxRaw {
+            body_bytes: decode_base64(&self.body_bytes, "body bytes")?,
+            auth_info_bytes: encode(&auth_info)?,
+            signatures: vec![encode(&MultiSignature { signatures })?],
+        };
+        Ok(BuiltTx::new(encode(&tx_raw)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hd_wallet::mnemonic::Mnemonic;
+    use crate::key_service::private_key_service::PrivateKeyService;
+    use crate::proto::cosmos::base::v1beta1::Coin;
+    use crate::tx_builder::grpc::TxBuilder;
+
+    #[tokio::test]
+    async fn test_partial_tx() {
+        let words = "dune car envelope chuckle elbow slight proud fury remove candy uphold puzzle call select sibling sport gadget please want vault glance verb damage gown";
+        let mnemonic = Mnemonic::from_str(words, None).unwrap();
+        let members: Vec<_> = (0..3)
+            .map(|index| PrivateKeyService::new(mnemonic.derive(0, 0, index).unwrap()))
+            .collect();
+        let mut public_keys = vec![];
+        for member in &members {
+            public_keys.push(member.public_key().await.unwrap());
+        }
+        let multisig = MultisigKeyService::new(2, public_keys).unwrap();
+        let fee = Fee {
+            amount: vec![],
+            gas_limit: 200000,
+            payer: "".to_string(),
+            granter: "".to_string(),
+        };
+        let mut builder = TxBuilder::new(
+            multisig.clone(),
+            "test".parse().unwrap(),
+            None,
+            0,
+            Some(fee),
+        );
+        let amount = Coin {
+            denom: "basecro".to_string(),
+            amount: 1.to_string(),
+        };
+        let msg = builder
+            .create_msg("cro1s2gsnugjhpzac8m7necv3527jp28z9w002najd".into(), amount)
+            .await
+            .unwrap();
+        builder
+            .add_message(msg)
+            .set_account_number(1)
+            .set_sequence(2);
+        let unsigned = builder.partial_tx(&multisig).unwrap();
+
+        let mut first = PartialTx::from_json(&unsigned.to_json().unwrap()).unwrap();
+        first.sign(&members[0]).await.unwrap();
+        assert!(!first.is_complete());
+        assert!(first.finalize().is_err());
+        let mut second = unsigned.clone();
+        second.sign(&members[2]).await.unwrap();
+        first.merge(&second).unwrap();
+        assert!(first.is_complete());
+
+        let built = first.finalize().unwrap();
+        let tx_raw: TxRaw = prost::Message::decode(built.bytes.as_slice()).unwrap();
+        let multi_signature: MultiSignature =
+            prost::Message::decode(tx_raw.signatures[0].as_slice()).unwrap();
+        assert_eq!(multi_signature.signatures.len(), 2);
+        let auth_info: AuthInfo =
+            prost::Message::decode(tx_raw.auth_info_bytes.as_slice()).unwrap();
+        assert_eq!(auth_info.signer_infos[0].sequence, 2);
+
+        let outsider = PrivateKeyService::new(mnemonic.derive(0, 0, 3).unwrap());
+        assert!(second.sign(&outsider).await.is_err());
+        let forged = first.signatures[&first.public_keys[0]].clone();
+        let member = PublicKey::from_base64_str(&first.public_keys[1]).unwrap();
+        assert!(second.add_signature(&member, forged.clone()).is_err());
+        let mut other = unsigned.clone();
+        other.sequence = 3;
+        assert!(first.merge(&other).is_err());
+
+        // a valid signature of a non member doesn't count and is rejected from the files
+        let mut tampered = unsigned.clone();
+        tampered.sign(&members[0]).await.unwrap();
+        let outsider_key = outsider.public_key().await.unwrap().to_string();
+        let outsider_signature = outsider
+            .sign(&unsigned.sign_bytes().unwrap())
+            .await
+            .unwrap();
+        tampered.signatures.insert(outsider_key, outsider_signature);
+        assert_eq!(tampered.signed_members(), 1);
+        assert!(!tampered.is_complete());
+        assert!(tampered.finalize().is_err());
+        assert!(PartialTx::from_json(&tampered.to_json().unwrap()).is_err());
+        assert!(first.clone().merge(&tampered).is_err());
+
+        // an invalid signature of a member is rejected from the files
+        let mut forged_tx = unsigned.clone();
+        forged_tx
+            .signatures
+            .insert(first.public_keys[1].clone(), forged);
+        assert!(PartialTx::from_json(&forged_tx.to_json().unwrap()).is_err());
+    }
+}
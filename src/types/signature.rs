@@ -1,11 +1,15 @@
 use crate::types::key::PublicKeyWrap;
-use serde::Serialize;
+use crate::utils::codec::deserde_u64_from_str_or_num;
+use serde::{Deserialize, Serialize};
 
 /// Signature used in Tx
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Signature {
     pub signature: String,
     pub pub_key: PublicKeyWrap,
+    /// not included by the newer gaiacli exports
+    #[serde(default, deserialize_with = "deserde_u64_from_str_or_num")]
     pub account_number: u64,
+    #[serde(default, deserialize_with = "deserde_u64_from_str_or_num")]
     pub sequence: u64,
 }
@@ -1,19 +1,83 @@
+use crate::error::Error;
 use crate::types::basic::{Fee, SyncMode};
 use crate::types::signature::Signature;
-use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// amino type of the StdTx
+pub const STD_TX_TYPE: &str = "cosmos-sdk/StdTx";
 
 /// tx in transfer transaction
-#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Tx<M: Serialize> {
     #[serde(rename = "msg")]
     pub messages: Vec<M>,
     pub fee: Fee,
     pub memo: String,
+    #[serde(default, deserialize_with = "deserialize_signatures")]
     pub signatures: Vec<Signature>,
 }
 
+/// an unsigned tx exported by gaiacli has `"signatures": null`
+fn deserialize_signatures<'de, D>(deserializer: D) -> Result<Vec<Signature>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<Vec<Signature>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// the StdTx json exported by gaiacli/chain-maind (`--generate-only`, `tx sign`)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StdTx<M: Serialize> {
+    #[serde(rename = "type")]
+    pub tx_type: String,
+    pub value: Tx<M>,
+}
+
+impl<M: Serialize + DeserializeOwned> Tx<M> {
+    /// load the tx from the StdTx json or the bare tx json
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| Error::SerializeError(e.to_string()))?;
+        let tx = if value.get("type").is_some() && value.get("value").is_some() {
+            serde_json::from_value::<StdTx<M>>(value).map(|std_tx| std_tx.value)
+        } else {
+            serde_json::from_value(value)
+        };
+        tx.map_err(|e| Error::SerializeError(e.to_string()))
+    }
+
+    /// the StdTx json which can be loaded by gaiacli/chain-maind
+    pub fn to_json(&self) -> Result<String, Error>
+    where
+        M: Clone,
+    {
+        let std_tx = StdTx {
+            tx_type: STD_TX_TYPE.to_string(),
+            value: self.clone(),
+        };
+        serde_json::to_string(&std_tx).map_err(|e| Error::SerializeError(e.to_string()))
+    }
+
+    /// load the tx from a StdTx json file
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let json = std::fs::read_to_string(path)?;
+        Self::from_json(&json)
+    }
+
+    /// write the tx into a StdTx json file
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error>
+    where
+        M: Clone,
+    {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+}
+
 /// transfer transaction
-#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Transaction<M: Serialize> {
     pub tx: Tx<M>,
     pub mode: SyncMode,
@@ -0,0 +1,152 @@
+use crate::error::BroadcastError;
+use crate::proto::cosmos::base::abci::v1beta1::TxResponse as ProtoTxResponse;
+use crate::proto::tendermint::abci::Event as ProtoEvent;
+use crate::utils::codec::deserde_u64_from_str_or_num;
+use serde::Deserialize;
+
+/// result of a tx included in a block (or checked by the node)
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TxResponse {
+    #[serde(default, deserialize_with = "deserde_u64_from_str_or_num")]
+    pub height: u64,
+    pub txhash: String,
+    #[serde(default)]
+    pub codespace: String,
+    /// 0 if the tx succeeded
+    #[serde(default)]
+    pub code: u32,
+    #[serde(default)]
+    pub raw_log: String,
+    #[serde(default, deserialize_with = "deserde_u64_from_str_or_num")]
+    pub gas_wanted: u64,
+    #[serde(default, deserialize_with = "deserde_u64_from_str_or_num")]
+    pub gas_used: u64,
+}
+
+impl TxResponse {
+    pub fn is_success(&self) -> bool {
+        self.code == 0
+    }
+
+    /// the reason of the failure, None if the tx succeeded
+    pub fn error(&self) -> Option<BroadcastError> {
+        BroadcastError::from_code(&self.codespace, self.code, &self.raw_log)
+    }
+}
+
+impl From<ProtoTxResponse> for TxResponse {
+    fn from(response: ProtoTxResponse) -> Self {
+        Self {
+            height: response.height as u64,
+            txhash: response.txhash,
+            codespace: response.codespace,
+            code: response.code,
+            raw_log: response.raw_log,
+            gas_wanted: response.gas_wanted as u64,
+            gas_used: response.gas_used as u64,
+        }
+    }
+}
+
+/// an abci event with the attributes decoded into strings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub event_type: String,
+    pub attributes: Vec<(String, String)>,
+}
+
+impl From<ProtoEvent> for Event {
+    fn from(event: ProtoEvent) -> Self {
+        Self {
+            event_type: event.r#type,
+            attributes: event
+                .attributes
+                .into_iter()
+                .map(|attribute| {
+                    (
+                        String::from_utf8_lossy(&attribute.key).to_string(),
+                        String::from_utf8_lossy(&attribute.value).to_string(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Event {
+    /// parse the event of the rest api, the attributes are base64 encoded by the nodes before
+    /// tendermint 0.35 (see `NodeInfo::base64_event_attributes`), an attribute which is not
+    /// valid base64 is kept as it is
+    pub fn from_json(value: &serde_json::Value, base64_attributes: bool) -> Self {
+        let decode = |value: &serde_json::Value| {
+            let s = value.as_str().unwrap_or_default();
+            if !base64_attributes {
+                return s.to_string();
+            }
+            base64::decode(s)
+                .ok()
+                .and_then(|raw| String::from_utf8(raw).ok())
+                .unwrap_or_else(|| s.to_string())
+        };
+        let attributes = value["attributes"]
+            .as_array()
+            .map(|attributes| {
+                attributes
+                    .iter()
+                    .map(|attribute| (decode(&attribute["key"]), decode(&attribute["value"])))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            event_type: value["type"].as_str().unwrap_or_default().to_string(),
+            attributes,
+        }
+    }
+}
+
+/// result of a simulated tx
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulateResponse {
+    pub gas_wanted: u64,
+    pub gas_used: u64,
+    pub events: Vec<Event>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let json = r#"{"height":"120","txhash":"0A1B","codespace":"","code":0,"data":"0A060A0473656E64","raw_log":"[]","logs":[],"info":"","gas_wanted":"200000","gas_used":"65003","tx":null,"timestamp":"2021-01-01T00:00:00Z"}"#;
+        let response: TxResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.height, 120);
+        assert_eq!(response.gas_used, 65003);
+        assert!(response.is_success());
+    }
+
+    #[test]
+    fn test_event_from_json() {
+        let event = serde_json::json!({
+            "type": "message",
+            "attributes": [{"key": "YWN0aW9u", "value": "c2VuZA=="}],
+        });
+        let decoded = Event::from_json(&event, true);
+        assert_eq!(
+            decoded.attributes,
+            vec![("action".to_string(), "send".to_string())]
+        );
+
+        // a plain value which happens to be valid base64 is not decoded
+        let event = serde_json::json!({
+            "type": "transfer",
+            "attributes": [{"key": "sender", "value": "abcd"}],
+        });
+        let plain = Event::from_json(&event, false);
+        assert_eq!(plain.event_type, "transfer");
+        assert_eq!(
+            plain.attributes,
+            vec![("sender".to_string(), "abcd".to_string())]
+        );
+    }
+}
@@ -1,5 +1,6 @@
-use serde::Serializer;
+use serde::{de, Deserialize, Deserializer, Serializer};
 use std::fmt;
+use std::str::FromStr;
 
 /// serde from <T> which impled `Display` to string
 pub fn serde_to_str<T, S>(value: &T, serializer: S) -> std::result::Result<S::Ok, S::Error>
@@ -9,3 +10,113 @@ where
 {
     serializer.serialize_str(&*value.to_string())
 }
+
+/// deserde <T> which impled `FromStr` from string
+pub fn deserde_from_str<'de, T, D>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    s.parse().map_err(de::Error::custom)
+}
+
+/// deserde u64 from a string or a number, both appear in the exported amino json
+pub fn deserde_u64_from_str_or_num<'de, D>(deserializer: D) -> std::result::Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StrOrNum {
+        Str(String),
+        Num(u64),
+    }
+    match StrOrNum::deserialize(deserializer)? {
+        StrOrNum::Str(s) => s.parse().map_err(de::Error::custom),
+        StrOrNum::Num(n) => Ok(n),
+    }
+}
+
+/// encode the json in the cosmos canonical form of the amino sign docs: the object keys
+/// are sorted, no insignificant whitespace and the strings are escaped as go `json.Marshal`
+pub fn canonical_json(value: &serde_json::Value) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &serde_json::Value, out: &mut String) {
+    use serde_json::Value;
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => out.push_str(&n.to_string()),
+        Value::String(s) => write_canonical_str(s, out),
+        Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(value, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical_str(key, out);
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_canonical_str(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            // go escapes the html characters and the js line separators
+            '<' | '>' | '&' | '\u{2028}' | '\u{2029}' => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_canonical_json() {
+        let value = json!({
+            "memo": "a memo with spaces <&>\n\u{1}",
+            "chain_id": "test",
+            "fee": {"gas": "200000", "amount": []},
+            "msgs": [{"value": {"b": 1, "a": null}, "type": "t"}],
+        });
+        assert_eq!(
+            canonical_json(&value),
+            r#"{"chain_id":"test","fee":{"amount":[],"gas":"200000"},"memo":"a memo with spaces \u003c\u0026\u003e\n\u0001","msgs":[{"type":"t","value":{"a":null,"b":1}}]}"#
+        );
+    }
+}